@@ -0,0 +1,11 @@
+CREATE TABLE users (
+    id            TEXT PRIMARY KEY,
+    email         TEXT        NOT NULL,
+    name          TEXT        NOT NULL,
+    password_hash TEXT        NOT NULL,
+    is_active     BOOLEAN     NOT NULL DEFAULT TRUE,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX users_email_key ON users (lower(email));
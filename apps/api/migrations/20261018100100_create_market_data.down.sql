@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS sentiment_items;
+DROP TABLE IF EXISTS macro_observations;
+DROP TABLE IF EXISTS macro_indicators;
+DROP TABLE IF EXISTS price_bars;
+DROP TABLE IF EXISTS assets;
@@ -0,0 +1,49 @@
+CREATE TABLE assets (
+    id          TEXT PRIMARY KEY,
+    symbol      TEXT        NOT NULL UNIQUE,
+    name        TEXT        NOT NULL,
+    asset_class TEXT        NOT NULL,
+    currency    TEXT        NOT NULL,
+    exchange    TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE price_bars (
+    asset_id TEXT             NOT NULL REFERENCES assets (id) ON DELETE CASCADE,
+    bar_date DATE             NOT NULL,
+    open     DOUBLE PRECISION NOT NULL,
+    high     DOUBLE PRECISION NOT NULL,
+    low      DOUBLE PRECISION NOT NULL,
+    close    DOUBLE PRECISION NOT NULL,
+    volume   BIGINT           NOT NULL,
+    PRIMARY KEY (asset_id, bar_date)
+);
+
+CREATE TABLE macro_indicators (
+    code   TEXT PRIMARY KEY,
+    name   TEXT NOT NULL,
+    unit   TEXT NOT NULL,
+    source TEXT NOT NULL
+);
+
+CREATE TABLE macro_observations (
+    indicator_code TEXT             NOT NULL REFERENCES macro_indicators (code) ON DELETE CASCADE,
+    country_code   TEXT             NOT NULL,
+    observed_on    DATE             NOT NULL,
+    value          DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (indicator_code, country_code, observed_on)
+);
+
+CREATE TABLE sentiment_items (
+    id           TEXT PRIMARY KEY,
+    source       TEXT             NOT NULL,
+    external_id  TEXT             NOT NULL,
+    query        TEXT             NOT NULL DEFAULT '',
+    text         TEXT             NOT NULL,
+    score        DOUBLE PRECISION NOT NULL,
+    metrics      JSONB,
+    published_at TIMESTAMPTZ      NOT NULL,
+    UNIQUE (source, external_id)
+);
+
+CREATE INDEX sentiment_items_published_at_idx ON sentiment_items (published_at DESC);
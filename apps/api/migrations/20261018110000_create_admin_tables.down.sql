@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS jobs;
+DROP TABLE IF EXISTS signing_keys;
+DROP TABLE IF EXISTS auth_sessions;
+DROP TABLE IF EXISTS api_keys;
+DROP TABLE IF EXISTS user_roles;
+DROP TABLE IF EXISTS roles;
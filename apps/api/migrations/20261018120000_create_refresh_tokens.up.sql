@@ -0,0 +1,11 @@
+-- Each auth_sessions row is a refresh-token family. Tokens are single use:
+-- presenting one that already has `used_at` set revokes the whole family.
+CREATE TABLE refresh_tokens (
+    token_hash TEXT PRIMARY KEY,
+    session_id TEXT        NOT NULL REFERENCES auth_sessions (id) ON DELETE CASCADE,
+    issued_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at    TIMESTAMPTZ
+);
+
+CREATE INDEX refresh_tokens_session_id_idx ON refresh_tokens (session_id);
@@ -0,0 +1,25 @@
+CREATE TABLE permissions (
+    name        TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE role_permissions (
+    role       TEXT NOT NULL REFERENCES roles (name) ON DELETE CASCADE,
+    permission TEXT NOT NULL REFERENCES permissions (name) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission)
+);
+
+-- `*` grants every permission, including ones added later.
+INSERT INTO permissions (name, description) VALUES
+    ('*', 'Every permission'),
+    ('users:read', 'View any user account'),
+    ('users:write', 'Create, modify and delete any user account'),
+    ('roles:manage', 'Grant and revoke roles'),
+    ('api_keys:manage', 'Issue and revoke API keys'),
+    ('sessions:manage', 'List and revoke any user''s sessions'),
+    ('assets:read', 'Read market data'),
+    ('assets:write', 'Modify market data');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', '*'),
+    ('user', 'assets:read');
@@ -0,0 +1,3 @@
+DELETE FROM permissions WHERE name IN ('ingest:assets', 'ingest:sentiment');
+
+ALTER TABLE api_keys DROP COLUMN IF EXISTS replaces;
@@ -0,0 +1,7 @@
+-- A rotated key points at the key it replaces; the old key keeps working
+-- until its (shortened) expiry so clients can switch over.
+ALTER TABLE api_keys ADD COLUMN replaces TEXT REFERENCES api_keys (id) ON DELETE SET NULL;
+
+INSERT INTO permissions (name, description) VALUES
+    ('ingest:assets', 'Push market data from ingestion clients'),
+    ('ingest:sentiment', 'Push sentiment items from ingestion clients');
@@ -0,0 +1,31 @@
+-- Whether the session was established with a second factor.
+ALTER TABLE auth_sessions ADD COLUMN mfa_verified BOOLEAN NOT NULL DEFAULT false;
+
+-- A row without `enabled_at` is an enrollment awaiting its confirmation code.
+CREATE TABLE user_mfa (
+    user_id        TEXT PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    secret         TEXT        NOT NULL,
+    enabled_at     TIMESTAMPTZ,
+    -- Last accepted TOTP time step; a code is never accepted twice.
+    last_used_step BIGINT,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE mfa_recovery_codes (
+    code_hash  TEXT PRIMARY KEY,
+    user_id    TEXT        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    used_at    TIMESTAMPTZ
+);
+
+CREATE INDEX mfa_recovery_codes_user_id_idx ON mfa_recovery_codes (user_id);
+
+-- Issued after a correct password for an account with 2FA; exchanged for
+-- tokens together with a valid code.
+CREATE TABLE mfa_challenges (
+    token_hash  TEXT PRIMARY KEY,
+    user_id     TEXT        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    attempts    INTEGER     NOT NULL DEFAULT 0,
+    consumed_at TIMESTAMPTZ
+);
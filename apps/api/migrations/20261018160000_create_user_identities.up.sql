@@ -0,0 +1,27 @@
+-- External OIDC identities linked to local accounts. One identity per
+-- provider per user; `subject` is the provider's stable `sub` claim.
+CREATE TABLE user_identities (
+    provider      TEXT        NOT NULL,
+    subject       TEXT        NOT NULL,
+    user_id       TEXT        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    email         TEXT,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_login_at TIMESTAMPTZ,
+    PRIMARY KEY (provider, subject),
+    UNIQUE (user_id, provider)
+);
+
+-- An authorization request in flight: the PKCE verifier and nonce that the
+-- callback must match. `link_user_id` is set when an authenticated user is
+-- linking a new identity rather than signing in.
+CREATE TABLE oidc_login_states (
+    state_hash    TEXT PRIMARY KEY,
+    provider      TEXT        NOT NULL,
+    code_verifier TEXT        NOT NULL,
+    nonce         TEXT        NOT NULL,
+    link_user_id  TEXT REFERENCES users (id) ON DELETE CASCADE,
+    expires_at    TIMESTAMPTZ NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX oidc_login_states_expires_at_idx ON oidc_login_states (expires_at);
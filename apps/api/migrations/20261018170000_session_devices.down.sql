@@ -0,0 +1,4 @@
+ALTER TABLE auth_sessions
+    DROP COLUMN IF EXISTS ip_address,
+    DROP COLUMN IF EXISTS user_agent,
+    DROP COLUMN IF EXISTS device_label;
@@ -0,0 +1,7 @@
+-- Device details shown when users review their sessions. `ip_address` and
+-- `user_agent` reflect the latest login or refresh; `last_seen_at` also
+-- advances while access tokens of the session are in use.
+ALTER TABLE auth_sessions
+    ADD COLUMN device_label TEXT,
+    ADD COLUMN user_agent   TEXT,
+    ADD COLUMN ip_address   TEXT;
@@ -0,0 +1,14 @@
+-- Failed password logins per account (normalized email) and per source IP.
+-- A row is blocked while `blocked_until` is in the future; `locked_at` marks
+-- blocks that reached the lockout threshold rather than a progressive delay.
+CREATE TABLE login_throttles (
+    scope           TEXT        NOT NULL CHECK (scope IN ('account', 'ip')),
+    key             TEXT        NOT NULL,
+    failures        INTEGER     NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    blocked_until   TIMESTAMPTZ,
+    locked_at       TIMESTAMPTZ,
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX login_throttles_last_failure_idx ON login_throttles (last_failure_at);
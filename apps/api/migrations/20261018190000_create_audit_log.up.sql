@@ -0,0 +1,43 @@
+-- Security-relevant actions. Rows are chained per UTC day: `hash` covers the
+-- row and the `prev_hash` of the row before it (seq - 1), so editing,
+-- removing or reordering a row breaks every later hash of that day. The
+-- triggers below make the table append-only for everyone but its owner
+-- dropping it.
+CREATE TABLE audit_log (
+    id          BIGINT      GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    day         DATE        NOT NULL,
+    seq         INTEGER     NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    event       TEXT        NOT NULL,
+    actor_type  TEXT        NOT NULL,
+    actor_id    TEXT,
+    subject     TEXT,
+    ip_address  TEXT,
+    details     JSONB       NOT NULL DEFAULT '{}',
+    prev_hash   TEXT        NOT NULL,
+    hash        TEXT        NOT NULL,
+    UNIQUE (day, seq)
+);
+
+CREATE INDEX audit_log_occurred_at_idx ON audit_log (occurred_at);
+CREATE INDEX audit_log_event_idx ON audit_log (event, occurred_at);
+CREATE INDEX audit_log_actor_idx ON audit_log (actor_id, occurred_at) WHERE actor_id IS NOT NULL;
+CREATE INDEX audit_log_subject_idx ON audit_log (subject, occurred_at) WHERE subject IS NOT NULL;
+
+CREATE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update_delete
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_log_append_only();
+
+INSERT INTO permissions (name, description) VALUES
+    ('audit:read', 'Search the security audit log');
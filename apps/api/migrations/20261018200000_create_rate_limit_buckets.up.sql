@@ -0,0 +1,9 @@
+-- GCRA state for the postgres rate-limit store: one theoretical arrival
+-- time per client and route group. Losing it on a crash only resets the
+-- limits, so the table skips the WAL.
+CREATE UNLOGGED TABLE rate_limit_buckets (
+    key TEXT        PRIMARY KEY,
+    tat TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX rate_limit_buckets_tat_idx ON rate_limit_buckets (tat);
@@ -0,0 +1,3 @@
+DELETE FROM permissions WHERE name = 'network:manage';
+
+DROP TABLE IF EXISTS network_policies;
@@ -0,0 +1,18 @@
+-- Named allow/deny lists of CIDR ranges, each guarding the route groups it
+-- lists. Instances cache them and re-read the table periodically.
+
+CREATE TABLE IF NOT EXISTS network_policies (
+    name TEXT PRIMARY KEY,
+    description TEXT,
+    allow CIDR[] NOT NULL DEFAULT '{}',
+    deny CIDR[] NOT NULL DEFAULT '{}',
+    route_groups TEXT[] NOT NULL DEFAULT '{}',
+    trust_forwarded_for BOOLEAN NOT NULL DEFAULT TRUE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_by TEXT
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('network:manage', 'Manage network access policies');
@@ -0,0 +1,7 @@
+-- Encrypted secrets cannot be decrypted in SQL; those users have to enroll
+-- in 2FA again.
+DELETE FROM user_mfa WHERE secret IS NULL;
+
+ALTER TABLE user_mfa DROP CONSTRAINT IF EXISTS user_mfa_secret_present;
+ALTER TABLE user_mfa DROP COLUMN IF EXISTS secret_encrypted;
+ALTER TABLE user_mfa ALTER COLUMN secret SET NOT NULL;
@@ -0,0 +1,8 @@
+-- TOTP secrets are now written encrypted (see EncryptedColumn in
+-- infrastructure/security/encryption.rs). Existing plaintext secrets stay
+-- in `secret`, and keep working, until `cli encryption re-encrypt` moves
+-- them into `secret_encrypted`.
+ALTER TABLE user_mfa ADD COLUMN secret_encrypted BYTEA;
+ALTER TABLE user_mfa ALTER COLUMN secret DROP NOT NULL;
+ALTER TABLE user_mfa ADD CONSTRAINT user_mfa_secret_present
+    CHECK (secret IS NOT NULL OR secret_encrypted IS NOT NULL);
@@ -0,0 +1,10 @@
+-- Nonces of verified signed requests, for the postgres nonce store. Each
+-- is kept until its request's timestamp leaves the allowed clock skew.
+-- Unlike rate-limit buckets, losing these would reopen a replay window,
+-- so the table is logged.
+CREATE TABLE request_nonces (
+    nonce      TEXT        PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX request_nonces_expires_at_idx ON request_nonces (expires_at);
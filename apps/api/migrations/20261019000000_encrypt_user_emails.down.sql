@@ -0,0 +1,18 @@
+-- Encrypted emails cannot be decrypted in SQL, and users cannot do without
+-- one; refuse rather than delete accounts. Identity emails are refreshed
+-- from the provider at the next sign-in.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM users WHERE email IS NULL) THEN
+        RAISE EXCEPTION 'users have encrypted emails only; cannot restore the plaintext column';
+    END IF;
+END
+$$;
+
+ALTER TABLE user_identities DROP COLUMN IF EXISTS email_encrypted;
+
+DROP INDEX IF EXISTS users_email_bidx_key;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_present;
+ALTER TABLE users DROP COLUMN IF EXISTS email_bidx;
+ALTER TABLE users DROP COLUMN IF EXISTS email_encrypted;
+ALTER TABLE users ALTER COLUMN email SET NOT NULL;
@@ -0,0 +1,12 @@
+-- User and linked-identity emails are now written encrypted (see
+-- EncryptedColumn in infrastructure/security/encryption.rs); user emails
+-- also get a blind index for lookups by email. Existing plaintext emails stay in
+-- `email`, and keep working, until `cli encryption re-encrypt` moves them.
+ALTER TABLE users ADD COLUMN email_encrypted BYTEA;
+ALTER TABLE users ADD COLUMN email_bidx BYTEA;
+ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+ALTER TABLE users ADD CONSTRAINT users_email_present
+    CHECK (email IS NOT NULL OR (email_encrypted IS NOT NULL AND email_bidx IS NOT NULL));
+CREATE UNIQUE INDEX users_email_bidx_key ON users (email_bidx);
+
+ALTER TABLE user_identities ADD COLUMN email_encrypted BYTEA;
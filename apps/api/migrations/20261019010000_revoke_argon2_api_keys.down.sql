@@ -0,0 +1,2 @@
+-- The revoked keys' secrets are not recoverable; nothing to undo.
+SELECT 1;
@@ -0,0 +1,6 @@
+-- API key secrets are only checked against HMAC digests now. Keys still
+-- carrying an argon2 hash from before digests can no longer authenticate;
+-- revoke them so listings say so. Issue replacements with `cli api-keys`.
+UPDATE api_keys
+SET revoked_at = now()
+WHERE revoked_at IS NULL AND secret_hash NOT LIKE 'hmac-sha256$%';
@@ -0,0 +1,2 @@
+UPDATE api_keys SET scopes = array_remove(scopes, 'ingest:macro');
+DELETE FROM permissions WHERE name = 'ingest:macro';
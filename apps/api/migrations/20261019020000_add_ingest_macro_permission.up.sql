@@ -0,0 +1,11 @@
+-- Macro series get their own permission instead of riding on
+-- `ingest:assets`. Keys and roles that could push them keep doing so.
+INSERT INTO permissions (name, description) VALUES
+    ('ingest:macro', 'Push macro-economic series from ingestion clients');
+
+UPDATE api_keys SET scopes = array_append(scopes, 'ingest:macro')
+WHERE 'ingest:assets' = ANY(scopes) AND NOT 'ingest:macro' = ANY(scopes);
+
+INSERT INTO role_permissions (role, permission)
+SELECT role, 'ingest:macro' FROM role_permissions WHERE permission = 'ingest:assets'
+ON CONFLICT DO NOTHING;
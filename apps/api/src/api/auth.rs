@@ -0,0 +1,329 @@
+use axum::body::Body;
+use axum::extract::{ConnectInfo, FromRequestParts, OptionalFromRequestParts, OriginalUri, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, Extensions, HeaderMap};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+use crate::bootstrap::AppState;
+use crate::common::constants::JWT_TOKEN_PREFIX;
+use crate::common::errors::{AppError, Result};
+use crate::infrastructure::security::api_keys::{looks_like_api_key, ApiKey};
+use crate::infrastructure::security::audit::Actor;
+use crate::infrastructure::security::network::client_ip;
+use crate::infrastructure::security::rbac::allows;
+use crate::infrastructure::security::request_signing::{RequestVerifier, SignatureError};
+use crate::infrastructure::security::sessions::ClientInfo;
+
+/// Optional header naming the device a session is started from, e.g.
+/// "Work laptop". Shown when the user reviews their sessions.
+pub const DEVICE_LABEL_HEADER: &str = "x-m5-device-label";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrincipalKind {
+    User,
+    ApiKey,
+}
+
+/// The authenticated caller of a request: a user holding an access token,
+/// or a machine client holding an API key.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthUser {
+    /// User id, or API key id for machine clients.
+    pub id: String,
+    pub kind: PrincipalKind,
+    /// Session the access token belongs to.
+    pub session_id: Option<String>,
+    pub roles: Vec<String>,
+    /// Effective permissions: granted through roles for users, fixed at
+    /// issue time for API keys.
+    pub scopes: BTreeSet<String>,
+    /// Whether the session was verified with a second factor.
+    pub mfa_verified: bool,
+    /// Roles held but inactive until the session is verified with 2FA.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending_mfa_roles: Vec<String>,
+}
+
+impl AuthUser {
+    fn from_api_key(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            kind: PrincipalKind::ApiKey,
+            session_id: None,
+            roles: Vec::new(),
+            scopes: key.scopes.into_iter().collect(),
+            mfa_verified: false,
+            pending_mfa_roles: Vec::new(),
+        }
+    }
+
+    /// The user id, refusing machine clients.
+    pub fn user_id(&self) -> Result<&str> {
+        match self.kind {
+            PrincipalKind::User => Ok(&self.id),
+            PrincipalKind::ApiKey => Err(AppError::Authorization(
+                "this endpoint is only available to users".to_string(),
+            )),
+        }
+    }
+
+    /// Who to record in the audit log for this caller's actions.
+    pub fn actor(&self) -> Actor {
+        match self.kind {
+            PrincipalKind::User => Actor::User(self.id.clone()),
+            PrincipalKind::ApiKey => Actor::ApiKey(self.id.clone()),
+        }
+    }
+
+    pub fn can(&self, permission: &str) -> bool {
+        allows(&self.scopes, permission)
+    }
+
+    pub fn require(&self, permission: &str) -> Result<()> {
+        if self.can(permission) {
+            Ok(())
+        } else {
+            Err(AppError::Authorization(format!("missing permission `{}`", permission)))
+        }
+    }
+
+    /// Passes when acting on one's own record or holding `permission`.
+    pub fn require_self_or(&self, user_id: &str, permission: &str) -> Result<()> {
+        if self.kind == PrincipalKind::User && self.id == user_id {
+            Ok(())
+        } else {
+            self.require(permission)
+        }
+    }
+}
+
+/// `Ok(None)` when the request carries no credentials; an error when it
+/// carries credentials that do not check out.
+pub async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<Option<AuthUser>> {
+    let Some(value) = headers.get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let token = value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix(JWT_TOKEN_PREFIX))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| AppError::Authentication("expected a bearer token".to_string()))?;
+
+    if looks_like_api_key(token) {
+        let key = state
+            .auth
+            .api_keys()
+            .authenticate(token)
+            .await?
+            .ok_or_else(|| AppError::Authentication("invalid API key".to_string()))?;
+        if !key.is_active() {
+            return Err(AppError::Authentication("API key is revoked or expired".to_string()));
+        }
+        return Ok(Some(AuthUser::from_api_key(key)));
+    }
+
+    let claims = state.auth.tokens().verify(token).await?;
+    if !state.auth.session_active(&claims.sid).await? {
+        return Err(AppError::Authentication("session has been revoked".to_string()));
+    }
+    let mfa_verified = claims.mfa_verified();
+    let withhold: &[String] = if mfa_verified {
+        &[]
+    } else {
+        &state.config.auth.mfa.required_roles
+    };
+    let grants = state
+        .auth
+        .rbac()
+        .grants_for(&claims.sub, withhold)
+        .await?
+        .ok_or_else(|| AppError::Authentication("account is disabled".to_string()))?;
+
+    Ok(Some(AuthUser {
+        id: claims.sub,
+        kind: PrincipalKind::User,
+        session_id: Some(claims.sid),
+        roles: grants.roles,
+        scopes: grants.permissions,
+        mfa_verified,
+        pending_mfa_roles: grants.withheld,
+    }))
+}
+
+/// Resolves the caller once per request and stores it in the request
+/// extensions for extractors, guards and the GraphQL context.
+pub async fn authentication(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    match authenticate(&state, request.headers()).await {
+        Ok(Some(user)) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        Ok(None) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Verifies HMAC request signatures. Signed requests are checked on any
+/// route; unsigned ones pass unless they were made with an API key and
+/// `auth.request_signing.require_for_api_keys` is set. Must run after
+/// [`authentication`].
+pub async fn request_signature(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let verifier = state.auth.request_signing();
+    if !RequestVerifier::is_signed(request.headers()) {
+        let is_api_key = request
+            .extensions()
+            .get::<AuthUser>()
+            .is_some_and(|user| user.kind == PrincipalKind::ApiKey);
+        if is_api_key && verifier.require_for_api_keys() {
+            return AppError::from(SignatureError::Required).into_response();
+        }
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, verifier.max_body_bytes()).await else {
+        return AppError::from(SignatureError::BodyTooLarge).into_response();
+    };
+
+    // Nested routers see a stripped path; the client signed the full one.
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map_or(&parts.uri, |original| &original.0);
+    let path_and_query = uri.path_and_query().map_or(uri.path(), |pq| pq.as_str());
+
+    match verifier
+        .verify(
+            parts.method.as_str(),
+            path_and_query,
+            &parts.headers,
+            &body,
+            chrono::Utc::now().timestamp(),
+        )
+        .await
+    {
+        Ok(signed) => {
+            parts.extensions.insert(signed);
+            next.run(Request::from_parts(parts, Body::from(body))).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self> {
+        <Self as OptionalFromRequestParts<AppState>>::from_request_parts(parts, state)
+            .await?
+            .ok_or_else(|| AppError::Authentication("missing bearer token".to_string()))
+    }
+}
+
+impl OptionalFromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Option<Self>> {
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(Some(user.clone()));
+        }
+        authenticate(state, &parts.headers).await
+    }
+}
+
+/// The client's address, resolved through `app.trusted_proxies`. Only known
+/// when the server runs with connect info.
+pub fn request_ip(extensions: &Extensions, headers: &HeaderMap, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| client_ip(addr.ip(), headers, trusted_proxies))
+}
+
+/// The device details recorded on sessions.
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> std::result::Result<Self, Infallible> {
+        let header = |name| parts.headers.get(name).and_then(|value| value.to_str().ok());
+        let ip_address = request_ip(&parts.extensions, &parts.headers, &state.config.app.trusted_proxies)
+            .map(|ip| ip.to_string());
+        Ok(ClientInfo::new(
+            header(DEVICE_LABEL_HEADER),
+            header(header::USER_AGENT.as_str()),
+            ip_address,
+        ))
+    }
+}
+
+/// Route layer that rejects callers lacking `permission`. Relies on the
+/// [`authentication`] middleware having run first.
+///
+/// ```ignore
+/// Router::new().route("/", get(list)).route_layer(require_permission(permissions::USERS_READ))
+/// ```
+pub fn require_permission(permission: &'static str) -> RequirePermission {
+    RequirePermission { permission }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission {
+    permission: &'static str,
+}
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.permission,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: &'static str,
+}
+
+impl<S> Service<Request<Body>> for RequirePermissionService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, std::result::Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let verdict = match request.extensions().get::<AuthUser>() {
+            Some(user) => user.require(self.permission),
+            None => Err(AppError::Authentication("missing bearer token".to_string())),
+        };
+        if let Err(e) = verdict {
+            return Box::pin(async move { Ok(e.into_response()) });
+        }
+
+        // The clone that was polled ready is the one that must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(request).await })
+    }
+}
@@ -0,0 +1,26 @@
+use async_graphql::{Context, ErrorExtensions, Guard};
+
+use crate::api::auth::AuthUser;
+use crate::bootstrap::AppState;
+use crate::common::errors::AppError;
+
+/// The application state registered on the schema by [`super::schema::build`].
+pub fn app_state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<AppState>()
+}
+
+/// The caller, attached per request by the GraphQL handler.
+pub fn current_user<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a AuthUser> {
+    ctx.data_opt::<AuthUser>()
+        .ok_or_else(|| AppError::Authentication("missing bearer token".to_string()).extend())
+}
+
+/// Field guard mirroring the REST `require_permission` layer:
+/// `#[graphql(guard = "PermissionGuard(permissions::USERS_READ)")]`.
+pub struct PermissionGuard(pub &'static str);
+
+impl Guard for PermissionGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        current_user(ctx)?.require(self.0).map_err(|e| e.extend())
+    }
+}
@@ -0,0 +1,72 @@
+pub mod context;
+pub mod resolvers;
+pub mod schema;
+
+use async_graphql::ErrorExtensions;
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::routing::get;
+use axum::{Extension, Router};
+
+use crate::api::auth::AuthUser;
+use crate::bootstrap::AppState;
+use crate::common::errors::AppError;
+
+use self::schema::AppSchema;
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/graphql", get(execute).post(execute))
+        .layer(Extension(schema::build(state.clone())))
+}
+
+/// Anonymous requests are executed too; resolvers decide what needs a
+/// principal via [`context::PermissionGuard`] or [`context::current_user`].
+async fn execute(
+    Extension(schema): Extension<AppSchema>,
+    user: Option<AuthUser>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner();
+    if let Some(user) = user {
+        request = request.data(user);
+    }
+    schema.execute(request).await.into()
+}
+
+/// Exposes the same error codes and messages as the REST API, with the code
+/// under `extensions.code`, plus field-level details for validation
+/// failures.
+impl ErrorExtensions for AppError {
+    fn extend(&self) -> async_graphql::Error {
+        self.log_internal();
+        async_graphql::Error::new(self.public_message()).extend_with(|_, extensions| {
+            let code = match self {
+                AppError::Authentication(_) => "UNAUTHENTICATED",
+                AppError::Authorization(_) => "FORBIDDEN",
+                AppError::Validation(_) | AppError::InvalidInput(_) => "BAD_USER_INPUT",
+                AppError::NotFound(_) => "NOT_FOUND",
+                AppError::Conflict(_) => "CONFLICT",
+                AppError::RateLimit(_) => "RATE_LIMITED",
+                AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+                AppError::Database { .. } | AppError::ExternalService { .. } | AppError::Internal(_) => {
+                    "INTERNAL_SERVER_ERROR"
+                }
+            };
+            extensions.set("code", code);
+            if let AppError::Validation(errors) = self {
+                let fields: Vec<async_graphql::Value> = errors
+                    .iter()
+                    .map(|error| {
+                        async_graphql::Value::from_json(serde_json::json!({
+                            "field": error.field,
+                            "code": error.code,
+                            "message": error.message,
+                        }))
+                        .unwrap_or_default()
+                    })
+                    .collect();
+                extensions.set("validation_errors", fields);
+            }
+        })
+    }
+}
@@ -0,0 +1,9 @@
+pub mod users;
+
+use async_graphql::MergedObject;
+
+#[derive(MergedObject, Default)]
+pub struct QueryRoot(users::UserQuery);
+
+#[derive(MergedObject, Default)]
+pub struct MutationRoot(users::UserMutation);
@@ -0,0 +1,82 @@
+use async_graphql::{Context, ErrorExtensions, Object, Result, SimpleObject, ID};
+
+use crate::api::graphql::context::{app_state, current_user, PermissionGuard};
+use crate::api::http::users::apply_update;
+use crate::application::command::{CommandHandler, QueryHandler};
+use crate::common::types::Pagination;
+use crate::features::users::application::commands::DeleteUser;
+use crate::features::users::application::dtos::{
+    CreateUserRequest, ListUsersQuery, UpdateUserRequest, UserResponse,
+};
+use crate::features::users::application::queries::GetUser;
+use crate::infrastructure::security::rbac::permissions;
+
+#[derive(SimpleObject)]
+pub struct UserPage {
+    pub data: Vec<UserResponse>,
+    pub pagination: Pagination,
+}
+
+#[derive(Default)]
+pub struct UserQuery;
+
+#[Object]
+impl UserQuery {
+    async fn user(&self, ctx: &Context<'_>, id: ID) -> Result<UserResponse> {
+        current_user(ctx)?
+            .require_self_or(&id, permissions::USERS_READ)
+            .map_err(|e| e.extend())?;
+        app_state(ctx)
+            .users
+            .get_user()
+            .handle(GetUser { id: id.to_string() })
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    #[graphql(guard = "PermissionGuard(permissions::USERS_READ)")]
+    async fn users(&self, ctx: &Context<'_>, page: Option<u32>, per_page: Option<u32>) -> Result<UserPage> {
+        let result = app_state(ctx)
+            .users
+            .list_users()
+            .handle(ListUsersQuery { page, per_page })
+            .await
+            .map_err(|e| e.extend())?;
+        Ok(UserPage {
+            data: result.data,
+            pagination: result.pagination,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+impl UserMutation {
+    async fn create_user(&self, ctx: &Context<'_>, input: CreateUserRequest) -> Result<UserResponse> {
+        app_state(ctx)
+            .users
+            .create_user()
+            .handle(input)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    async fn update_user(&self, ctx: &Context<'_>, id: ID, input: UpdateUserRequest) -> Result<UserResponse> {
+        apply_update(app_state(ctx), current_user(ctx)?, id.to_string(), input)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    #[graphql(guard = "PermissionGuard(permissions::USERS_WRITE)")]
+    async fn delete_user(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        app_state(ctx)
+            .users
+            .delete_user()
+            .handle(DeleteUser { id: id.to_string() })
+            .await
+            .map_err(|e| e.extend())?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,12 @@
+use async_graphql::{EmptySubscription, Schema};
+
+use super::resolvers::{MutationRoot, QueryRoot};
+use crate::bootstrap::AppState;
+
+pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn build(state: AppState) -> AppSchema {
+    Schema::build(QueryRoot::default(), MutationRoot::default(), EmptySubscription)
+        .data(state)
+        .finish()
+}
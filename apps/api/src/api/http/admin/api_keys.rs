@@ -0,0 +1,147 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::auth::{require_permission, AuthUser};
+use crate::bootstrap::AppState;
+use crate::common::errors::{AppError, Result};
+use crate::infrastructure::security::api_keys::{ApiKey, IssuedApiKey, DEFAULT_ROTATION_OVERLAP, MAX_ROTATION_OVERLAP};
+use crate::infrastructure::security::audit::AuditEvent;
+use crate::infrastructure::security::sessions::ClientInfo;
+use crate::infrastructure::security::rbac::permissions;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_keys).post(issue_key))
+        .route("/{key}", get(get_key).delete(revoke_key))
+        .route("/{key}/rotate", post(rotate_key))
+        .route_layer(require_permission(permissions::API_KEYS_MANAGE))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListKeysQuery {
+    /// Include revoked and expired keys.
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueKeyRequest {
+    name: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RotateKeyRequest {
+    /// How long the old key keeps working; defaults to 24 hours, at most 30 days.
+    overlap_secs: Option<i64>,
+}
+
+/// The one response that carries a plaintext key.
+#[derive(Debug, Serialize)]
+struct IssuedKeyResponse {
+    #[serde(flatten)]
+    key: ApiKey,
+    token: String,
+}
+
+impl From<IssuedApiKey> for IssuedKeyResponse {
+    fn from(issued: IssuedApiKey) -> Self {
+        Self {
+            token: issued.token.expose().clone(),
+            key: issued.key,
+        }
+    }
+}
+
+async fn list_keys(State(state): State<AppState>, Query(query): Query<ListKeysQuery>) -> Result<Json<Vec<ApiKey>>> {
+    Ok(Json(state.auth.api_keys().list(query.all).await?))
+}
+
+async fn get_key(State(state): State<AppState>, Path(key): Path<String>) -> Result<Json<ApiKey>> {
+    let key = state
+        .auth
+        .api_keys()
+        .get(&key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("API key `{}`", key)))?;
+    Ok(Json(key))
+}
+
+async fn issue_key(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    client: ClientInfo,
+    Json(request): Json<IssueKeyRequest>,
+) -> Result<(StatusCode, Json<IssuedKeyResponse>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("name must not be empty".to_string()));
+    }
+    if request.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(AppError::InvalidInput("expires_at must be in the future".to_string()));
+    }
+    let store = state.auth.api_keys();
+    if let Some(unknown) = store.unknown_scopes(&request.scopes).await?.first() {
+        return Err(AppError::InvalidInput(format!("unknown scope `{}`", unknown)));
+    }
+
+    let issued = store.issue(name, &request.scopes, request.expires_at).await?;
+    state
+        .audit
+        .record(AuditEvent::api_key_issued(&issued.key).by(caller.actor()).from_ip(client.ip_address)).await;
+    Ok((StatusCode::CREATED, Json(issued.into())))
+}
+
+async fn rotate_key(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    client: ClientInfo,
+    Path(key): Path<String>,
+    request: Option<Json<RotateKeyRequest>>,
+) -> Result<(StatusCode, Json<IssuedKeyResponse>)> {
+    let overlap = match request.and_then(|Json(request)| request.overlap_secs) {
+        Some(secs) => Duration::try_seconds(secs)
+            .filter(|overlap| *overlap >= Duration::zero() && *overlap <= MAX_ROTATION_OVERLAP)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "overlap_secs must be between 0 and {}",
+                    MAX_ROTATION_OVERLAP.num_seconds()
+                ))
+            })?,
+        None => DEFAULT_ROTATION_OVERLAP,
+    };
+    let issued = state
+        .auth
+        .api_keys()
+        .rotate(&key, overlap)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("active API key `{}`", key)))?;
+    state
+        .audit
+        .record(AuditEvent::api_key_issued(&issued.key).by(caller.actor()).from_ip(client.ip_address)).await;
+    Ok((StatusCode::CREATED, Json(issued.into())))
+}
+
+async fn revoke_key(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    client: ClientInfo,
+    Path(key): Path<String>,
+) -> Result<Json<ApiKey>> {
+    let revoked = state
+        .auth
+        .api_keys()
+        .revoke(&key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("unrevoked API key `{}`", key)))?;
+    state
+        .audit
+        .record(AuditEvent::api_key_revoked(&revoked).by(caller.actor()).from_ip(client.ip_address)).await;
+    Ok(Json(revoked))
+}
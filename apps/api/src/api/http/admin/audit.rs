@@ -0,0 +1,24 @@
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::auth::require_permission;
+use crate::bootstrap::AppState;
+use crate::common::errors::Result;
+use crate::infrastructure::security::audit::{AuditFilter, AuditRecord};
+use crate::infrastructure::security::rbac::permissions;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/audit", get(search_audit_log))
+        .route_layer(require_permission(permissions::AUDIT_READ))
+}
+
+/// Newest entries first. Page with `before`, the last `id` of the previous
+/// page; `event` takes a name or a prefix such as `auth.*`.
+async fn search_audit_log(
+    State(state): State<AppState>,
+    Query(filter): Query<AuditFilter>,
+) -> Result<Json<Vec<AuditRecord>>> {
+    Ok(Json(state.audit.store().search(&filter).await?))
+}
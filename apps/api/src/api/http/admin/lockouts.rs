@@ -0,0 +1,48 @@
+use axum::extract::{Path, State};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+
+use crate::api::auth::{require_permission, AuthUser};
+use crate::application::command::CommandHandler;
+use crate::bootstrap::AppState;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::{Unlock, UnlockLogin, UnlockResponse};
+use crate::infrastructure::security::lockout::ThrottleEntry;
+use crate::infrastructure::security::rbac::permissions;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/lockouts", get(list_lockouts))
+        .route("/lockouts/ips/{ip}", delete(unlock_ip))
+        .route("/users/{user}/unlock", post(unlock_user))
+        .route_layer(require_permission(permissions::USERS_WRITE))
+}
+
+/// Accounts and source IPs locked out after repeated failed logins.
+async fn list_lockouts(State(state): State<AppState>) -> Result<Json<Vec<ThrottleEntry>>> {
+    Ok(Json(state.auth.login_throttle().list_locked().await?))
+}
+
+async fn unlock_user(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    Path(user_id): Path<String>,
+) -> Result<Json<UnlockResponse>> {
+    let command = UnlockLogin {
+        target: Unlock::User { user_id },
+        actor_id: Some(caller.id),
+    };
+    Ok(Json(state.auth.unlock_login().handle(command).await?))
+}
+
+async fn unlock_ip(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    Path(ip_address): Path<String>,
+) -> Result<Json<UnlockResponse>> {
+    let command = UnlockLogin {
+        target: Unlock::Ip { ip_address },
+        actor_id: Some(caller.id),
+    };
+    Ok(Json(state.auth.unlock_login().handle(command).await?))
+}
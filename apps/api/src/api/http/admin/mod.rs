@@ -0,0 +1,19 @@
+pub mod api_keys;
+pub mod audit;
+pub mod lockouts;
+pub mod network_policies;
+pub mod sessions;
+
+use axum::Router;
+
+use crate::bootstrap::AppState;
+
+/// Operator endpoints. Each group carries its own permission guard.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .nest("/api-keys", api_keys::routes())
+        .nest("/network-policies", network_policies::routes())
+        .merge(sessions::routes())
+        .merge(lockouts::routes())
+        .merge(audit::routes())
+}
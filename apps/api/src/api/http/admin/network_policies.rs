@@ -0,0 +1,160 @@
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::HeaderMap;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use ipnet::IpNet;
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+use crate::api::auth::{require_permission, AuthUser};
+use crate::bootstrap::AppState;
+use crate::common::constants::ROUTE_GROUPS;
+use crate::common::errors::{AppError, Result};
+use crate::common::validation::validate_slug;
+use crate::infrastructure::security::audit::AuditEvent;
+use crate::infrastructure::security::network::{client_ip, parse_network, NetworkPolicy, NetworkPolicySpec};
+use crate::infrastructure::security::rbac::permissions;
+use crate::infrastructure::security::sessions::ClientInfo;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_policies))
+        .route("/{name}", get(get_policy).put(save_policy).delete(delete_policy))
+        .route_layer(require_permission(permissions::NETWORK_MANAGE))
+}
+
+#[derive(Debug, Deserialize)]
+struct SavePolicyRequest {
+    description: Option<String>,
+    /// Addresses or CIDR ranges, IPv4 or IPv6.
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    route_groups: Vec<String>,
+    /// Defaults to true.
+    trust_forwarded_for: Option<bool>,
+    /// Defaults to true.
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SavePolicyQuery {
+    /// Save an admin policy even though it denies the caller.
+    #[serde(default)]
+    force: bool,
+}
+
+impl SavePolicyRequest {
+    fn into_spec(self) -> Result<NetworkPolicySpec> {
+        let networks = |values: Vec<String>, field: &str| {
+            values
+                .iter()
+                .map(|value| {
+                    parse_network(value).ok_or_else(|| {
+                        AppError::InvalidInput(format!("{}: `{}` is not an IP address or CIDR range", field, value))
+                    })
+                })
+                .collect::<Result<Vec<IpNet>>>()
+        };
+        let allow = networks(self.allow, "allow")?;
+        let deny = networks(self.deny, "deny")?;
+        if allow.is_empty() && deny.is_empty() {
+            return Err(AppError::InvalidInput("a policy needs an allow or deny range".to_string()));
+        }
+        if let Some(group) = self.route_groups.iter().find(|group| !ROUTE_GROUPS.contains(&group.as_str())) {
+            return Err(AppError::InvalidInput(format!(
+                "unknown route group `{}`; expected one of {}",
+                group,
+                ROUTE_GROUPS.join(", ")
+            )));
+        }
+        Ok(NetworkPolicySpec {
+            description: self.description.filter(|description| !description.trim().is_empty()),
+            allow,
+            deny,
+            route_groups: self.route_groups,
+            trust_forwarded_for: self.trust_forwarded_for.unwrap_or(true),
+            enabled: self.enabled.unwrap_or(true),
+        })
+    }
+}
+
+async fn list_policies(State(state): State<AppState>) -> Result<Json<Vec<NetworkPolicy>>> {
+    Ok(Json(state.network_policies.store().list().await?))
+}
+
+async fn get_policy(State(state): State<AppState>, Path(name): Path<String>) -> Result<Json<NetworkPolicy>> {
+    let policy = state
+        .network_policies
+        .store()
+        .get(&name)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("network policy `{}`", name)))?;
+    Ok(Json(policy))
+}
+
+/// Creates or replaces a policy; it applies to this instance at once and
+/// to the others within `app.network_policy_refresh_secs`.
+async fn save_policy(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(query): Query<SavePolicyQuery>,
+    Json(request): Json<SavePolicyRequest>,
+) -> Result<Json<NetworkPolicy>> {
+    if validate_slug(&name).is_err() {
+        return Err(AppError::InvalidInput(
+            "name must be lowercase letters, digits and single hyphens".to_string(),
+        ));
+    }
+    let spec = request.into_spec()?;
+
+    let trusted_proxies = &state.config.app.trusted_proxies;
+    let peer = peer.map(|Extension(ConnectInfo(addr))| addr.ip());
+
+    // Refuse to cut off the connection the change is being made from.
+    if let Some(peer) = peer {
+        let ip = spec.client_ip(peer, &headers, trusted_proxies);
+        if spec.guards("admin") && !spec.allows(ip) && !query.force {
+            return Err(AppError::Conflict(format!(
+                "the policy would deny admin requests from your address {}; pass force=true to save it anyway",
+                ip
+            )));
+        }
+    }
+
+    let policy = state
+        .network_policies
+        .store()
+        .save(&name, &spec, Some(&caller.actor().to_string()))
+        .await?;
+    state.network_policies.reload().await?;
+    let ip_address = peer.map(|peer| client_ip(peer, &headers, trusted_proxies).to_string());
+    state
+        .audit
+        .record(AuditEvent::network_policy_saved(&policy).by(caller.actor()).from_ip(ip_address)).await;
+    Ok(Json(policy))
+}
+
+async fn delete_policy(
+    State(state): State<AppState>,
+    caller: AuthUser,
+    client: ClientInfo,
+    Path(name): Path<String>,
+) -> Result<Json<NetworkPolicy>> {
+    let policy = state
+        .network_policies
+        .store()
+        .delete(&name)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("network policy `{}`", name)))?;
+    state.network_policies.reload().await?;
+    let event = AuditEvent::NetworkPolicyDeleted {
+        name: policy.name.clone(),
+    };
+    state.audit.record(event.by(caller.actor()).from_ip(client.ip_address)).await;
+    Ok(Json(policy))
+}
@@ -0,0 +1,39 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use crate::api::auth::require_permission;
+use crate::application::command::CommandHandler;
+use crate::bootstrap::AppState;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::{ForceLogout, RevokedSessionsResponse};
+use crate::infrastructure::security::rbac::permissions;
+use crate::infrastructure::security::sessions::Session;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/users/{user}/sessions", get(list_sessions))
+        .route("/users/{user}/logout", post(force_logout))
+        .route_layer(require_permission(permissions::SESSIONS_MANAGE))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListSessionsQuery {
+    /// Include revoked and expired sessions.
+    #[serde(default)]
+    all: bool,
+}
+
+async fn list_sessions(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(query): Query<ListSessionsQuery>,
+) -> Result<Json<Vec<Session>>> {
+    Ok(Json(state.auth.sessions().list_for_user(&user_id, query.all).await?))
+}
+
+/// Ends every session of the user; their access tokens stop working too.
+async fn force_logout(State(state): State<AppState>, Path(user_id): Path<String>) -> Result<Json<RevokedSessionsResponse>> {
+    Ok(Json(state.auth.force_logout().handle(ForceLogout { user_id }).await?))
+}
@@ -0,0 +1,228 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, patch, post};
+use axum::{Json, Router};
+
+use crate::api::auth::AuthUser;
+use crate::application::command::CommandHandler;
+use crate::bootstrap::AppState;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::{
+    ConfirmMfaRequest, ConfirmMfa, DisableMfa, DisableMfaRequest, EnrollMfa, LoginRequest, LoginResponse,
+    LogoutRequest, MfaEnrollmentResponse, RecoveryCodesResponse, RefreshRequest, TokenResponse, VerifyMfaRequest,
+    CompleteOidcLogin, OidcAuthorizationResponse, OidcCallbackRequest, OidcCallbackResponse, StartOidcLogin,
+    UnlinkIdentity, Login, Refresh, RenameSession, RenameSessionRequest, RevokeOtherSessions, RevokeSession,
+    RevokedSessionsResponse, SessionResponse, VerifyMfa,
+};
+use crate::infrastructure::security::identities::LinkedIdentity;
+use crate::infrastructure::security::mfa::MfaStatus;
+use crate::infrastructure::security::oidc::ProviderInfo;
+use crate::infrastructure::security::sessions::{ClientInfo, Session};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/me", get(me))
+        .route("/mfa", get(mfa_status).delete(disable_mfa))
+        .route("/mfa/enroll", post(enroll_mfa))
+        .route("/mfa/confirm", post(confirm_mfa))
+        .route("/mfa/verify", post(verify_mfa))
+        .route("/oidc/providers", get(oidc_providers))
+        .route("/oidc/{provider}/authorize", post(oidc_authorize))
+        .route("/oidc/{provider}/link", post(oidc_link))
+        .route("/oidc/{provider}/callback", post(oidc_callback))
+        .route("/identities", get(identities))
+        .route("/identities/{provider}", delete(unlink_identity))
+        .route("/sessions", get(sessions))
+        .route("/sessions/revoke-others", post(revoke_other_sessions))
+        .route("/sessions/{id}", patch(rename_session).delete(revoke_session))
+}
+
+async fn login(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>> {
+    Ok(Json(state.auth.login().handle(Login { request, client }).await?))
+}
+
+async fn refresh(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>> {
+    Ok(Json(state.auth.refresh().handle(Refresh { request, client }).await?))
+}
+
+async fn logout(State(state): State<AppState>, Json(request): Json<LogoutRequest>) -> Result<StatusCode> {
+    state.auth.logout().handle(request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The caller as the API sees it, including effective permissions.
+async fn me(user: AuthUser) -> Json<AuthUser> {
+    Json(user)
+}
+
+async fn mfa_status(State(state): State<AppState>, user: AuthUser) -> Result<Json<MfaStatus>> {
+    Ok(Json(state.auth.mfa().status(user.user_id()?).await?))
+}
+
+async fn enroll_mfa(State(state): State<AppState>, user: AuthUser) -> Result<Json<MfaEnrollmentResponse>> {
+    let command = EnrollMfa {
+        user_id: user.user_id()?.to_string(),
+    };
+    Ok(Json(state.auth.enroll_mfa().handle(command).await?))
+}
+
+async fn confirm_mfa(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<ConfirmMfaRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    let command = ConfirmMfa {
+        user_id: user.user_id()?.to_string(),
+        code: request.code,
+    };
+    Ok(Json(state.auth.confirm_mfa().handle(command).await?))
+}
+
+async fn verify_mfa(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<VerifyMfaRequest>,
+) -> Result<Json<TokenResponse>> {
+    Ok(Json(state.auth.verify_mfa().handle(VerifyMfa { request, client }).await?))
+}
+
+async fn disable_mfa(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(request): Json<DisableMfaRequest>,
+) -> Result<StatusCode> {
+    let command = DisableMfa {
+        user_id: user.user_id()?.to_string(),
+        request,
+    };
+    state.auth.disable_mfa().handle(command).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn oidc_providers(State(state): State<AppState>) -> Json<Vec<ProviderInfo>> {
+    Json(state.auth.oidc().list())
+}
+
+async fn oidc_authorize(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> Result<Json<OidcAuthorizationResponse>> {
+    let command = StartOidcLogin {
+        provider,
+        link_user_id: None,
+    };
+    Ok(Json(state.auth.start_oidc_login().handle(command).await?))
+}
+
+/// Like `authorize`, but the callback links the identity to the caller.
+async fn oidc_link(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(provider): Path<String>,
+) -> Result<Json<OidcAuthorizationResponse>> {
+    let command = StartOidcLogin {
+        provider,
+        link_user_id: Some(user.user_id()?.to_string()),
+    };
+    Ok(Json(state.auth.start_oidc_login().handle(command).await?))
+}
+
+async fn oidc_callback(
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    client: ClientInfo,
+    Path(provider): Path<String>,
+    Json(request): Json<OidcCallbackRequest>,
+) -> Result<Json<OidcCallbackResponse>> {
+    let caller_id = match &user {
+        Some(user) => Some(user.user_id()?.to_string()),
+        None => None,
+    };
+    let command = CompleteOidcLogin {
+        provider,
+        caller_id,
+        request,
+        client,
+    };
+    Ok(Json(state.auth.complete_oidc_login().handle(command).await?))
+}
+
+async fn identities(State(state): State<AppState>, user: AuthUser) -> Result<Json<Vec<LinkedIdentity>>> {
+    Ok(Json(state.auth.identities().list_for_user(user.user_id()?).await?))
+}
+
+async fn unlink_identity(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(provider): Path<String>,
+) -> Result<StatusCode> {
+    let command = UnlinkIdentity {
+        user_id: user.user_id()?.to_string(),
+        provider,
+    };
+    state.auth.unlink_identity().handle(command).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The caller's live sessions, newest first.
+async fn sessions(State(state): State<AppState>, user: AuthUser) -> Result<Json<Vec<SessionResponse>>> {
+    let sessions = state.auth.sessions().list_for_user(user.user_id()?, false).await?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| SessionResponse {
+                current: user.session_id.as_deref() == Some(session.id.as_str()),
+                session,
+            })
+            .collect(),
+    ))
+}
+
+async fn rename_session(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(session_id): Path<String>,
+    Json(request): Json<RenameSessionRequest>,
+) -> Result<Json<Session>> {
+    let command = RenameSession {
+        user_id: user.user_id()?.to_string(),
+        session_id,
+        device_label: request.device_label,
+    };
+    Ok(Json(state.auth.rename_session().handle(command).await?))
+}
+
+async fn revoke_session(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode> {
+    let command = RevokeSession {
+        user_id: user.user_id()?.to_string(),
+        session_id,
+    };
+    state.auth.revoke_session().handle(command).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<RevokedSessionsResponse>> {
+    let command = RevokeOtherSessions {
+        user_id: user.user_id()?.to_string(),
+        current_session_id: user.session_id.clone(),
+    };
+    Ok(Json(state.auth.revoke_other_sessions().handle(command).await?))
+}
@@ -0,0 +1,256 @@
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::api::auth::require_permission;
+use crate::bootstrap::AppState;
+use crate::common::errors::{AppError, Result};
+use crate::infrastructure::database::market_data::{
+    AssetBars, MacroObservation, MacroSeries, PriceBar, SentimentItem,
+};
+use crate::infrastructure::security::rbac::permissions;
+
+/// Endpoints the scraper pushes to, one per data type, with payloads as
+/// its processors produce them. Rows that cannot be stored (a missing
+/// price, an unparseable date) are skipped and counted rather than failing
+/// the batch.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/asset",
+            post(ingest_asset).route_layer(require_permission(permissions::INGEST_ASSETS)),
+        )
+        .route(
+            "/macro",
+            post(ingest_macro).route_layer(require_permission(permissions::INGEST_MACRO)),
+        )
+        .route(
+            "/sentiment",
+            post(ingest_sentiment).route_layer(require_permission(permissions::INGEST_SENTIMENT)),
+        )
+}
+
+const MAX_SYMBOL_LENGTH: usize = 32;
+
+#[derive(Debug, Deserialize)]
+struct AssetRequest {
+    symbol: String,
+    #[serde(default)]
+    meta: AssetMeta,
+    prices: Vec<PriceRow>,
+}
+
+/// The Yahoo chart `meta` fields we keep.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetMeta {
+    currency: Option<String>,
+    instrument_type: Option<String>,
+    exchange_name: Option<String>,
+    long_name: Option<String>,
+    short_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceRow {
+    /// Unix timestamp of the session.
+    date: i64,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    close: Option<f64>,
+    volume: Option<f64>,
+}
+
+impl PriceRow {
+    fn into_bar(self) -> Option<PriceBar> {
+        let date = DateTime::from_timestamp(self.date, 0)?.date_naive();
+        let prices = [self.open?, self.high?, self.low?, self.close?];
+        if !prices.iter().all(|price| price.is_finite()) {
+            return None;
+        }
+        Some(PriceBar {
+            date,
+            open: prices[0],
+            high: prices[1],
+            low: prices[2],
+            close: prices[3],
+            volume: self.volume.filter(|volume| volume.is_finite()).unwrap_or(0.0) as i64,
+        })
+    }
+}
+
+async fn ingest_asset(State(state): State<AppState>, Json(request): Json<AssetRequest>) -> Result<Json<Value>> {
+    let symbol = request.symbol.trim().to_string();
+    if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LENGTH {
+        return Err(AppError::InvalidInput(format!(
+            "symbol must be 1 to {} characters",
+            MAX_SYMBOL_LENGTH
+        )));
+    }
+
+    let received = request.prices.len();
+    // A later row for the same day replaces an earlier one.
+    let bars: BTreeMap<NaiveDate, PriceBar> = request
+        .prices
+        .into_iter()
+        .filter_map(PriceRow::into_bar)
+        .map(|bar| (bar.date, bar))
+        .collect();
+    let stored = bars.len();
+    let meta = request.meta;
+    let asset = AssetBars {
+        name: meta.long_name.or(meta.short_name).unwrap_or_else(|| symbol.clone()),
+        asset_class: meta
+            .instrument_type
+            .map_or_else(|| "unknown".to_string(), |kind| kind.to_lowercase()),
+        currency: meta.currency.unwrap_or_else(|| "USD".to_string()),
+        exchange: meta.exchange_name,
+        symbol,
+        bars: bars.into_values().collect(),
+    };
+    let asset_id = state.market_data.upsert_bars(&asset).await?;
+
+    Ok(Json(json!({
+        "asset_id": asset_id,
+        "stored": stored,
+        "skipped": received - stored,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct MacroRequest {
+    #[serde(default = "unknown_source")]
+    source: String,
+    indicator: String,
+    values: Vec<MacroRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MacroRow {
+    country: String,
+    value: Option<f64>,
+    #[serde(default)]
+    unit: String,
+    /// `YYYY-MM-DD`, `YYYY-MM` or a year, as a string or number.
+    date: Option<Value>,
+}
+
+async fn ingest_macro(State(state): State<AppState>, Json(request): Json<MacroRequest>) -> Result<Json<Value>> {
+    let indicator = request.indicator.trim().to_string();
+    if indicator.is_empty() {
+        return Err(AppError::InvalidInput("indicator must not be empty".to_string()));
+    }
+
+    let received = request.values.len();
+    let unit = request
+        .values
+        .iter()
+        .map(|row| row.unit.trim())
+        .find(|unit| !unit.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    let observations: BTreeMap<(String, NaiveDate), f64> = request
+        .values
+        .into_iter()
+        .filter_map(|row| {
+            let country = row.country.trim().to_uppercase();
+            let value = row.value.filter(|value| value.is_finite())?;
+            let observed_on = row.date.as_ref().and_then(parse_period)?;
+            (!country.is_empty()).then_some(((country, observed_on), value))
+        })
+        .collect();
+    let stored = observations.len();
+    let series = MacroSeries {
+        indicator,
+        unit,
+        source: request.source,
+        observations: observations
+            .into_iter()
+            .map(|((country_code, observed_on), value)| MacroObservation {
+                country_code,
+                observed_on,
+                value,
+            })
+            .collect(),
+    };
+    state.market_data.upsert_macro(&series).await?;
+
+    Ok(Json(json!({ "stored": stored, "skipped": received - stored })))
+}
+
+/// The first day of the period a macro value is reported for.
+fn parse_period(date: &Value) -> Option<NaiveDate> {
+    let text = match date {
+        Value::Number(year) => year.as_i64()?.to_string(),
+        Value::String(text) => text.trim().to_string(),
+        _ => return None,
+    };
+    let mut parts = text.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next().map_or(Some(1), |month| month.parse().ok())?;
+    let day = parts.next().map_or(Some(1), |day| day.parse().ok())?;
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+#[derive(Debug, Deserialize)]
+struct SentimentRequest {
+    #[serde(default = "unknown_source")]
+    source: String,
+    #[serde(default)]
+    query: String,
+    items: Vec<SentimentRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SentimentRow {
+    /// The item's id at its source.
+    id: Option<String>,
+    #[serde(default)]
+    text: String,
+    /// RFC 3339.
+    created_at: String,
+    sentiment: f64,
+    metrics: Option<Value>,
+}
+
+async fn ingest_sentiment(
+    State(state): State<AppState>,
+    Json(request): Json<SentimentRequest>,
+) -> Result<Json<Value>> {
+    let received = request.items.len();
+    let items: BTreeMap<String, SentimentItem> = request
+        .items
+        .into_iter()
+        .filter_map(|row| {
+            let external_id = row.id.filter(|id| !id.trim().is_empty())?;
+            let published_at = DateTime::parse_from_rfc3339(&row.created_at).ok()?.with_timezone(&Utc);
+            row.sentiment.is_finite().then_some(())?;
+            Some((
+                external_id.clone(),
+                SentimentItem {
+                    external_id,
+                    text: row.text,
+                    score: row.sentiment.clamp(-1.0, 1.0),
+                    metrics: row.metrics,
+                    published_at,
+                },
+            ))
+        })
+        .collect();
+    let items: Vec<SentimentItem> = items.into_values().collect();
+    state
+        .market_data
+        .upsert_sentiment(&request.source, &request.query, &items)
+        .await?;
+
+    Ok(Json(json!({ "stored": items.len(), "skipped": received - items.len() })))
+}
+
+fn unknown_source() -> String {
+    "unknown".to_string()
+}
@@ -0,0 +1,106 @@
+pub mod admin;
+pub mod auth;
+pub mod ingestion;
+pub mod users;
+pub mod well_known;
+
+use axum::extract::{DefaultBodyLimit, OriginalUri};
+use axum::http::{header, StatusCode};
+use axum::middleware::from_fn_with_state;
+use axum::Router;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
+use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
+use tracing::Level;
+
+use crate::api::network_policy::network_policy;
+use crate::api::auth::{authentication, request_signature};
+use crate::api::rate_limit::{
+    rate_limit, source_ip_rate_limit, RATE_LIMIT_LIMIT, RATE_LIMIT_POLICY, RATE_LIMIT_REMAINING,
+    RATE_LIMIT_RESET,
+};
+use crate::api::read_your_writes::read_your_writes;
+use crate::bootstrap::AppState;
+use crate::common::constants::API_VERSION;
+use crate::common::errors::AppError;
+use crate::config::http::{AllowedOrigins, CorsConfig};
+
+/// Everything the server answers: discovery documents at the root and
+/// [`router`] under `/api/{API_VERSION}`, wrapped in the layers `http`
+/// configures. Layers are listed innermost first, so tracing sees every
+/// response, timeouts and rejected preflights included.
+pub fn app(state: AppState) -> Router {
+    let http = state.config.http.clone();
+    let mut app = Router::new()
+        .nest(&format!("/api/{}", API_VERSION), router(&state))
+        .merge(well_known::routes())
+        .fallback(not_found)
+        .with_state(state)
+        // The limit below covers every body, not only those read through
+        // axum's extractors, which would otherwise apply their own 2 MiB.
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(http.max_body_bytes));
+    if http.compression {
+        app = app.layer(CompressionLayer::new());
+    }
+    app = app.layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, http.request_timeout));
+    if !http.cors.allowed_origins.is_empty() {
+        app = app.layer(cors(&http.cors));
+    }
+    app.layer(
+        TraceLayer::new_for_http()
+            .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+            .on_response(DefaultOnResponse::new().level(Level::INFO)),
+    )
+}
+
+/// Feature routes, mounted by the server under `/api/{API_VERSION}`. Each
+/// group has its own network policies and rate limits; see [`group`].
+pub fn router(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .nest("/admin", group(state, "admin", admin::routes()))
+        .nest("/auth", group(state, "auth", auth::routes()))
+        .nest("/users", group(state, "users", users::routes()))
+        .nest("/ingest", group(state, "ingestion", ingestion::routes()))
+        .merge(group(state, "graphql", crate::api::graphql::routes(state)))
+}
+
+/// Guards one of [`ROUTE_GROUPS`](crate::common::constants::ROUTE_GROUPS).
+/// Layers are listed innermost first: network policies, then the source IP
+/// limit, turn a request away before any credential is checked; the
+/// per-client limit follows authentication so it can key on the principal.
+fn group(state: &AppState, name: &'static str, routes: Router<AppState>) -> Router<AppState> {
+    routes
+        .layer(from_fn_with_state(state.clone(), read_your_writes))
+        .layer(from_fn_with_state(state.clone(), request_signature))
+        .layer(rate_limit(state, name))
+        .layer(from_fn_with_state(state.clone(), authentication))
+        .layer(source_ip_rate_limit(state))
+        .layer(network_policy(state, name))
+}
+
+fn cors(config: &CorsConfig) -> CorsLayer {
+    let origins = match &config.allowed_origins {
+        AllowedOrigins::Any => AllowOrigin::any(),
+        AllowedOrigins::List(origins) => AllowOrigin::list(origins.iter().cloned()),
+    };
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(config.allowed_methods.clone())
+        .allow_headers(config.allowed_headers.clone())
+        .allow_credentials(config.allow_credentials)
+        .expose_headers([
+            RATE_LIMIT_LIMIT,
+            RATE_LIMIT_REMAINING,
+            RATE_LIMIT_RESET,
+            RATE_LIMIT_POLICY,
+            header::RETRY_AFTER,
+        ])
+        .max_age(config.max_age)
+}
+
+async fn not_found(OriginalUri(uri): OriginalUri) -> AppError {
+    AppError::NotFound(format!("route `{}`", uri.path()))
+}
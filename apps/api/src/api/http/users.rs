@@ -0,0 +1,120 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+
+use crate::api::auth::{require_permission, AuthUser, PrincipalKind};
+use crate::application::command::{CommandHandler, QueryHandler};
+use crate::bootstrap::AppState;
+use crate::common::errors::{AppError, Result};
+use crate::common::types::PagedResponse;
+use crate::features::users::application::commands::{DeleteUser, UpdateUserCommand};
+use crate::features::users::application::dtos::{
+    CreateUserRequest, ListUsersQuery, UpdateUserRequest, UserResponse,
+};
+use crate::features::users::application::queries::GetUser;
+use crate::infrastructure::security::rbac::permissions;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(list_users)
+                .route_layer(require_permission(permissions::USERS_READ))
+                .post(create_user),
+        )
+        .route(
+            "/{id}",
+            delete(delete_user)
+                .route_layer(require_permission(permissions::USERS_WRITE))
+                .get(get_user)
+                .patch(update_user),
+        )
+}
+
+/// Users may edit their own profile, but only `users:write` can toggle
+/// whether an account is active.
+fn authorize_update(user: &AuthUser, id: &str, changes: &UpdateUserRequest) -> Result<()> {
+    user.require_self_or(id, permissions::USERS_WRITE)?;
+    if changes.is_active.is_some() && !user.can(permissions::USERS_WRITE) {
+        return Err(AppError::Authorization(format!(
+            "missing permission `{}`",
+            permissions::USERS_WRITE
+        )));
+    }
+    Ok(())
+}
+
+/// Reason recorded on sessions ended by a password change.
+const PASSWORD_CHANGED_REASON: &str = "password_changed";
+/// Reason recorded on sessions ended by deactivating the account.
+const USER_DISABLED_REASON: &str = "user_disabled";
+
+/// Applies an update for the REST and GraphQL endpoints. Users changing
+/// their own password must confirm the current one. A password change ends
+/// the account's other sessions, so a leaked refresh token stops working;
+/// users changing their own keep the session they made the change from.
+/// Deactivating an account ends all of its sessions.
+pub(crate) async fn apply_update(
+    state: &AppState,
+    user: &AuthUser,
+    id: String,
+    changes: UpdateUserRequest,
+) -> Result<UserResponse> {
+    authorize_update(user, &id, &changes)?;
+    let own_account = user.kind == PrincipalKind::User && user.id == id;
+    let password_changed = changes.password.is_some();
+    let deactivated = changes.is_active == Some(false);
+    let updated = state
+        .users
+        .update_user()
+        .handle(UpdateUserCommand {
+            id,
+            changes,
+            confirm_password: own_account,
+        })
+        .await?;
+    if deactivated {
+        let revoked = state.auth.end_sessions(&updated.id, None, USER_DISABLED_REASON).await?;
+        tracing::info!(user_id = %updated.id, revoked, "User deactivated; sessions ended");
+    } else if password_changed {
+        let keep = user.session_id.as_deref().filter(|_| own_account);
+        let revoked = state.auth.end_sessions(&updated.id, keep, PASSWORD_CHANGED_REASON).await?;
+        tracing::info!(user_id = %updated.id, revoked, "Password changed; other sessions ended");
+    }
+    Ok(updated)
+}
+
+async fn create_user(
+    State(state): State<AppState>,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserResponse>)> {
+    let user = state.users.create_user().handle(request).await?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+async fn list_users(
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<PagedResponse<UserResponse>>> {
+    Ok(Json(state.users.list_users().handle(query).await?))
+}
+
+async fn get_user(State(state): State<AppState>, user: AuthUser, Path(id): Path<String>) -> Result<Json<UserResponse>> {
+    user.require_self_or(&id, permissions::USERS_READ)?;
+    Ok(Json(state.users.get_user().handle(GetUser { id }).await?))
+}
+
+async fn update_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(changes): Json<UpdateUserRequest>,
+) -> Result<Json<UserResponse>> {
+    Ok(Json(apply_update(&state, &user, id, changes).await?))
+}
+
+async fn delete_user(State(state): State<AppState>, Path(id): Path<String>) -> Result<StatusCode> {
+    state.users.delete_user().handle(DeleteUser { id }).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
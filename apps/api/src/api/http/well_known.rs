@@ -0,0 +1,25 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::bootstrap::AppState;
+
+/// Discovery documents. Mounted at the server root rather than under the
+/// versioned API prefix, where verifiers expect to find them.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/.well-known/jwks.json", get(jwks))
+}
+
+/// Verifiers may cache the set for as long as instances take to pick up a
+/// rotation; a new key is only published once it exists, and it signs from
+/// the moment it does, so a verifier that sees an unknown `kid` should
+/// refetch.
+async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    let max_age = state.config.auth.key_refresh_interval.as_secs();
+    (
+        [(header::CACHE_CONTROL, format!("public, max-age={}", max_age))],
+        Json(state.auth.tokens().jwks()),
+    )
+}
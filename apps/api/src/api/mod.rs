@@ -0,0 +1,6 @@
+pub mod auth;
+pub mod graphql;
+pub mod http;
+pub mod network_policy;
+pub mod rate_limit;
+pub mod read_your_writes;
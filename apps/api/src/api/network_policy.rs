@@ -0,0 +1,109 @@
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+use crate::bootstrap::AppState;
+use crate::common::errors::AppError;
+use crate::infrastructure::security::audit::{Actor, AuditEvent, AuditLog};
+use crate::infrastructure::security::network::NetworkPolicies;
+
+/// Layer rejecting requests to `group` that any network policy attached to
+/// it does not allow, recording each rejection in the audit log. Policies
+/// are read per request, so changes apply without rebuilding the router.
+/// Without connect info nothing can be judged and guarded groups refuse
+/// every request.
+pub fn network_policy(state: &AppState, group: &'static str) -> NetworkPolicy {
+    NetworkPolicy {
+        group,
+        policies: state.network_policies.clone(),
+        trusted_proxies: state.config.app.trusted_proxies.clone().into(),
+        audit: state.audit.clone(),
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkPolicy {
+    group: &'static str,
+    policies: NetworkPolicies,
+    trusted_proxies: Arc<[IpNet]>,
+    audit: AuditLog,
+}
+
+impl NetworkPolicy {
+    /// The first policy denying `request`, with the address it judged.
+    fn denied_by(&self, request: &Request<Body>) -> Option<(String, Option<String>)> {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        self.policies.for_group(self.group).into_iter().find_map(|policy| {
+            let Some(peer) = peer else {
+                return Some((policy.name, None));
+            };
+            let ip = policy.spec.client_ip(peer, request.headers(), &self.trusted_proxies);
+            (!policy.spec.allows(ip)).then(|| (policy.name, Some(ip.to_string())))
+        })
+    }
+}
+
+impl<S> Layer<S> for NetworkPolicy {
+    type Service = NetworkPolicyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        NetworkPolicyService {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkPolicyService<S> {
+    inner: S,
+    config: NetworkPolicy,
+}
+
+impl<S> Service<Request<Body>> for NetworkPolicyService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, std::result::Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if let Some((policy, ip_address)) = self.config.denied_by(&request) {
+            // Policies run before authentication; the caller is not known yet.
+            let actor = Actor::Anonymous;
+            let event = AuditEvent::NetworkAccessDenied {
+                policy,
+                route_group: self.config.group.to_string(),
+                method: request.method().to_string(),
+                path: request.uri().path().to_string(),
+            };
+            let entry = event.by(actor).from_ip(ip_address);
+            let audit = self.config.audit.clone();
+            return Box::pin(async move {
+                audit.record(entry).await;
+                Ok(AppError::Authorization("requests from this network are not allowed".to_string()).into_response())
+            });
+        }
+
+        // The clone that was polled ready is the one that must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { inner.call(request).await })
+    }
+}
@@ -0,0 +1,173 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+use crate::api::auth::{request_ip, AuthUser, PrincipalKind};
+use crate::bootstrap::AppState;
+use crate::common::errors::AppError;
+use crate::config::reload::RuntimeConfigRx;
+use crate::config::runtime::RateLimitQuota;
+use crate::infrastructure::security::rate_limit::{Decision, RateLimiter};
+
+pub const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+pub const RATE_LIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+/// Layer limiting each client to the quota `runtime.rate_limit` sets for
+/// `group`; quota changes apply on reload. Clients are told apart by API
+/// key, then user, then source IP, so it must run after
+/// [`authentication`](crate::api::auth::authentication). Requests with no
+/// principal and no known address pass unlimited, as do all requests
+/// while the store is unreachable.
+///
+/// ```ignore
+/// Router::new().nest("/auth", auth::routes().layer(rate_limit(&state, "auth")))
+/// ```
+pub fn rate_limit(state: &AppState, group: &'static str) -> RateLimit {
+    RateLimit::new(state, Scope::Group(group))
+}
+
+/// Layer limiting each source IP to `runtime.rate_limit.source_ip` across
+/// all groups. Unlike [`rate_limit`] it needs no principal, so it can run
+/// before authentication and spare the server from checking credentials
+/// for a client that is flooding it.
+pub fn source_ip_rate_limit(state: &AppState) -> RateLimit {
+    RateLimit::new(state, Scope::SourceIp)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Group(&'static str),
+    SourceIp,
+}
+
+#[derive(Clone)]
+pub struct RateLimit {
+    scope: Scope,
+    limiter: RateLimiter,
+    runtime: RuntimeConfigRx,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl RateLimit {
+    fn new(state: &AppState, scope: Scope) -> Self {
+        Self {
+            scope,
+            limiter: state.rate_limiter.clone(),
+            runtime: state.runtime.clone(),
+            trusted_proxies: state.config.app.trusted_proxies.clone().into(),
+        }
+    }
+
+    /// The quota that applies and the bucket `request` draws on.
+    fn bucket(&self, request: &Request<Body>) -> (Option<RateLimitQuota>, Option<String>) {
+        let settings = &self.runtime.borrow().rate_limit;
+        match self.scope {
+            Scope::Group(group) => {
+                let quota = settings.quota(group);
+                let key = quota
+                    .and_then(|_| client_key(request, &self.trusted_proxies))
+                    .map(|client| format!("{}:{}", group, client));
+                (quota, key)
+            }
+            Scope::SourceIp => {
+                let quota = settings.source_ip_quota();
+                let key = quota
+                    .and_then(|_| request_ip(request.extensions(), request.headers(), &self.trusted_proxies))
+                    .map(|ip| format!("source_ip:{}", ip));
+                (quota, key)
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimit {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    config: RateLimit,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, std::result::Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let scope = self.config.scope;
+        let (quota, key) = self.config.bucket(&request);
+        let limiter = self.config.limiter.clone();
+
+        // The clone that was polled ready is the one that must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (Some(quota), Some(key)) = (quota, key) else {
+                return inner.call(request).await;
+            };
+            let decision = match limiter.check(&key, &quota).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    tracing::warn!(scope = ?scope, error = %e, "Rate limit check failed; letting the request through");
+                    return inner.call(request).await;
+                }
+            };
+
+            let mut response = if decision.allowed {
+                inner.call(request).await?
+            } else {
+                tracing::debug!(scope = ?scope, key = %key, "Rate limit exceeded");
+                AppError::RateLimit(decision.retry_after_secs).into_response()
+            };
+            insert_headers(response.headers_mut(), &decision, &quota);
+            Ok(response)
+        })
+    }
+}
+
+/// Whose allowance a request draws on.
+fn client_key(request: &Request<Body>, trusted_proxies: &[IpNet]) -> Option<String> {
+    if let Some(user) = request.extensions().get::<AuthUser>() {
+        return Some(match user.kind {
+            PrincipalKind::ApiKey => format!("api_key:{}", user.id),
+            PrincipalKind::User => format!("user:{}", user.id),
+        });
+    }
+    request_ip(request.extensions(), request.headers(), trusted_proxies).map(|ip| format!("ip:{}", ip))
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &Decision, quota: &RateLimitQuota) {
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(decision.reset_secs));
+    let policy = format!("{};w={};burst={}", quota.max_requests, quota.window_secs, quota.burst);
+    if let Ok(policy) = HeaderValue::from_str(&policy) {
+        headers.insert(RATE_LIMIT_POLICY, policy);
+    }
+}
@@ -0,0 +1,20 @@
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::api::auth::{AuthUser, PrincipalKind};
+use crate::bootstrap::AppState;
+
+/// Runs the request inside [`DatabasePool::scope`] so a write sends its
+/// later reads, and the same principal's next few requests, to the primary.
+/// Must run after [`authentication`](crate::api::auth::authentication) to
+/// tell principals apart; anonymous requests only see their own writes.
+///
+/// [`DatabasePool::scope`]: crate::infrastructure::database::connection::DatabasePool::scope
+pub async fn read_your_writes(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let principal = request.extensions().get::<AuthUser>().map(|user| match user.kind {
+        PrincipalKind::ApiKey => format!("api_key:{}", user.id),
+        PrincipalKind::User => format!("user:{}", user.id),
+    });
+    state.db_pool.scope(principal, next.run(request)).await
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::common::errors::Result;
+
+/// Handles one state-changing use case. Handlers own their dependencies, so
+/// the HTTP and GraphQL layers only build the command and call `handle`.
+#[async_trait]
+pub trait CommandHandler<C>: Send + Sync {
+    type Output;
+
+    async fn handle(&self, command: C) -> Result<Self::Output>;
+}
+
+/// Read-only counterpart of [`CommandHandler`].
+#[async_trait]
+pub trait QueryHandler<Q>: Send + Sync {
+    type Output;
+
+    async fn handle(&self, query: Q) -> Result<Self::Output>;
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+pub trait DomainEvent: Serialize + Send + Sync {
+    /// Dotted event name, e.g. `user.created`.
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    pub name: &'static str,
+    pub payload: Value,
+}
+
+/// In-process fan-out of domain events. Publishing never blocks or fails;
+/// subscribers that fall behind miss events rather than slowing writers.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<EventEnvelope>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish<E: DomainEvent>(&self, event: &E) {
+        let envelope = EventEnvelope {
+            name: event.name(),
+            payload: serde_json::to_value(event).unwrap_or(Value::Null),
+        };
+        debug!(event = envelope.name, "Domain event");
+        // No receivers is fine: nothing is interested yet.
+        let _ = self.sender.send(envelope);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
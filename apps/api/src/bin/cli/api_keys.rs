@@ -0,0 +1,135 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{Duration, Utc};
+use clap::Subcommand;
+use sqlx::PgPool;
+
+use m5::config::Config;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::api_keys::{ApiKeyStore, IssuedApiKey, DEFAULT_ROTATION_OVERLAP, MAX_ROTATION_OVERLAP};
+use m5::infrastructure::security::audit::{AuditEvent, AuditStore};
+
+use crate::audit;
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Issue a key; the full key is printed once and cannot be shown again
+    Issue {
+        /// Label identifying the client, e.g. `scraper-prod`
+        name: String,
+        /// Scope granted to the key, e.g. `ingest:assets` (repeatable)
+        #[arg(long = "scope")]
+        scopes: Vec<String>,
+        /// Expire the key after this many days
+        #[arg(long)]
+        expires_in_days: Option<i64>,
+    },
+    /// List keys
+    List {
+        /// Include revoked and expired keys
+        #[arg(long)]
+        all: bool,
+    },
+    /// Issue a replacement for a key by id or prefix; the old key keeps
+    /// working for the overlap period
+    Rotate {
+        key: String,
+        /// Hours the old key stays valid (default 24, at most 720)
+        #[arg(long)]
+        overlap_hours: Option<i64>,
+    },
+    /// Revoke a key by id or prefix
+    Revoke { key: String },
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let store = ApiKeyStore::new(DatabasePool::new(pool.clone()), config.auth.api_key_pepper.clone());
+    let audit_log = AuditStore::new(DatabasePool::new(pool.clone()));
+
+    match command {
+        Command::Issue { name, scopes, expires_in_days } => {
+            if name.trim().is_empty() {
+                bail!("name must not be empty");
+            }
+            let expires_at = match expires_in_days {
+                Some(days) if days <= 0 => bail!("--expires-in-days must be positive"),
+                Some(days) => Some(Utc::now() + Duration::days(days)),
+                None => None,
+            };
+            let issued = store.issue(name.trim(), &scopes, expires_at).await?;
+            audit_log.record(AuditEvent::api_key_issued(&issued.key).by(audit::actor())).await?;
+            print_issued(out, &issued, "Issued")
+        }
+        Command::Rotate { key, overlap_hours } => {
+            let overlap = match overlap_hours {
+                Some(hours) => Duration::try_hours(hours)
+                    .filter(|overlap| *overlap >= Duration::zero() && *overlap <= MAX_ROTATION_OVERLAP)
+                    .ok_or_else(|| {
+                        anyhow!("--overlap-hours must be between 0 and {}", MAX_ROTATION_OVERLAP.num_hours())
+                    })?,
+                None => DEFAULT_ROTATION_OVERLAP,
+            };
+            let issued = store
+                .rotate(&key, overlap)
+                .await?
+                .ok_or_else(|| anyhow!("no active API key matches `{}`", key))?;
+            audit_log.record(AuditEvent::api_key_issued(&issued.key).by(audit::actor())).await?;
+            print_issued(out, &issued, "Rotated to")
+        }
+        Command::List { all } => {
+            let keys = store.list(all).await?;
+            out.emit(&keys, |keys| {
+                println!(
+                    "{:<10} {:<24} {:<8} {:<19} {:<19} SCOPES",
+                    "PREFIX", "NAME", "STATUS", "EXPIRES", "LAST USED"
+                );
+                for key in keys {
+                    println!(
+                        "{:<10} {:<24} {:<8} {:<19} {:<19} {}",
+                        key.prefix,
+                        key.name,
+                        if key.is_active() { "active" } else { "inactive" },
+                        timestamp(key.expires_at),
+                        timestamp(key.last_used_at),
+                        scopes_label(&key.scopes)
+                    );
+                }
+            })
+        }
+        Command::Revoke { key } => {
+            let revoked = store
+                .revoke(&key)
+                .await?
+                .ok_or_else(|| anyhow!("no unrevoked API key matches `{}`", key))?;
+            audit_log.record(AuditEvent::api_key_revoked(&revoked).by(audit::actor())).await?;
+            out.emit(&revoked, |key| println!("Revoked API key {} ({})", key.prefix, key.name))
+        }
+    }
+}
+
+fn print_issued(out: &Output, issued: &IssuedApiKey, action: &str) -> Result<()> {
+    // The JSON form deliberately carries the plaintext key; scripts
+    // issuing keys need it and it is never retrievable later.
+    if out.is_json() {
+        let mut value = serde_json::to_value(issued)?;
+        value["token"] = issued.token.expose().clone().into();
+        return out.emit(&value, |_| {});
+    }
+    out.emit(issued, |issued| {
+        println!("{} API key {} ({})", action, issued.key.prefix, issued.key.name);
+        println!("scopes:  {}", scopes_label(&issued.key.scopes));
+        println!("expires: {}", timestamp(issued.key.expires_at));
+        println!();
+        println!("{}", issued.token.expose());
+        println!();
+        println!("Store this key now; it cannot be shown again.");
+    })
+}
+
+fn scopes_label(scopes: &[String]) -> String {
+    if scopes.is_empty() {
+        "-".to_string()
+    } else {
+        scopes.join(",")
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use clap::Subcommand;
+use sqlx::PgPool;
+
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{Actor, AuditFilter, AuditStore};
+
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Recompute each day's hash chain; fails if any day does not verify
+    Verify {
+        /// First day to check, e.g. 2026-01-31; defaults to the oldest
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to check; defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+    /// Show recent entries, newest first
+    List {
+        /// Event name, or a prefix such as `auth.*`
+        #[arg(long)]
+        event: Option<String>,
+        #[arg(long)]
+        actor: Option<String>,
+        /// User id, key id or IP address the action was about
+        #[arg(long)]
+        subject: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+}
+
+/// Audit entries for changes made from the command line name the OS
+/// account running it.
+pub fn actor() -> Actor {
+    Actor::Cli(std::env::var("USER").or_else(|_| std::env::var("LOGNAME")).ok())
+}
+
+pub async fn run(pool: &PgPool, out: &Output, command: Command) -> Result<()> {
+    let store = AuditStore::new(DatabasePool::new(pool.clone()));
+
+    match command {
+        Command::Verify { from, to } => {
+            let mut results = Vec::new();
+            for day in store.days(from, to).await? {
+                results.push(store.verify_day(day).await?);
+            }
+            out.emit(&results, |results| {
+                for result in results {
+                    match &result.broken {
+                        None => println!("{}  {:>7} entries  ok", result.day, result.entries),
+                        Some(broken) => println!(
+                            "{}  {:>7} entries  BROKEN at seq {} (id {}): {}",
+                            result.day, result.entries, broken.seq, broken.id, broken.reason
+                        ),
+                    }
+                }
+                if results.is_empty() {
+                    println!("No audit entries in range");
+                }
+            })?;
+
+            let broken = results.iter().filter(|result| result.broken.is_some()).count();
+            if broken > 0 {
+                bail!("audit chain broken on {} of {} day(s)", broken, results.len());
+            }
+            Ok(())
+        }
+        Command::List { event, actor, subject, limit } => {
+            let filter = AuditFilter {
+                event,
+                actor_id: actor,
+                subject,
+                limit: Some(limit),
+                ..AuditFilter::default()
+            };
+            let records = store.search(&filter).await?;
+            out.emit(&records, |records| {
+                println!("{:<19} {:<28} {:<30} {:<30} DETAILS", "OCCURRED", "EVENT", "ACTOR", "SUBJECT");
+                for record in records {
+                    let actor = match &record.actor_id {
+                        Some(id) => format!("{}:{}", record.actor_type, id),
+                        None => record.actor_type.clone(),
+                    };
+                    println!(
+                        "{:<19} {:<28} {:<30} {:<30} {}",
+                        timestamp(Some(record.occurred_at)),
+                        record.event,
+                        actor,
+                        record.subject.as_deref().unwrap_or("-"),
+                        record.details
+                    );
+                }
+            })
+        }
+    }
+}
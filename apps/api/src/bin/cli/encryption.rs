@@ -0,0 +1,101 @@
+use anyhow::{bail, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::Subcommand;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use sqlx::PgPool;
+use zeroize::Zeroizing;
+
+use m5::config::encryption::KEY_LENGTH;
+use m5::config::Config;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{AuditEvent, AuditStore};
+use m5::infrastructure::security::encryption::{Encryptor, Reencryptor, ENCRYPTED_COLUMNS};
+
+use crate::audit;
+use crate::output::Output;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print a new random key for `encryption.master_key` or
+    /// `encryption.blind_index_key`
+    GenerateKey,
+    /// Count the rows of each encrypted column per master key
+    Status,
+    /// Move every encrypted value to the current master key after a
+    /// rotation, and encrypt values still stored in plaintext. Safe to run
+    /// while servers are up and to re-run after an interruption
+    ReEncrypt {
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+    },
+}
+
+#[derive(Serialize)]
+struct GeneratedKey {
+    key: String,
+}
+
+/// Needs no configuration, so it also works before any key is set.
+pub fn generate_key(out: &Output) -> Result<()> {
+    let mut bytes = Zeroizing::new([0u8; KEY_LENGTH]);
+    OsRng.fill_bytes(&mut bytes[..]);
+    let key = GeneratedKey {
+        key: STANDARD.encode(&bytes[..]),
+    };
+    out.emit(&key, |key| println!("{}", key.key))
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let encryptor = Encryptor::from_config(&config.encryption)?;
+    let master_key_id = encryptor.master_key_id();
+    let reencryptor = Reencryptor::new(pool.clone(), encryptor);
+
+    match command {
+        Command::GenerateKey => generate_key(out),
+        Command::Status => {
+            let mut usage = Vec::new();
+            for column in ENCRYPTED_COLUMNS {
+                usage.extend(reencryptor.usage(column).await?);
+            }
+            out.emit(&usage, |usage| {
+                println!("Current master key: {}", master_key_id);
+                println!("{:<32} {:<18} {:>10}", "COLUMN", "MASTER KEY", "ROWS");
+                for entry in usage {
+                    let marker = if entry.current { " (current)" } else { "" };
+                    println!("{:<32} {:<18} {:>10}{}", entry.column, entry.master_key_id, entry.rows, marker);
+                }
+            })
+        }
+        Command::ReEncrypt { batch_size } => {
+            if batch_size <= 0 {
+                bail!("--batch-size must be greater than zero");
+            }
+            let audit_log = AuditStore::new(DatabasePool::new(pool.clone()));
+            let mut reports = Vec::new();
+            for column in ENCRYPTED_COLUMNS {
+                let report = reencryptor.reencrypt(column, batch_size).await?;
+                if report.rewrapped > 0 || report.encrypted > 0 {
+                    let event = AuditEvent::DataReencrypted {
+                        column: report.column.clone(),
+                        master_key_id: master_key_id.clone(),
+                        rewrapped: report.rewrapped,
+                        encrypted: report.encrypted,
+                    };
+                    audit_log.record(event.by(audit::actor())).await?;
+                }
+                reports.push(report);
+            }
+            out.emit(&reports, |reports| {
+                for report in reports {
+                    println!(
+                        "{}: {} re-wrapped, {} encrypted from plaintext",
+                        report.column, report.rewrapped, report.encrypted
+                    );
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use sqlx::PgPool;
+
+use m5::infrastructure::jobs::JobQueue;
+
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List failed jobs, most recent first
+    Failed {
+        #[arg(long)]
+        kind: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+    /// Show a job with its payload and last error
+    Show { id: String },
+    /// Requeue a failed job, or every failed job with `--all`
+    Retry {
+        #[arg(required_unless_present = "all")]
+        id: Option<String>,
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+        /// With `--all`, only retry jobs of this kind
+        #[arg(long, requires = "all")]
+        kind: Option<String>,
+    },
+}
+
+pub async fn run(pool: &PgPool, out: &Output, command: Command) -> Result<()> {
+    let queue = JobQueue::new(pool.clone());
+
+    match command {
+        Command::Failed { kind, limit } => {
+            let jobs = queue.failed(kind.as_deref(), limit).await?;
+            out.emit(&jobs, |jobs| {
+                println!("{:<26} {:<20} {:<8} {:<19} ERROR", "ID", "KIND", "ATTEMPTS", "FAILED AT");
+                for job in jobs {
+                    let error = job.last_error.as_deref().unwrap_or("-");
+                    println!(
+                        "{:<26} {:<20} {:<8} {:<19} {}",
+                        job.id,
+                        job.kind,
+                        format!("{}/{}", job.attempts, job.max_attempts),
+                        timestamp(Some(job.updated_at)),
+                        error.lines().next().unwrap_or_default()
+                    );
+                }
+            })
+        }
+        Command::Show { id } => {
+            let job = queue.get(&id).await?.ok_or_else(|| anyhow!("no job `{}`", id))?;
+            out.emit(&job, |job| {
+                println!("id:       {}", job.id);
+                println!("kind:     {}", job.kind);
+                println!("status:   {}", job.status);
+                println!("attempts: {}/{}", job.attempts, job.max_attempts);
+                println!("run at:   {}", timestamp(Some(job.run_at)));
+                println!("created:  {}", timestamp(Some(job.created_at)));
+                println!("updated:  {}", timestamp(Some(job.updated_at)));
+                println!("payload:  {}", job.payload);
+                if let Some(error) = &job.last_error {
+                    println!("error:\n{}", error);
+                }
+            })
+        }
+        Command::Retry { id, all: _, kind } => {
+            let retried = queue.retry(id.as_deref(), kind.as_deref()).await?;
+            if let (Some(id), true) = (&id, retried.is_empty()) {
+                bail!("job `{}` does not exist or has not failed", id);
+            }
+            out.emit(&retried, |jobs| println!("Requeued {} job(s)", jobs.len()))
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use m5::config::Config;
+use m5::infrastructure::security::signing_keys::{default_verify_grace, KeyAlgorithm, SigningKeyStore};
+
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List signing keys and their state
+    List,
+    /// Activate a new signing key and demote the current one to verify-only
+    Rotate {
+        /// Retire verify-only keys demoted more than this many seconds ago
+        /// (defaults to `auth.access_token_ttl_secs` plus
+        /// `auth.key_refresh_interval_secs`)
+        #[arg(long)]
+        grace_secs: Option<i64>,
+        /// HS256, RS256 or EdDSA (defaults to `auth.signing_algorithm`)
+        #[arg(long)]
+        algorithm: Option<KeyAlgorithm>,
+        /// Only rotate when the active key is older than this many hours;
+        /// lets a scheduler run the command more often than keys rotate
+        #[arg(long)]
+        if_older_than_hours: Option<i64>,
+    },
+    /// Stop a verify-only key from verifying before its grace period ends
+    Retire { kid: String },
+}
+
+#[derive(Serialize)]
+struct Skipped {
+    rotated: bool,
+    active_since: DateTime<Utc>,
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let store = SigningKeyStore::new(pool.clone());
+
+    match command {
+        Command::List => {
+            let keys = store.list().await?;
+            out.emit(&keys, |keys| {
+                println!("{:<26} {:<8} {:<12} {:<19} ROTATED", "KID", "ALG", "STATE", "CREATED");
+                for key in keys {
+                    println!(
+                        "{:<26} {:<8} {:<12} {:<19} {}",
+                        key.kid,
+                        key.algorithm,
+                        key.state,
+                        timestamp(Some(key.created_at)),
+                        timestamp(key.rotated_at)
+                    );
+                }
+            })
+        }
+        Command::Rotate {
+            grace_secs,
+            algorithm,
+            if_older_than_hours,
+        } => {
+            let grace = match grace_secs {
+                Some(secs) if secs < 0 => bail!("--grace-secs must not be negative"),
+                Some(secs) => Duration::seconds(secs),
+                None => default_verify_grace(&config.auth),
+            };
+            if let Some(hours) = if_older_than_hours {
+                if hours < 0 {
+                    bail!("--if-older-than-hours must not be negative");
+                }
+                if let Some(active_since) = store.active_since().await? {
+                    if Utc::now() - active_since < Duration::hours(hours) {
+                        let skipped = Skipped {
+                            rotated: false,
+                            active_since,
+                        };
+                        return out.emit(&skipped, |skipped| {
+                            println!(
+                                "Active key created {} is younger than {} hours; not rotating",
+                                timestamp(Some(skipped.active_since)),
+                                hours
+                            )
+                        });
+                    }
+                }
+            }
+            let algorithm = algorithm.unwrap_or(config.auth.signing_algorithm);
+            let rotation = store.rotate(grace, algorithm).await?;
+            out.emit(&rotation, |rotation| {
+                println!("Activated {}", rotation.activated);
+                if let Some(kid) = &rotation.demoted {
+                    println!("Demoted {} to verify-only", kid);
+                }
+                for kid in &rotation.retired {
+                    println!("Retired {}", kid);
+                }
+            })
+        }
+        Command::Retire { kid } => {
+            let previous = store
+                .retire(&kid)
+                .await?
+                .ok_or_else(|| anyhow!("no signing key `{}`", kid))?;
+            out.emit(&serde_json::json!({ "kid": kid, "previous_state": previous }), |_| {
+                println!("Retired {} (was {})", kid, previous)
+            })
+        }
+    }
+}
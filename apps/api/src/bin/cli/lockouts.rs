@@ -0,0 +1,86 @@
+use anyhow::Result;
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::net::IpAddr;
+
+use m5::config::Config;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{AuditEvent, AuditStore};
+use m5::infrastructure::security::lockout::{LoginThrottle, ThrottleScope};
+
+use crate::output::{timestamp, Output};
+use crate::{audit, users};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List accounts and IPs locked out after failed logins
+    List,
+    /// Clear a user's failed logins, lifting any lockout
+    Unlock {
+        /// User id or email
+        user: String,
+    },
+    /// Clear the failed logins of a source IP
+    UnlockIp { ip: IpAddr },
+}
+
+#[derive(Serialize)]
+struct Unlocked {
+    unlocked: bool,
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let throttle = LoginThrottle::new(pool.clone(), config.auth.lockout.clone());
+    let audit_log = AuditStore::new(DatabasePool::new(pool.clone()));
+
+    match command {
+        Command::List => {
+            let locked = throttle.list_locked().await?;
+            out.emit(&locked, |locked| {
+                println!("{:<8} {:<40} {:<8} {:<19} LOCKED UNTIL", "SCOPE", "KEY", "FAILURES", "LAST FAILURE");
+                for entry in locked {
+                    println!(
+                        "{:<8} {:<40} {:<8} {:<19} {}",
+                        entry.scope,
+                        entry.key,
+                        entry.failures,
+                        timestamp(Some(entry.last_failure_at)),
+                        timestamp(entry.blocked_until)
+                    );
+                }
+            })
+        }
+        Command::Unlock { user } => {
+            let user = users::resolve(pool, config, &user).await?;
+            let unlocked = throttle.clear(ThrottleScope::Account, &user.email).await?;
+            let event = AuditEvent::AccountUnlocked {
+                user_id: user.id.clone(),
+                cleared: unlocked,
+            };
+            audit_log.record(event.by(audit::actor())).await?;
+            out.emit(&Unlocked { unlocked }, |result| {
+                if result.unlocked {
+                    println!("Cleared failed logins for {}", user.email)
+                } else {
+                    println!("No failed logins recorded for {}", user.email)
+                }
+            })
+        }
+        Command::UnlockIp { ip } => {
+            let unlocked = throttle.clear(ThrottleScope::Ip, &ip.to_string()).await?;
+            let event = AuditEvent::IpUnlocked {
+                ip_address: ip.to_string(),
+                cleared: unlocked,
+            };
+            audit_log.record(event.by(audit::actor())).await?;
+            out.emit(&Unlocked { unlocked }, |result| {
+                if result.unlocked {
+                    println!("Cleared failed logins for {}", ip)
+                } else {
+                    println!("No failed logins recorded for {}", ip)
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,120 @@
+mod api_keys;
+mod audit;
+mod encryption;
+mod jobs;
+mod keys;
+mod lockouts;
+mod network;
+mod output;
+mod passwords;
+mod roles;
+mod sessions;
+mod users;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPoolOptions;
+use std::path::PathBuf;
+
+use m5::config::loader::LoadOptions;
+use m5::config::{self, Config};
+
+use output::Output;
+
+#[derive(Parser)]
+#[command(name = "cli", about = "Administrative commands for the m5 API")]
+struct Cli {
+    /// Additional config file layered over config/ and the environment
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Print machine-readable JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create, disable and list user accounts
+    #[command(subcommand)]
+    Users(users::Command),
+    /// Grant and revoke roles
+    #[command(subcommand)]
+    Roles(roles::Command),
+    /// Issue, list and revoke API keys for machine clients
+    #[command(subcommand, name = "api-keys")]
+    ApiKeys(api_keys::Command),
+    /// List and kill login sessions
+    #[command(subcommand)]
+    Sessions(sessions::Command),
+    /// List and lift failed-login lockouts
+    #[command(subcommand)]
+    Lockouts(lockouts::Command),
+    /// Inspect and rotate token signing keys
+    #[command(subcommand)]
+    Keys(keys::Command),
+    /// Tune password hashing for this hardware
+    #[command(subcommand)]
+    Passwords(passwords::Command),
+    /// Inspect and retry failed background jobs
+    #[command(subcommand)]
+    Jobs(jobs::Command),
+    /// Search the security audit log and verify its hash chain
+    #[command(subcommand)]
+    Audit(audit::Command),
+    /// List, disable and delete network access policies
+    #[command(subcommand, name = "network-policies")]
+    NetworkPolicies(network::Command),
+    /// Generate encryption keys and re-encrypt data after a key rotation
+    #[command(subcommand)]
+    Encryption(encryption::Command),
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Keys are generated before there is a configuration to load.
+    if let Command::Encryption(encryption::Command::GenerateKey) = cli.command {
+        return encryption::generate_key(&Output::new(cli.json));
+    }
+
+    config::env::init();
+    let options = LoadOptions {
+        config_file: cli.config.clone(),
+        ..LoadOptions::from_process()
+    };
+    let config = Config::load_with(&options)?;
+    let out = Output::new(cli.json);
+
+    // Commands that never touch the database.
+    let command = match cli.command {
+        Command::Passwords(command) => return passwords::run(&config, &out, command),
+        command => command,
+    };
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(config.database.connect_options())
+        .await?;
+
+    let result = match command {
+        Command::Users(command) => users::run(&pool, &config, &out, command).await,
+        Command::Roles(command) => roles::run(&pool, &config, &out, command).await,
+        Command::ApiKeys(command) => api_keys::run(&pool, &config, &out, command).await,
+        Command::Sessions(command) => sessions::run(&pool, &config, &out, command).await,
+        Command::Lockouts(command) => lockouts::run(&pool, &config, &out, command).await,
+        Command::Keys(command) => keys::run(&pool, &config, &out, command).await,
+        Command::Jobs(command) => jobs::run(&pool, &out, command).await,
+        Command::Audit(command) => audit::run(&pool, &out, command).await,
+        Command::NetworkPolicies(command) => network::run(&pool, &out, command).await,
+        Command::Encryption(command) => encryption::run(&pool, &config, &out, command).await,
+        Command::Passwords(_) => unreachable!("handled before connecting"),
+    };
+
+    pool.close().await;
+    result
+}
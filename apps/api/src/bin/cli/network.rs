@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use ipnet::IpNet;
+use sqlx::PgPool;
+
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{Actor, AuditEvent, AuditStore};
+use m5::infrastructure::security::network::NetworkPolicyStore;
+
+use crate::audit;
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List network policies and the route groups they guard
+    List,
+    /// Put a disabled policy back in force
+    Enable { name: String },
+    /// Stop enforcing a policy without deleting it, e.g. after locking
+    /// yourself out of the admin endpoints
+    Disable { name: String },
+    /// Delete a policy
+    Delete { name: String },
+}
+
+/// Running servers pick up changes made here on their next policy refresh.
+pub async fn run(pool: &PgPool, out: &Output, command: Command) -> Result<()> {
+    let store = NetworkPolicyStore::new(DatabasePool::new(pool.clone()));
+    let audit_log = AuditStore::new(DatabasePool::new(pool.clone()));
+    let actor = audit::actor();
+
+    match command {
+        Command::List => {
+            let policies = store.list().await?;
+            out.emit(&policies, |policies| {
+                println!(
+                    "{:<20} {:<8} {:<20} {:<32} {:<32} UPDATED",
+                    "NAME", "ENABLED", "GROUPS", "ALLOW", "DENY"
+                );
+                for policy in policies {
+                    println!(
+                        "{:<20} {:<8} {:<20} {:<32} {:<32} {}",
+                        policy.name,
+                        policy.spec.enabled,
+                        policy.spec.route_groups.join(","),
+                        networks(&policy.spec.allow),
+                        networks(&policy.spec.deny),
+                        timestamp(Some(policy.updated_at))
+                    );
+                }
+            })
+        }
+        Command::Enable { name } => set_enabled(&store, &audit_log, out, &name, true, actor).await,
+        Command::Disable { name } => set_enabled(&store, &audit_log, out, &name, false, actor).await,
+        Command::Delete { name } => {
+            let policy = store
+                .delete(&name)
+                .await?
+                .ok_or_else(|| anyhow!("no network policy named `{}`", name))?;
+            let event = AuditEvent::NetworkPolicyDeleted {
+                name: policy.name.clone(),
+            };
+            audit_log.record(event.by(actor)).await?;
+            out.emit(&policy, |policy| println!("Deleted network policy {}", policy.name))
+        }
+    }
+}
+
+async fn set_enabled(
+    store: &NetworkPolicyStore,
+    audit_log: &AuditStore,
+    out: &Output,
+    name: &str,
+    enabled: bool,
+    actor: Actor,
+) -> Result<()> {
+    let policy = store
+        .set_enabled(name, enabled, Some(&actor.to_string()))
+        .await?
+        .ok_or_else(|| anyhow!("no network policy named `{}`", name))?;
+    audit_log.record(AuditEvent::network_policy_saved(&policy).by(actor)).await?;
+    out.emit(&policy, |policy| {
+        let state = if policy.spec.enabled { "Enabled" } else { "Disabled" };
+        println!("{} network policy {}", state, policy.name)
+    })
+}
+
+fn networks(networks: &[IpNet]) -> String {
+    if networks.is_empty() {
+        return "-".to_string();
+    }
+    networks.iter().map(IpNet::to_string).collect::<Vec<_>>().join(",")
+}
@@ -0,0 +1,112 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use m5::common::security::PasswordHashing;
+use m5::config::auth::PasswordHashingConfig;
+use m5::config::Config;
+
+use crate::output::Output;
+
+/// Hashes timed per measurement; the slowest outlier matters less than a
+/// stable average.
+const SAMPLES: u32 = 3;
+/// Memory is never suggested below this; less makes GPU attacks cheap.
+const MIN_MEMORY_KIB: u32 = 8 * 1024;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Time argon2id on this machine and suggest `auth.password_hashing`
+    /// parameters that take about `--target-ms` per hash
+    Benchmark {
+        #[arg(long, default_value_t = 500)]
+        target_ms: u64,
+        /// Memory to start from (defaults to `auth.password_hashing.memory_kib`);
+        /// halved while a single iteration is slower than the target
+        #[arg(long)]
+        memory_kib: Option<u32>,
+        /// Defaults to `auth.password_hashing.parallelism`
+        #[arg(long)]
+        parallelism: Option<u32>,
+    },
+}
+
+#[derive(Serialize)]
+struct Suggestion {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    measured_ms: u128,
+    target_ms: u64,
+}
+
+pub fn run(config: &Config, out: &Output, command: Command) -> Result<()> {
+    match command {
+        Command::Benchmark {
+            target_ms,
+            memory_kib,
+            parallelism,
+        } => {
+            if target_ms == 0 {
+                bail!("--target-ms must be greater than zero");
+            }
+            let target = Duration::from_millis(target_ms);
+            let current = &config.auth.password_hashing;
+            let parallelism = parallelism.unwrap_or(current.parallelism);
+            let mut memory_kib = memory_kib.unwrap_or(current.memory_kib);
+
+            // One iteration sets the per-iteration cost at this memory size;
+            // iterations then scale roughly linearly.
+            let mut single = time_hash(current, memory_kib, 1, parallelism)?;
+            while single > target && memory_kib / 2 >= MIN_MEMORY_KIB {
+                memory_kib /= 2;
+                single = time_hash(current, memory_kib, 1, parallelism)?;
+            }
+            let mut iterations = ((target.as_secs_f64() / single.as_secs_f64()).floor() as u32).max(1);
+            let mut measured = time_hash(current, memory_kib, iterations, parallelism)?;
+            while measured > target && iterations > 1 {
+                iterations -= 1;
+                measured = time_hash(current, memory_kib, iterations, parallelism)?;
+            }
+
+            let suggestion = Suggestion {
+                memory_kib,
+                iterations,
+                parallelism,
+                measured_ms: measured.as_millis(),
+                target_ms,
+            };
+            out.emit(&suggestion, |s| {
+                println!("# {} ms per hash on this machine (target {} ms)", s.measured_ms, s.target_ms);
+                if s.measured_ms > u128::from(s.target_ms) {
+                    println!("# slower than the target even at the minimum; consider a higher --target-ms");
+                }
+                println!("[auth.password_hashing]");
+                println!("memory_kib = {}", s.memory_kib);
+                println!("iterations = {}", s.iterations);
+                println!("parallelism = {}", s.parallelism);
+            })
+        }
+    }
+}
+
+/// Average time to hash one password with these parameters and the
+/// configured pepper.
+fn time_hash(current: &PasswordHashingConfig, memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Duration> {
+    let hashing = PasswordHashing::new(&PasswordHashingConfig {
+        memory_kib,
+        iterations,
+        parallelism,
+        pepper: current.pepper.clone(),
+    })
+    .map_err(|e| anyhow!("unusable argon2 parameters: {}", e))?;
+
+    let started = Instant::now();
+    for _ in 0..SAMPLES {
+        hashing
+            .hash("correct horse battery staple")
+            .map_err(|e| anyhow!("hashing failed: {}", e))?;
+    }
+    Ok(started.elapsed() / SAMPLES)
+}
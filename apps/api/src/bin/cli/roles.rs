@@ -0,0 +1,112 @@
+use anyhow::Result;
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use m5::config::Config;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{AuditEvent, AuditStore};
+use m5::infrastructure::security::rbac::RbacStore;
+use m5::infrastructure::security::roles::RoleStore;
+
+use crate::output::Output;
+use crate::{audit, users};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List the roles that can be granted and their permissions
+    List,
+    /// Grant a role to a user
+    Grant {
+        /// User id or email
+        user: String,
+        role: String,
+    },
+    /// Revoke a role from a user
+    Revoke {
+        /// User id or email
+        user: String,
+        role: String,
+    },
+}
+
+#[derive(Serialize)]
+struct RoleListing {
+    name: String,
+    description: String,
+    permissions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RoleChange {
+    user_id: String,
+    email: String,
+    role: String,
+    changed: bool,
+    roles: Vec<String>,
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let store = RoleStore::new(pool.clone());
+
+    let (user, role, granted) = match command {
+        Command::List => {
+            let mut permissions = RbacStore::new(pool.clone()).role_permissions().await?;
+            let roles: Vec<RoleListing> = store
+                .list()
+                .await?
+                .into_iter()
+                .map(|role| RoleListing {
+                    permissions: permissions
+                        .iter_mut()
+                        .find(|(name, _)| *name == role.name)
+                        .map(|(_, granted)| std::mem::take(granted))
+                        .unwrap_or_default(),
+                    name: role.name,
+                    description: role.description,
+                })
+                .collect();
+            return out.emit(&roles, |roles| {
+                for role in roles {
+                    println!("{:<16} {}", role.name, role.description);
+                    println!("{:<16} {}", "", role.permissions.join(", "));
+                }
+            });
+        }
+        Command::Grant { user, role } => (user, role, true),
+        Command::Revoke { user, role } => (user, role, false),
+    };
+
+    let user = users::resolve(pool, config, &user).await?;
+    let changed = if granted {
+        store.grant(&user.id, &role).await?
+    } else {
+        store.revoke(&user.id, &role).await?
+    };
+    if changed {
+        let (user_id, role) = (user.id.clone(), role.clone());
+        let event = if granted {
+            AuditEvent::RoleGranted { user_id, role }
+        } else {
+            AuditEvent::RoleRevoked { user_id, role }
+        };
+        AuditStore::new(DatabasePool::new(pool.clone())).record(event.by(audit::actor())).await?;
+    }
+    let change = RoleChange {
+        roles: store.roles_for(&user.id).await?,
+        user_id: user.id,
+        email: user.email,
+        role,
+        changed,
+    };
+
+    out.emit(&change, |change| {
+        match (granted, change.changed) {
+            (true, true) => println!("Granted `{}` to {}", change.role, change.email),
+            (true, false) => println!("{} already has `{}`", change.email, change.role),
+            (false, true) => println!("Revoked `{}` from {}", change.role, change.email),
+            (false, false) => println!("{} does not have `{}`", change.email, change.role),
+        }
+        println!("roles: {}", if change.roles.is_empty() { "-".to_string() } else { change.roles.join(", ") });
+    })
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use m5::config::Config;
+use m5::features::auth::application::commands::session_force_logout::ADMIN_REVOKE_REASON;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::sessions::SessionStore;
+
+use crate::output::{timestamp, Output};
+use crate::users;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List a user's sessions
+    List {
+        /// User id or email
+        user: String,
+        /// Include revoked and expired sessions
+        #[arg(long)]
+        all: bool,
+    },
+    /// End a single session
+    Kill { session: String },
+    /// End every session of a user
+    KillAll {
+        /// User id or email
+        user: String,
+    },
+}
+
+#[derive(Serialize)]
+struct Killed {
+    revoked: usize,
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let store = SessionStore::new(DatabasePool::new(pool.clone()));
+
+    match command {
+        Command::List { user, all } => {
+            let user = users::resolve(pool, config, &user).await?;
+            let sessions = store.list_for_user(&user.id, all).await?;
+            out.emit(&sessions, |sessions| {
+                println!(
+                    "{:<26} {:<19} {:<19} {:<19} {:<24} {:<15} STATUS",
+                    "ID", "CREATED", "LAST SEEN", "EXPIRES", "DEVICE", "IP"
+                );
+                for session in sessions {
+                    let status = match (&session.revoked_reason, session.is_active()) {
+                        (Some(reason), _) => format!("revoked ({})", reason),
+                        (None, true) => "active".to_string(),
+                        (None, false) => "expired".to_string(),
+                    };
+                    let device: String = session.device_label.as_deref().unwrap_or("-").chars().take(24).collect();
+                    println!(
+                        "{:<26} {:<19} {:<19} {:<19} {:<24} {:<15} {}",
+                        session.id,
+                        timestamp(Some(session.created_at)),
+                        timestamp(Some(session.last_seen_at)),
+                        timestamp(Some(session.expires_at)),
+                        device,
+                        session.ip_address.as_deref().unwrap_or("-"),
+                        status
+                    );
+                }
+            })
+        }
+        Command::Kill { session } => {
+            if !store.revoke(&session, ADMIN_REVOKE_REASON).await? {
+                return Err(anyhow!("no active session `{}`", session));
+            }
+            out.emit(&Killed { revoked: 1 }, |_| println!("Revoked session {}", session))
+        }
+        Command::KillAll { user } => {
+            let user = users::resolve(pool, config, &user).await?;
+            let revoked = store.revoke_all_for_user(&user.id, None, ADMIN_REVOKE_REASON).await?.len();
+            out.emit(&Killed { revoked }, |killed| {
+                println!("Revoked {} session(s) for {}", killed.revoked, user.email)
+            })
+        }
+    }
+}
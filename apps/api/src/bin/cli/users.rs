@@ -0,0 +1,204 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::io::BufRead;
+
+use m5::application::command::CommandHandler;
+use m5::application::event::EventBus;
+use m5::common::errors::AppError;
+use m5::config::Config;
+use m5::features::users::application::commands::UpdateUserCommand;
+use m5::features::users::application::dtos::{CreateUserRequest, UpdateUserRequest, UserResponse};
+use m5::features::users::domain::models::UserId;
+use m5::features::users::infrastructure::repositories::PgUserRepository;
+use m5::features::users::ports::repositories::UserRepository;
+use m5::features::users::UsersModule;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{AuditEvent, AuditStore};
+use m5::infrastructure::security::encryption::Encryptor;
+use m5::infrastructure::security::roles::RoleStore;
+use m5::infrastructure::security::sessions::SessionStore;
+
+use crate::audit;
+use crate::output::{timestamp, Output};
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create an account; the password is read from the first line of stdin
+    Create {
+        email: String,
+        #[arg(long)]
+        name: String,
+        /// Roles to grant immediately
+        #[arg(long = "role")]
+        roles: Vec<String>,
+    },
+    /// Block logins and end every session of a user
+    Disable {
+        /// User id or email
+        user: String,
+    },
+    /// Allow a disabled user to log in again
+    Enable {
+        /// User id or email
+        user: String,
+    },
+    /// Show a user with their roles
+    Show {
+        /// User id or email
+        user: String,
+    },
+}
+
+#[derive(Serialize)]
+struct UserDetails {
+    #[serde(flatten)]
+    user: UserResponse,
+    roles: Vec<String>,
+    sessions_revoked: Option<usize>,
+}
+
+pub fn module(pool: &PgPool, config: &Config) -> Result<UsersModule> {
+    UsersModule::postgres(
+        DatabasePool::new(pool.clone()),
+        &config.auth,
+        Encryptor::from_config(&config.encryption)?,
+        EventBus::new(),
+    )
+}
+
+/// Looks a user up by id or, case-insensitively, by email.
+pub async fn resolve(pool: &PgPool, config: &Config, user: &str) -> Result<UserResponse> {
+    let repository = PgUserRepository::new(
+        DatabasePool::new(pool.clone()),
+        Encryptor::from_config(&config.encryption)?,
+    );
+    let found = match UserId::parse(user) {
+        Ok(id) => repository.find_by_id(&id).await?,
+        Err(_) => None,
+    };
+    let found = match found {
+        Some(found) => Some(found),
+        None => repository.find_by_email(&user.trim().to_lowercase()).await?,
+    };
+    found
+        .map(|user| UserResponse::from(&user))
+        .ok_or_else(|| anyhow!("no user matches `{}`", user))
+}
+
+pub async fn run(pool: &PgPool, config: &Config, out: &Output, command: Command) -> Result<()> {
+    let roles = RoleStore::new(pool.clone());
+
+    let details = match command {
+        Command::Create { email, name, roles: granted } => {
+            let password = read_password()?;
+            let user = module(pool, config)?
+                .create_user()
+                .handle(CreateUserRequest { email, name, password })
+                .await
+                .map_err(describe)?;
+
+            let audit_log = AuditStore::new(DatabasePool::new(pool.clone()));
+            for role in &granted {
+                if roles.grant(&user.id, role).await? {
+                    let event = AuditEvent::RoleGranted {
+                        user_id: user.id.clone(),
+                        role: role.clone(),
+                    };
+                    audit_log.record(event.by(audit::actor())).await?;
+                }
+            }
+            UserDetails {
+                roles: roles.roles_for(&user.id).await?,
+                user,
+                sessions_revoked: None,
+            }
+        }
+        Command::Disable { user } => {
+            let user = resolve(pool, config, &user).await?;
+            let user = set_active(pool, config, &user.id, false).await?;
+            let revoked = SessionStore::new(DatabasePool::new(pool.clone()))
+                .revoke_all_for_user(&user.id, None, "user_disabled")
+                .await?
+                .len();
+            UserDetails {
+                roles: roles.roles_for(&user.id).await?,
+                user,
+                sessions_revoked: Some(revoked),
+            }
+        }
+        Command::Enable { user } => {
+            let user = resolve(pool, config, &user).await?;
+            let user = set_active(pool, config, &user.id, true).await?;
+            UserDetails {
+                roles: roles.roles_for(&user.id).await?,
+                user,
+                sessions_revoked: None,
+            }
+        }
+        Command::Show { user } => {
+            let user = resolve(pool, config, &user).await?;
+            UserDetails {
+                roles: roles.roles_for(&user.id).await?,
+                user,
+                sessions_revoked: None,
+            }
+        }
+    };
+
+    out.emit(&details, |details| {
+        let user = &details.user;
+        println!("id:       {}", user.id);
+        println!("email:    {}", user.email);
+        println!("name:     {}", user.name);
+        println!("status:   {}", if user.is_active { "active" } else { "disabled" });
+        println!("roles:    {}", if details.roles.is_empty() { "-".to_string() } else { details.roles.join(", ") });
+        println!("created:  {}", timestamp(Some(user.created_at)));
+        if let Some(revoked) = details.sessions_revoked {
+            println!("sessions: {} revoked", revoked);
+        }
+    })
+}
+
+async fn set_active(pool: &PgPool, config: &Config, id: &str, active: bool) -> Result<UserResponse> {
+    let changes = UpdateUserRequest {
+        is_active: Some(active),
+        ..Default::default()
+    };
+    module(pool, config)?
+        .update_user()
+        .handle(UpdateUserCommand {
+            id: id.to_string(),
+            changes,
+            confirm_password: false,
+        })
+        .await
+        .map_err(describe)
+}
+
+/// Validation errors only say how many fields failed; spell them out.
+fn describe(error: AppError) -> anyhow::Error {
+    match error {
+        AppError::Validation(errors) => anyhow!(
+            "{}",
+            errors
+                .iter()
+                .map(|e| format!("{}: {}", e.field, e.message))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+        other => other.into(),
+    }
+}
+
+/// Reading from stdin keeps passwords out of shell history and `ps` output.
+fn read_password() -> Result<String> {
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        bail!("expected the password on stdin");
+    }
+    Ok(password)
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use sqlx::migrate::Migration;
+use sqlx::postgres::PgPoolOptions;
+use std::path::{Path, PathBuf};
+
+use m5::config::loader::LoadOptions;
+use m5::config::{self, Config};
+use m5::infrastructure::database::migrations::{self, MigrationState, MIGRATIONS_DIR};
+
+#[derive(Parser)]
+#[command(name = "migrate", about = "Manage the m5 database schema")]
+struct Cli {
+    /// Additional config file layered over config/ and the environment
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply all pending migrations
+    Up {
+        /// Print the SQL that would run instead of running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Revert the most recent migrations
+    Down {
+        /// How many migrations to revert
+        #[arg(default_value_t = 1)]
+        count: usize,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Revert and re-apply the most recent migration
+    Redo {
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List migrations and whether they are applied, pending or drifted
+    Status,
+    /// Exit non-zero unless the schema is fully up to date
+    Verify,
+    /// Create a new timestamped migration file
+    New {
+        description: String,
+        /// Create an .up.sql/.down.sql pair
+        #[arg(long, short)]
+        reversible: bool,
+        #[arg(long, default_value = MIGRATIONS_DIR)]
+        dir: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    if let Command::New { description, reversible, dir } = &cli.command {
+        for path in migrations::create(dir, description, *reversible)? {
+            println!("Created {}", path.display());
+        }
+        return Ok(());
+    }
+
+    config::env::init();
+    let options = LoadOptions {
+        config_file: cli.config.clone(),
+        ..LoadOptions::from_process()
+    };
+    let config = Config::load_with(&options)?;
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_with(config.database.connect_options())
+        .await?;
+
+    match cli.command {
+        Command::Up { dry_run } => {
+            let applied = migrations::up(&pool, dry_run).await?;
+            report("apply", &applied, dry_run);
+        }
+        Command::Down { count, dry_run } => {
+            let reverted = migrations::down(&pool, count, dry_run).await?;
+            report("revert", &reverted, dry_run);
+        }
+        Command::Redo { dry_run } => {
+            let steps = migrations::redo(&pool, dry_run).await?;
+            report("run", &steps, dry_run);
+        }
+        Command::Status => {
+            let statuses = migrations::status(&pool).await?;
+            if statuses.is_empty() {
+                println!("No migrations found in {}", Path::new(MIGRATIONS_DIR).display());
+            }
+            for s in &statuses {
+                println!(
+                    "{:<16} {:<18} {}{}",
+                    s.version,
+                    s.state.as_str(),
+                    s.description,
+                    if s.reversible { "" } else { " (irreversible)" }
+                );
+            }
+            if statuses.iter().any(|s| !matches!(s.state, MigrationState::Applied | MigrationState::Pending)) {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify => migrations::verify(&pool).await?,
+        Command::New { .. } => unreachable!(),
+    }
+
+    pool.close().await;
+    Ok(())
+}
+
+fn report(action: &str, migrations: &[&Migration], dry_run: bool) {
+    if migrations.is_empty() {
+        println!("Nothing to {}", action);
+        return;
+    }
+
+    for migration in migrations {
+        if dry_run {
+            println!(
+                "-- {} {} ({})\n{}\n",
+                migration.version,
+                migration.description,
+                migration.migration_type.label(),
+                migration.sql.trim_end()
+            );
+        } else {
+            println!("{} {} {}", migration.migration_type.label(), migration.version, migration.description);
+        }
+    }
+}
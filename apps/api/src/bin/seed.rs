@@ -0,0 +1,55 @@
+use anyhow::Result;
+use clap::Parser;
+use sqlx::postgres::PgPoolOptions;
+use std::path::PathBuf;
+
+use m5::config::loader::LoadOptions;
+use m5::config::{self, Config};
+use m5::infrastructure::database::migrations;
+use m5::infrastructure::database::seed::{self, SeedProfile, DEFAULT_SEED};
+use m5::infrastructure::security::encryption::Encryptor;
+
+#[derive(Parser)]
+#[command(name = "seed", about = "Populate the m5 database with synthetic data")]
+struct Cli {
+    /// Additional config file layered over config/ and the environment
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Dataset size: minimal, demo or load-test
+    #[arg(long, default_value = "demo")]
+    profile: SeedProfile,
+
+    /// RNG seed; the same seed always produces the same data
+    #[arg(long, default_value_t = DEFAULT_SEED)]
+    seed: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    config::env::init();
+    let options = LoadOptions {
+        config_file: cli.config.clone(),
+        ..LoadOptions::from_process()
+    };
+    let config = Config::load_with(&options)?;
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(config.database.connect_options())
+        .await?;
+
+    migrations::verify(&pool).await?;
+    let report = seed::run(&pool, &Encryptor::from_config(&config.encryption)?, cli.profile, cli.seed).await?;
+
+    println!("Seeded profile {} with seed {}:", cli.profile, cli.seed);
+    println!("  users               {}", report.users);
+    println!("  assets              {}", report.assets);
+    println!("  price bars          {}", report.price_bars);
+    println!("  macro observations  {}", report.macro_observations);
+    println!("  sentiment items     {}", report.sentiment_items);
+
+    pool.close().await;
+    Ok(())
+}
@@ -1,35 +1,135 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 use anyhow::Result;
+use tokio::net::TcpListener;
 use tokio::signal;
 
-use crate::config::Config;
+use crate::application::event::EventBus;
+use crate::config::loader::LoadOptions;
+use crate::config::reload::{self, RuntimeConfigRx, RuntimeReloader};
+use crate::config::{self, Config};
+use crate::features::auth::AuthModule;
+use crate::features::users::UsersModule;
 use crate::infrastructure::database;
+use crate::infrastructure::database::market_data::MarketDataStore;
+use crate::infrastructure::jobs::{JobQueue, JobRunner};
+use crate::infrastructure::security::audit::{AuditLog, AuditStore};
+use crate::infrastructure::security::encryption::Encryptor;
+use crate::infrastructure::security::jwt::TokenService;
+use crate::infrastructure::security::network::{NetworkPolicies, NetworkPolicyStore};
+use crate::infrastructure::security::rate_limit::RateLimiter;
+use crate::infrastructure::security::signing_keys::SigningKeyStore;
+use crate::infrastructure::services::{Capabilities, Services};
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config,
     pub db_pool: database::connection::DatabasePool,
+    pub capabilities: Capabilities,
+    pub services: Services,
+    pub runtime: RuntimeConfigRx,
+    pub events: EventBus,
+    pub audit: AuditLog,
+    pub encryptor: Encryptor,
+    pub rate_limiter: RateLimiter,
+    pub network_policies: NetworkPolicies,
+    pub market_data: MarketDataStore,
+    pub users: UsersModule,
+    pub auth: AuthModule,
 }
 
 pub async fn init() -> Result<Arc<AppState>> {
-    let config = Config::load()?;
+    config::env::init();
+    let load_options = LoadOptions::from_process();
+    let config = Config::load_with(&load_options)?;
 
-    crate::common::logging::init(
-        &config.app.environment,
-        "debug",
-    );
+    let log_handle = crate::common::logging::init(&config.runtime.log_filter);
 
+    let encryptor = Encryptor::from_config(&config.encryption)?;
     let db_pool = database::connection::create_pool(&config).await?;
-    database::migrations::run_migrations(&db_pool).await?;
+    database::migrations::prepare_schema(db_pool.primary(), config.database.migrations).await?;
+    let audit = AuditLog::spawn(AuditStore::new(db_pool.clone()));
+    let rate_limiter = RateLimiter::from_config(config.app.rate_limit_store, db_pool.primary().clone());
+    let network_policies = NetworkPolicies::load(NetworkPolicyStore::new(db_pool.clone())).await?;
+    network_policies.spawn_refresh(config.app.network_policy_refresh);
+    let market_data = MarketDataStore::new(db_pool.primary().clone());
+
+    let (runtime_tx, runtime) = reload::channel(config.runtime.clone());
+    RuntimeReloader::new(load_options, runtime_tx, log_handle, audit.clone()).spawn();
+
+    let capabilities = Capabilities::from_config(&config.services);
+    let services = Services::from_config(&config.services).await?;
+    for capability in &capabilities.services {
+        tracing::info!(
+            service = %capability.service,
+            enabled = capability.enabled,
+            backend = capability.backend,
+            "Service capability"
+        );
+    }
+
+    let events = EventBus::new();
+    let users = UsersModule::postgres(db_pool.clone(), &config.auth, encryptor.clone(), events.clone())?;
+
+    let tokens = TokenService::load(SigningKeyStore::new(db_pool.primary().clone()), &config.auth).await?;
+    tokens.spawn_refresh(config.auth.key_refresh_interval);
+    let auth = AuthModule::postgres(
+        users.clone(),
+        tokens,
+        db_pool.clone(),
+        audit.clone(),
+        encryptor.clone(),
+        services.mail().ok(),
+        &config.auth,
+    )?;
+    auth.register_jobs(JobRunner::new(JobQueue::new(db_pool.primary().clone())))
+        .spawn();
 
     let app_state = AppState {
         config,
         db_pool,
+        capabilities,
+        services,
+        runtime,
+        events,
+        audit,
+        encryptor,
+        rate_limiter,
+        network_policies,
+        market_data,
+        users,
+        auth,
     };
 
     Ok(Arc::new(app_state))
 }
 
+/// Serves [`api::http::app`](crate::api::http::app) on `app.host` and
+/// `app.port` until [`shutdown_signal`], letting requests in flight finish
+/// and the audit log drain before the pool is closed.
+pub async fn serve(state: AppState) -> Result<()> {
+    let db_pool = state.db_pool.clone();
+    let audit = state.audit.clone();
+    let listener = TcpListener::bind((state.config.app.host.as_str(), state.config.app.port)).await?;
+    tracing::info!(
+        "Listening on {} in {} mode",
+        listener.local_addr()?,
+        state.config.app.environment
+    );
+
+    // Clients without a principal are told apart by peer address, which
+    // the handlers only see through `ConnectInfo`.
+    let app = crate::api::http::app(state).into_make_service_with_connect_info::<SocketAddr>();
+    axum::serve(listener, app).with_graceful_shutdown(shutdown_signal()).await?;
+
+    tracing::info!("Shutting down...");
+    // Audit entries recorded by the last requests are still buffered.
+    audit.close().await;
+    database::connection::close_pool(db_pool).await;
+    tracing::info!("Shutdown complete");
+    Ok(())
+}
+
 pub async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
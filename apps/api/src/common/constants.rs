@@ -1,4 +1,6 @@
 pub const API_VERSION: &str = "v1";
+/// Route groups that rate limits and network policies attach to.
+pub const ROUTE_GROUPS: &[&str] = &["auth", "users", "admin", "graphql", "ingestion"];
 pub const DEFAULT_PAGE_SIZE: u32 = 20;
 pub const MAX_PAGE_SIZE: u32 = 100;
 
@@ -10,4 +12,5 @@ pub const ACCESS_TOKEN_DURATION: i64 = 3600;
 pub const REFRESH_TOKEN_DURATION: i64 = 2592000;   
 
 pub const RATE_LIMIT_WINDOW: u64 = 60;  
-pub const RATE_LIMIT_MAX_REQUESTS: u32 = 100;
\ No newline at end of file
+pub const RATE_LIMIT_MAX_REQUESTS: u32 = 100;
+pub const RATE_LIMIT_SOURCE_IP_MAX_REQUESTS: u32 = 1000;
\ No newline at end of file
@@ -1,7 +1,6 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
-use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,7 +11,7 @@ pub enum AppError {
     #[error("Authorization error: {0}")]
     Authorization(String),
 
-    #[error("Validation error: {0}")]
+    #[error("Validation error: {} invalid field(s)", .0.len())]
     Validation(Vec<ValidationError>),
 
     #[error("Not found: {0}")]
@@ -23,7 +22,7 @@ pub enum AppError {
 
     #[error("Database error: {source}")]
     Database {
-        #[from]
+        #[source]
         source: sqlx::Error,
         context: Option<String>,
     },
@@ -41,11 +40,16 @@ pub enum AppError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Service unavailable: {0} is not enabled on this server")]
+    ServiceUnavailable(String),
+
     #[error("Internal server error")]
     Internal(#[from] anyhow::Error),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+pub type Result<T> = std::result::Result<T, AppError>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
     pub field: String,
     pub code: String,
@@ -85,6 +89,27 @@ impl AppError {
         }
     }
 
+    /// The message shown to clients. Database and internal failures get a
+    /// generic one: SQL, constraint names and error chains are for the
+    /// server log, see [`log_internal`](Self::log_internal).
+    pub fn public_message(&self) -> String {
+        match self {
+            AppError::Database { .. } | AppError::Internal(_) => "Internal server error".to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Logs the details [`public_message`](Self::public_message) withholds.
+    pub fn log_internal(&self) {
+        match self {
+            AppError::Database { source, context } => {
+                tracing::error!(context = context.as_deref(), "Database error: {}", source)
+            }
+            AppError::Internal(e) => tracing::error!("Internal error: {:#}", e),
+            _ => {}
+        }
+    }
+
     fn error_response(&self) -> ErrorResponse {
         let (status_code, error_type, help) = match self {
             AppError::Authentication(_) => (
@@ -97,7 +122,7 @@ impl AppError {
                 "authorization_error",
                 Some("You don't have permission to perform this action"),
             ),
-            AppError::Validation(errors) => (
+            AppError::Validation(_) => (
                 StatusCode::BAD_REQUEST,
                 "validation_error",
                 Some("Please check the input requirements"),
@@ -132,6 +157,11 @@ impl AppError {
                 "invalid_input",
                 Some("Please check your input and try again"),
             ),
+            AppError::ServiceUnavailable(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+                Some("This feature is disabled in the current deployment"),
+            ),
             AppError::Internal(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "internal_error",
@@ -141,7 +171,7 @@ impl AppError {
 
         ErrorResponse {
             error: error_type.to_string(),
-            message: self.to_string(),
+            message: self.public_message(),
             status_code: status_code.as_u16(),
             validation_errors: if let AppError::Validation(errors) = self {
                 Some(errors.clone())
@@ -149,7 +179,16 @@ impl AppError {
                 None
             },
             error_code: Some(format!("E{}", status_code.as_u16())),
-            help,
+            help: help.map(str::to_string),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(source: sqlx::Error) -> Self {
+        Self::Database {
+            source,
+            context: None,
         }
     }
 }
@@ -166,14 +205,20 @@ impl IntoResponse for AppError {
             AppError::RateLimit(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::ExternalService { .. } => StatusCode::BAD_GATEWAY,
             AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
+        self.log_internal();
         let body = serde_json::to_string(&self.error_response())
             .unwrap_or_else(|_| {
                 r#"{"error":"internal_server_error","message":"Failed to serialize error","status_code":500}"#.to_string()
             });
 
-        (status_code, body).into_response()
+        let mut response = (status_code, body).into_response();
+        if let AppError::RateLimit(retry_after) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
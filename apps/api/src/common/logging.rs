@@ -1,19 +1,27 @@
 use std::str::FromStr;
-use tracing::{Level, Subscriber};
+use tracing::Level;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
+    reload,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Registry,
 };
 
-pub fn init(app_name: &str, log_level: &str) {
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-        EnvFilter::new(format!(
-            "{}={},tower_http=debug,axum::rejection=trace",
-            app_name, log_level
-        ))
-    });
+/// Swaps the active filter directives at runtime.
+#[derive(Clone)]
+pub struct LogHandle(reload::Handle<EnvFilter, Registry>);
+
+impl LogHandle {
+    pub fn set_filter(&self, directives: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+        self.0.reload(filter).map_err(|e| e.to_string())
+    }
+}
+
+pub fn init(directives: &str) -> LogHandle {
+    let env_filter = EnvFilter::try_new(directives).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (env_filter, handle) = reload::Layer::new(env_filter);
 
     let formatting_layer = fmt::layer()
         .with_target(true)
@@ -29,7 +37,9 @@ pub fn init(app_name: &str, log_level: &str) {
         .with(formatting_layer)
         .init();
 
-    tracing::info!("Logging initialized with level: {}", log_level);
+    tracing::info!("Logging initialized with filter: {}", directives);
+
+    LogHandle(handle)
 }
 
 pub fn parse_log_level(level: &str) -> Level {
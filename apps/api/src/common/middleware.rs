@@ -1,18 +1,19 @@
 use axum::{
+    body::Body,
     middleware::Next,
     response::Response,
     http::Request,
 };
 use crate::common::logging::RequestId;
 
-pub async fn request_id<B>(req: Request<B>, next: Next<B>) -> Response {
+pub async fn request_id(req: Request<Body>, next: Next) -> Response {
     let request_id = RequestId::new();
-    tracing::Span::current().record("request_id", &request_id.as_str());
+    tracing::Span::current().record("request_id", request_id.as_str());
     
     next.run(req).await
 }
 
-pub async fn request_timer<B>(req: Request<B>, next: Next<B>) -> Response {
+pub async fn request_timer(req: Request<Body>, next: Next) -> Response {
     let start = std::time::Instant::now();
     let response = next.run(req).await;
     let duration = start.elapsed();
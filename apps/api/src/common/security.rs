@@ -1,25 +1,112 @@
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
 };
 
+use crate::config::auth::PasswordHashingConfig;
+use crate::config::Secret;
+
+/// Bytes of the pepper fingerprint stored as the PHC `keyid`.
+const PEPPER_KEY_ID_LENGTH: usize = 4;
+
+/// Argon2id password hashing under a configured cost and optional pepper.
+///
+/// A peppered hash carries a short fingerprint of the pepper as its PHC
+/// `keyid`, so verification knows whether to apply it and hashes made
+/// before a pepper was introduced keep verifying.
+#[derive(Clone, Default)]
+pub struct PasswordHashing {
+    params: Params,
+    pepper: Option<Secret<String>>,
+}
+
+impl PasswordHashing {
+    pub fn new(config: &PasswordHashingConfig) -> Result<Self, argon2::Error> {
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(config.memory_kib)
+            .t_cost(config.iterations)
+            .p_cost(config.parallelism);
+        if let Some(pepper) = &config.pepper {
+            builder.keyid(pepper_key_id(pepper)?);
+        }
+        Ok(Self {
+            params: builder.build()?,
+            pepper: config.pepper.clone(),
+        })
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String, argon2::password_hash::Error> {
+        let salt = SaltString::generate(&mut OsRng);
+        Ok(self
+            .argon2(self.pepper.as_ref())?
+            .hash_password(password.as_bytes(), &salt)?
+            .to_string())
+    }
+
+    pub fn verify(&self, password: &str, hash: &str) -> bool {
+        let Some((parsed, params)) = parse(hash) else {
+            return false;
+        };
+        let pepper = match &self.pepper {
+            _ if params.keyid().is_empty() => None,
+            Some(pepper) if self.params.keyid() == params.keyid() => Some(pepper),
+            _ => {
+                tracing::warn!("Password hash was made with a pepper this server does not have");
+                return false;
+            }
+        };
+        self.argon2(pepper)
+            .is_ok_and(|argon2| argon2.verify_password(password.as_bytes(), &parsed).is_ok())
+    }
+
+    /// Whether `hash` falls short of the current policy: another algorithm
+    /// or version, less memory or fewer iterations, or a different pepper.
+    /// Parallelism is not compared; it does not change the total work.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Some((parsed, params)) = parse(hash) else {
+            return false;
+        };
+        parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13.into())
+            || params.m_cost() < self.params.m_cost()
+            || params.t_cost() < self.params.t_cost()
+            || params.keyid() != self.params.keyid()
+    }
+
+    fn argon2<'a>(&self, pepper: Option<&'a Secret<String>>) -> Result<Argon2<'a>, argon2::Error> {
+        match pepper {
+            Some(pepper) => Argon2::new_with_secret(
+                pepper.expose().as_bytes(),
+                Algorithm::Argon2id,
+                Version::V0x13,
+                self.params.clone(),
+            ),
+            None => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())),
+        }
+    }
+}
+
+fn parse(hash: &str) -> Option<(PasswordHash<'_>, Params)> {
+    let parsed = PasswordHash::new(hash).ok()?;
+    let params = Params::try_from(&parsed).ok()?;
+    Some((parsed, params))
+}
+
+fn pepper_key_id(pepper: &Secret<String>) -> Result<KeyId, argon2::Error> {
+    use sha2::{Digest, Sha256};
+    KeyId::new(&Sha256::digest(pepper.expose().as_bytes())[..PEPPER_KEY_ID_LENGTH])
+}
+
+/// Argon2id with the library's default cost and no pepper. Fine for
+/// high-entropy secrets such as API keys; user passwords go through
+/// [`PasswordHashing`] with the configured policy.
 pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    Ok(argon2
-        .hash_password(password.as_bytes(), &salt)?
-        .to_string())
+    PasswordHashing::default().hash(password)
 }
 
 pub fn verify_password(password: &str, hash: &str) -> bool {
-    let parsed_hash = match PasswordHash::new(hash) {
-        Ok(h) => h,
-        Err(_) => return false,
-    };
-
-    Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok()
+    PasswordHashing::default().verify(password, hash)
 }
 
 pub fn generate_random_token(length: usize) -> String {
@@ -35,4 +122,10 @@ pub fn generate_random_token(length: usize) -> String {
             CHARSET[idx] as char
         })
         .collect()
-}
\ No newline at end of file
+}
+
+/// Hex SHA-256 for looking up high-entropy tokens by hash. Not for passwords.
+pub fn sha256_hex(input: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 pub type Result<T> = std::result::Result<T, crate::common::errors::AppError>;
 pub type JsonMap = HashMap<String, JsonValue>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct Pagination {
     pub page: u32,
     pub per_page: u32,
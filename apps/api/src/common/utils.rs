@@ -1,8 +1,7 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::time::{Duration, SystemTime};
-use tokio::time::{sleep, Duration};
+use tokio::time::sleep;
 
 pub fn timestamp_now() -> i64 {
     SystemTime::now()
@@ -29,7 +28,7 @@ where
 {
     tokio::time::timeout(timeout, future)
         .await
-        .map_err(|_| "operation timed out")?
+        .map_err(|_| "operation timed out")
 }
 
 pub fn mask_sensitive_data(data: &str) -> String {
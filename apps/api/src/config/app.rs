@@ -1,18 +1,91 @@
-use super::env;
+use ipnet::IpNet;
+use std::time::Duration;
+
+use super::loader::ConfigReader;
+use crate::infrastructure::security::network::parse_network;
+
+pub const ENVIRONMENTS: &[&str] = &["development", "test", "staging", "production"];
+pub const STORES: &[&str] = &["memory", "postgres"];
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub host: String,
     pub port: u16,
     pub environment: String,
+    /// Peers whose `X-Forwarded-For` is believed. Empty trusts no one.
+    pub trusted_proxies: Vec<IpNet>,
+    pub rate_limit_store: StoreKind,
+    /// How often network policies changed by other instances are picked up.
+    pub network_policy_refresh: Duration,
+}
+
+/// Where state that instances must agree on, such as rate-limit buckets or
+/// seen request nonces, lives: per instance, or shared through Postgres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreKind {
+    Memory,
+    Postgres,
+}
+
+impl StoreKind {
+    /// Reads `key`, defaulting to `memory`.
+    pub fn from_reader(reader: &mut ConfigReader, key: &str) -> Self {
+        let store: String = reader.or(key, "memory".to_string());
+        match store.as_str() {
+            "memory" => StoreKind::Memory,
+            "postgres" => StoreKind::Postgres,
+            other => {
+                reader.invalid(key, format!("expected one of {}, got `{}`", STORES.join(", "), other));
+                StoreKind::Memory
+            }
+        }
+    }
 }
 
 impl AppConfig {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            host: env::get_var("APP_HOST")?,
-            port: env::get_var("APP_PORT")?,
-            environment: env::get_var_or("APP_ENV", "development".to_string()),
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let host: Option<String> = reader.required("app.host");
+        let port: Option<u16> = reader.required("app.port");
+        let environment: Option<String> = reader.required("app.environment");
+        let proxies: Vec<String> = reader.or("app.trusted_proxies", Vec::new());
+        let rate_limit_store = StoreKind::from_reader(reader, "app.rate_limit_store");
+        let policy_refresh_secs: u64 = reader.or("app.network_policy_refresh_secs", 30);
+
+        if let Some(env) = &environment {
+            if !ENVIRONMENTS.contains(&env.as_str()) {
+                reader.invalid(
+                    "app.environment",
+                    format!("expected one of {}, got `{}`", ENVIRONMENTS.join(", "), env),
+                );
+            }
+        }
+
+        let mut trusted_proxies = Vec::with_capacity(proxies.len());
+        for proxy in &proxies {
+            match parse_network(proxy) {
+                Some(network) => trusted_proxies.push(network),
+                None => reader.invalid(
+                    "app.trusted_proxies",
+                    format!("`{}` is not an IP address or CIDR range", proxy),
+                ),
+            }
+        }
+
+        if policy_refresh_secs == 0 {
+            reader.invalid("app.network_policy_refresh_secs", "must be greater than zero");
+        }
+
+        Some(Self {
+            host: host?,
+            port: port?,
+            environment: environment?,
+            trusted_proxies,
+            rate_limit_store,
+            network_policy_refresh: Duration::from_secs(policy_refresh_secs),
         })
     }
+
+    pub fn is_production(&self) -> bool {
+        self.environment == "production"
+    }
 }
@@ -0,0 +1,562 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use url::Url;
+
+use super::app::StoreKind;
+use super::loader::ConfigReader;
+use super::Secret;
+use crate::infrastructure::security::signing_keys::KeyAlgorithm;
+use crate::common::constants::{ACCESS_TOKEN_DURATION, REFRESH_TOKEN_DURATION};
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// `iss` claim of issued access tokens, checked on verification.
+    pub issuer: String,
+    /// `aud` claim of issued access tokens, checked on verification.
+    pub audience: String,
+    pub access_token_ttl: Duration,
+    pub refresh_token_ttl: Duration,
+    /// How often each instance reloads signing keys, so a rotation made by
+    /// another process is picked up.
+    pub key_refresh_interval: Duration,
+    /// Algorithm for keys created by rotation. Existing keys keep theirs.
+    pub signing_algorithm: KeyAlgorithm,
+    /// How long an instance trusts its last look at a session before
+    /// checking again whether it was revoked. Zero checks every request.
+    pub session_check_interval: Duration,
+    pub request_signing: RequestSigningConfig,
+    pub mfa: MfaConfig,
+    pub oidc: OidcConfig,
+    pub lockout: LockoutConfig,
+    pub password_hashing: PasswordHashingConfig,
+    pub password_policy: PasswordPolicyConfig,
+    /// Key for the HMAC-SHA256 digests of API key secrets, kept out of the
+    /// database. Changing it invalidates every issued key.
+    pub api_key_pepper: Secret<String>,
+}
+
+/// Two-factor authentication.
+#[derive(Debug, Clone)]
+pub struct MfaConfig {
+    /// Issuer shown by authenticator apps next to the account.
+    pub issuer: String,
+    /// Lifetime of the "mfa pending" token issued after the password step.
+    pub pending_ttl: Duration,
+    pub recovery_codes: usize,
+    /// Roles that only take effect in sessions verified with a second
+    /// factor. Holders without 2FA keep their other roles.
+    pub required_roles: Vec<String>,
+}
+
+impl MfaConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let issuer: String = reader.or("auth.mfa.issuer", "m5".to_string());
+        let pending_secs: u64 = reader.or("auth.mfa.pending_ttl_secs", 300);
+        let recovery_codes: usize = reader.or("auth.mfa.recovery_codes", 10);
+        let required_roles: Vec<String> = reader.or("auth.mfa.required_roles", Vec::new());
+
+        if issuer.trim().is_empty() {
+            reader.invalid("auth.mfa.issuer", "must not be empty");
+        }
+        if pending_secs == 0 {
+            reader.invalid("auth.mfa.pending_ttl_secs", "must be greater than zero");
+        }
+        if !(1..=50).contains(&recovery_codes) {
+            reader.invalid("auth.mfa.recovery_codes", "must be between 1 and 50");
+        }
+
+        Some(Self {
+            issuer,
+            pending_ttl: Duration::from_secs(pending_secs),
+            recovery_codes,
+            required_roles,
+        })
+    }
+}
+
+/// Argon2id cost parameters for user passwords. Hashes stored with weaker
+/// parameters, or without the current pepper, are upgraded at the owner's
+/// next successful login.
+#[derive(Debug, Clone)]
+pub struct PasswordHashingConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Server-side secret mixed into every hash, kept out of the database.
+    /// Hashes made with a pepper cannot be verified without it.
+    pub pepper: Option<Secret<String>>,
+}
+
+/// Peppers shorter than this are rejected at startup.
+const MIN_PEPPER_LENGTH: usize = 32;
+
+impl PasswordHashingConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let memory_kib: u32 = reader.or("auth.password_hashing.memory_kib", argon2::Params::DEFAULT_M_COST);
+        let iterations: u32 = reader.or("auth.password_hashing.iterations", argon2::Params::DEFAULT_T_COST);
+        let parallelism: u32 = reader.or("auth.password_hashing.parallelism", argon2::Params::DEFAULT_P_COST);
+        let pepper = reader.optional_secret("auth.password_hashing.pepper");
+
+        if let Err(e) = argon2::Params::new(memory_kib, iterations, parallelism, None) {
+            reader.invalid("auth.password_hashing", format!("unusable argon2 parameters: {}", e));
+        }
+        if pepper.as_ref().is_some_and(|pepper| pepper.expose().len() < MIN_PEPPER_LENGTH) {
+            reader.invalid(
+                "auth.password_hashing.pepper",
+                format!("must be at least {} characters", MIN_PEPPER_LENGTH),
+            );
+        }
+
+        Some(Self {
+            memory_kib,
+            iterations,
+            parallelism,
+            pepper,
+        })
+    }
+}
+
+impl Default for PasswordHashingConfig {
+    /// The argon2 crate's defaults (OWASP's minimum), without a pepper.
+    fn default() -> Self {
+        Self {
+            memory_kib: argon2::Params::DEFAULT_M_COST,
+            iterations: argon2::Params::DEFAULT_T_COST,
+            parallelism: argon2::Params::DEFAULT_P_COST,
+            pepper: None,
+        }
+    }
+}
+
+/// Rules new passwords must meet. Lengths count characters, not bytes.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Reject passwords containing the local part of the user's email or a
+    /// word of their name.
+    pub reject_personal_info: bool,
+    /// Sorted file of SHA-1 hashes (or hash prefixes) of breached
+    /// passwords, one per line with an optional `:count`, such as the
+    /// "ordered by hash" Pwned Passwords download.
+    pub breached_passwords: Option<PathBuf>,
+    /// Occurrences in the corpus at which a password is rejected.
+    pub breached_min_count: u64,
+}
+
+/// Upper bound for `max_length`, so a policy cannot invite huge inputs.
+const MAX_PASSWORD_LENGTH_LIMIT: usize = 1024;
+
+impl PasswordPolicyConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let min_length: usize = reader.or("auth.password_policy.min_length", 8);
+        let max_length: usize = reader.or("auth.password_policy.max_length", 72);
+        let require_uppercase = reader.or("auth.password_policy.require_uppercase", true);
+        let require_lowercase = reader.or("auth.password_policy.require_lowercase", true);
+        let require_digit = reader.or("auth.password_policy.require_digit", true);
+        let require_symbol = reader.or("auth.password_policy.require_symbol", true);
+        let reject_personal_info = reader.or("auth.password_policy.reject_personal_info", true);
+        let breached_passwords: Option<PathBuf> = reader.optional("auth.password_policy.breached_passwords");
+        let breached_min_count: u64 = reader.or("auth.password_policy.breached_min_count", 1);
+
+        if min_length == 0 {
+            reader.invalid("auth.password_policy.min_length", "must be greater than zero");
+        }
+        if max_length < min_length || max_length > MAX_PASSWORD_LENGTH_LIMIT {
+            reader.invalid(
+                "auth.password_policy.max_length",
+                format!(
+                    "must be between auth.password_policy.min_length ({}) and {}",
+                    min_length, MAX_PASSWORD_LENGTH_LIMIT
+                ),
+            );
+        }
+        if breached_min_count == 0 {
+            reader.invalid("auth.password_policy.breached_min_count", "must be greater than zero");
+        }
+
+        Some(Self {
+            min_length,
+            max_length,
+            require_uppercase,
+            require_lowercase,
+            require_digit,
+            require_symbol,
+            reject_personal_info,
+            breached_passwords,
+            breached_min_count,
+        })
+    }
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 72,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: true,
+            reject_personal_info: true,
+            breached_passwords: None,
+            breached_min_count: 1,
+        }
+    }
+}
+
+/// Throttling of failed password logins. Failures are counted per account
+/// and per source IP; past `free_attempts` each failure blocks the key for
+/// an exponentially growing delay, and reaching a threshold locks it for
+/// `lockout_duration`.
+#[derive(Debug, Clone)]
+pub struct LockoutConfig {
+    pub enabled: bool,
+    /// Failures older than this no longer count.
+    pub window: Duration,
+    pub free_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub account_threshold: u32,
+    pub ip_threshold: u32,
+    pub lockout_duration: Duration,
+}
+
+impl LockoutConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let enabled = reader.or("auth.lockout.enabled", true);
+        let window_secs: u64 = reader.or("auth.lockout.window_secs", 900);
+        let free_attempts: u32 = reader.or("auth.lockout.free_attempts", 3);
+        let base_delay_secs: u64 = reader.or("auth.lockout.base_delay_secs", 1);
+        let max_delay_secs: u64 = reader.or("auth.lockout.max_delay_secs", 60);
+        let account_threshold: u32 = reader.or("auth.lockout.account_threshold", 10);
+        let ip_threshold: u32 = reader.or("auth.lockout.ip_threshold", 50);
+        let lockout_secs: u64 = reader.or("auth.lockout.lockout_secs", 900);
+
+        if window_secs == 0 {
+            reader.invalid("auth.lockout.window_secs", "must be greater than zero");
+        }
+        if max_delay_secs < base_delay_secs {
+            reader.invalid(
+                "auth.lockout.max_delay_secs",
+                format!("must be at least auth.lockout.base_delay_secs ({})", base_delay_secs),
+            );
+        }
+        for (key, threshold) in [
+            ("auth.lockout.account_threshold", account_threshold),
+            ("auth.lockout.ip_threshold", ip_threshold),
+        ] {
+            if threshold <= free_attempts {
+                reader.invalid(key, format!("must exceed auth.lockout.free_attempts ({})", free_attempts));
+            }
+        }
+        if lockout_secs == 0 {
+            reader.invalid("auth.lockout.lockout_secs", "must be greater than zero");
+        }
+
+        Some(Self {
+            enabled,
+            window: Duration::from_secs(window_secs),
+            free_attempts,
+            base_delay: Duration::from_secs(base_delay_secs),
+            max_delay: Duration::from_secs(max_delay_secs),
+            account_threshold,
+            ip_threshold,
+            lockout_duration: Duration::from_secs(lockout_secs),
+        })
+    }
+
+    /// How long a key stays blocked after its `failures`th failure, and
+    /// whether that block is a lockout.
+    pub fn block_after(&self, failures: u32, threshold: u32) -> Option<(Duration, bool)> {
+        if failures >= threshold {
+            return Some((self.lockout_duration, true));
+        }
+        if failures <= self.free_attempts || self.base_delay.is_zero() {
+            return None;
+        }
+        let exponent = (failures - self.free_attempts - 1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        Some((delay, false))
+    }
+}
+
+/// An external OpenID Connect provider users can sign in with. Endpoints
+/// come from `{issuer}/.well-known/openid-configuration`.
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Identifier used in routes and stored with linked identities.
+    pub name: String,
+    pub display_name: String,
+    pub issuer: Url,
+    pub client_id: String,
+    /// Absent for public clients, which rely on PKCE alone.
+    pub client_secret: Option<Secret<String>>,
+    /// Where the provider sends the browser back to; the frontend there
+    /// posts `code` and `state` to the callback endpoint.
+    pub redirect_uri: Url,
+    pub scopes: Vec<String>,
+    /// Sign in an existing account whose email matches a verified email
+    /// from the provider, linking the identity on first use.
+    pub link_by_email: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// How long an authorization request may take to come back.
+    pub state_ttl: Duration,
+    pub http_timeout: Duration,
+    pub providers: Vec<OidcProviderConfig>,
+}
+
+impl OidcConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let state_secs: u64 = reader.or("auth.oidc.state_ttl_secs", 600);
+        let timeout_secs: u64 = reader.or("auth.oidc.http_timeout_secs", 10);
+
+        let mut providers: Vec<OidcProviderConfig> = Vec::new();
+        let mut complete = true;
+        for index in 0..reader.array_len("auth.oidc.providers") {
+            let prefix = format!("auth.oidc.providers[{}]", index);
+            let name: Option<String> = reader.required(&format!("{}.name", prefix));
+            let display_name: Option<String> = reader.optional(&format!("{}.display_name", prefix));
+            let issuer: Option<String> = reader.required(&format!("{}.issuer", prefix));
+            let client_id: Option<String> = reader.required(&format!("{}.client_id", prefix));
+            let client_secret = reader.optional_secret(&format!("{}.client_secret", prefix));
+            let redirect_uri: Option<String> = reader.required(&format!("{}.redirect_uri", prefix));
+            let scopes: Vec<String> = reader.or(
+                &format!("{}.scopes", prefix),
+                vec!["openid".to_string(), "email".to_string(), "profile".to_string()],
+            );
+            let link_by_email: bool = reader.or(&format!("{}.link_by_email", prefix), true);
+            let (Some(name), Some(issuer), Some(client_id), Some(redirect_uri)) =
+                (name, issuer, client_id, redirect_uri)
+            else {
+                complete = false;
+                continue;
+            };
+
+            let valid_name = !name.is_empty()
+                && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+            if !valid_name {
+                reader.invalid(&format!("{}.name", prefix), "must be lowercase letters, digits and `-`");
+            } else if providers.iter().any(|provider| provider.name == name) {
+                reader.invalid(&format!("{}.name", prefix), format!("duplicate provider `{}`", name));
+            }
+            if client_id.trim().is_empty() {
+                reader.invalid(&format!("{}.client_id", prefix), "must not be empty");
+            }
+            if !scopes.iter().any(|scope| scope == "openid") {
+                reader.invalid(&format!("{}.scopes", prefix), "must include `openid`");
+            }
+            let issuer = parse_url(reader, &format!("{}.issuer", prefix), &issuer);
+            let redirect_uri = parse_url(reader, &format!("{}.redirect_uri", prefix), &redirect_uri);
+            let (Some(issuer), Some(redirect_uri)) = (issuer, redirect_uri) else {
+                complete = false;
+                continue;
+            };
+
+            providers.push(OidcProviderConfig {
+                display_name: display_name.unwrap_or_else(|| name.clone()),
+                name,
+                issuer,
+                client_id,
+                client_secret,
+                redirect_uri,
+                scopes,
+                link_by_email,
+            });
+        }
+
+        if state_secs == 0 {
+            reader.invalid("auth.oidc.state_ttl_secs", "must be greater than zero");
+        }
+        if timeout_secs == 0 {
+            reader.invalid("auth.oidc.http_timeout_secs", "must be greater than zero");
+        }
+        if !complete {
+            return None;
+        }
+
+        Some(Self {
+            state_ttl: Duration::from_secs(state_secs),
+            http_timeout: Duration::from_secs(timeout_secs),
+            providers,
+        })
+    }
+}
+
+/// Provider URLs must be https; plain http is allowed for loopback hosts
+/// so a local provider (or a mock in tests) can be used.
+fn parse_url(reader: &mut ConfigReader, key: &str, value: &str) -> Option<Url> {
+    let url = match Url::parse(value) {
+        Ok(url) => url,
+        Err(e) => {
+            reader.invalid(key, format!("invalid URL: {}", e));
+            return None;
+        }
+    };
+    let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if url.scheme() != "https" && !(url.scheme() == "http" && loopback) {
+        reader.invalid(key, "must use https");
+        return None;
+    }
+    Some(url)
+}
+
+/// Shared secret a machine client signs requests with.
+#[derive(Debug, Clone)]
+pub struct SigningClient {
+    pub key_id: String,
+    pub secret: Secret<String>,
+}
+
+/// HMAC request signing, layered on top of bearer authentication.
+#[derive(Debug, Clone)]
+pub struct RequestSigningConfig {
+    /// Reject unsigned requests made with an API key. Requests with user
+    /// access tokens are never required to be signed.
+    pub require_for_api_keys: bool,
+    /// How far a request timestamp may be from the server clock.
+    pub max_clock_skew: Duration,
+    /// Where seen nonces are kept. With `memory`, each instance only knows
+    /// its own, so a captured request can be replayed once against every
+    /// other instance within the clock skew.
+    pub nonce_store: StoreKind,
+    /// Upper bound on nonces remembered in memory. Signed requests are
+    /// refused with 429 while it is reached.
+    pub nonce_capacity: usize,
+    /// Largest body buffered for verification.
+    pub max_body_bytes: usize,
+    pub clients: Vec<SigningClient>,
+}
+
+/// Secrets shorter than this are rejected at startup.
+const MIN_SIGNING_SECRET_LENGTH: usize = 32;
+
+impl RequestSigningConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let require_for_api_keys = reader.or("auth.request_signing.require_for_api_keys", false);
+        let skew_secs: u64 = reader.or("auth.request_signing.max_clock_skew_secs", 300);
+        let nonce_store = StoreKind::from_reader(reader, "auth.request_signing.nonce_store");
+        let nonce_capacity: usize = reader.or("auth.request_signing.nonce_capacity", 100_000);
+        let max_body_bytes: usize = reader.or("auth.request_signing.max_body_bytes", 10 * 1024 * 1024);
+
+        let mut clients: Vec<SigningClient> = Vec::new();
+        for index in 0..reader.array_len("auth.request_signing.clients") {
+            let prefix = format!("auth.request_signing.clients[{}]", index);
+            let key_id: Option<String> = reader.required(&format!("{}.key_id", prefix));
+            let secret = reader.required_secret(&format!("{}.secret", prefix));
+            let (Some(key_id), Some(secret)) = (key_id, secret) else {
+                continue;
+            };
+            if key_id.trim().is_empty() {
+                reader.invalid(&format!("{}.key_id", prefix), "must not be empty");
+            } else if clients.iter().any(|client| client.key_id == key_id) {
+                reader.invalid(&format!("{}.key_id", prefix), format!("duplicate key id `{}`", key_id));
+            }
+            if secret.expose().len() < MIN_SIGNING_SECRET_LENGTH {
+                reader.invalid(
+                    &format!("{}.secret", prefix),
+                    format!("must be at least {} characters", MIN_SIGNING_SECRET_LENGTH),
+                );
+            }
+            clients.push(SigningClient { key_id, secret });
+        }
+
+        if skew_secs == 0 {
+            reader.invalid("auth.request_signing.max_clock_skew_secs", "must be greater than zero");
+        }
+        if nonce_capacity == 0 {
+            reader.invalid("auth.request_signing.nonce_capacity", "must be greater than zero");
+        }
+        if require_for_api_keys && clients.is_empty() {
+            reader.invalid(
+                "auth.request_signing.require_for_api_keys",
+                "requires at least one entry in auth.request_signing.clients",
+            );
+        }
+
+        Some(Self {
+            require_for_api_keys,
+            max_clock_skew: Duration::from_secs(skew_secs),
+            nonce_store,
+            nonce_capacity,
+            max_body_bytes,
+            clients,
+        })
+    }
+}
+
+impl AuthConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let issuer: String = reader.or("auth.issuer", "m5".to_string());
+        let audience: String = reader.or("auth.audience", "m5-api".to_string());
+        let access_secs: u64 = reader.or("auth.access_token_ttl_secs", ACCESS_TOKEN_DURATION as u64);
+        let refresh_secs: u64 = reader.or("auth.refresh_token_ttl_secs", REFRESH_TOKEN_DURATION as u64);
+        let key_refresh_secs: u64 = reader.or("auth.key_refresh_interval_secs", 60);
+        let session_check_secs: u64 = reader.or("auth.session_check_interval_secs", 30);
+        let algorithm: String = reader.or("auth.signing_algorithm", KeyAlgorithm::EdDSA.as_str().to_string());
+        let request_signing = RequestSigningConfig::from_reader(reader);
+        let mfa = MfaConfig::from_reader(reader);
+        let oidc = OidcConfig::from_reader(reader);
+        let lockout = LockoutConfig::from_reader(reader);
+        let password_hashing = PasswordHashingConfig::from_reader(reader);
+        let password_policy = PasswordPolicyConfig::from_reader(reader);
+        let api_key_pepper = reader.required_secret("auth.api_key_pepper");
+
+        if access_secs == 0 {
+            reader.invalid("auth.access_token_ttl_secs", "must be greater than zero");
+        }
+        if refresh_secs <= access_secs {
+            reader.invalid(
+                "auth.refresh_token_ttl_secs",
+                format!("must exceed auth.access_token_ttl_secs ({})", access_secs),
+            );
+        }
+        if key_refresh_secs == 0 {
+            reader.invalid("auth.key_refresh_interval_secs", "must be greater than zero");
+        }
+        if session_check_secs >= access_secs {
+            reader.invalid(
+                "auth.session_check_interval_secs",
+                format!("must be less than auth.access_token_ttl_secs ({})", access_secs),
+            );
+        }
+        if api_key_pepper.as_ref().is_some_and(|pepper| pepper.expose().len() < MIN_PEPPER_LENGTH) {
+            reader.invalid(
+                "auth.api_key_pepper",
+                format!("must be at least {} characters", MIN_PEPPER_LENGTH),
+            );
+        }
+        let signing_algorithm = match algorithm.parse::<KeyAlgorithm>() {
+            Ok(algorithm) => Some(algorithm),
+            Err(e) => {
+                reader.invalid("auth.signing_algorithm", e.to_string());
+                None
+            }
+        };
+
+        Some(Self {
+            issuer,
+            audience,
+            access_token_ttl: Duration::from_secs(access_secs),
+            refresh_token_ttl: Duration::from_secs(refresh_secs),
+            key_refresh_interval: Duration::from_secs(key_refresh_secs),
+            signing_algorithm: signing_algorithm?,
+            session_check_interval: Duration::from_secs(session_check_secs),
+            request_signing: request_signing?,
+            mfa: mfa?,
+            oidc: oidc?,
+            lockout: lockout?,
+            password_hashing: password_hashing?,
+            password_policy: password_policy?,
+            api_key_pepper: api_key_pepper?,
+        })
+    }
+}
@@ -1,32 +1,238 @@
-use super::env;
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::loader::ConfigReader;
+use super::secret::Secret;
+
+pub const SSL_MODES: &[&str] = &["disable", "allow", "prefer", "require", "verify-ca", "verify-full"];
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
+    pub url: Option<Secret<String>>,
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub password: String,
+    pub password: Secret<String>,
     pub database_name: String,
     pub ssl_mode: String,
+    pub pool: PoolConfig,
+    pub replicas: Vec<ReplicaConfig>,
+    pub replica_health_interval: Duration,
+    /// How long a principal's reads stay on the primary after it writes,
+    /// so its next requests see the write despite replica lag.
+    pub read_after_write: Duration,
+    pub migrations: MigrationMode,
+}
+
+/// What `bootstrap::init` does with pending migrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationMode {
+    Auto,
+    VerifyOnly,
+    Skip,
+}
+
+impl FromStr for MigrationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(MigrationMode::Auto),
+            "verify" => Ok(MigrationMode::VerifyOnly),
+            "skip" => Ok(MigrationMode::Skip),
+            other => Err(format!("expected one of auto, verify, skip, got `{}`", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub min_connections: u32,
+    pub max_connections: u32,
+    pub max_lifetime: Duration,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    pub name: String,
+    pub url: Secret<String>,
+    pub pool: PoolConfig,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            min_connections: 2,
+            max_connections: 30,
+            max_lifetime: Duration::from_secs(30 * 60),
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Reads `{prefix}.min_connections` etc., falling back to `defaults`.
+    pub fn from_reader(reader: &mut ConfigReader, prefix: &str, defaults: &PoolConfig) -> Self {
+        let key = |name: &str| format!("{}.{}", prefix, name);
+
+        let pool = Self {
+            min_connections: reader.or(&key("min_connections"), defaults.min_connections),
+            max_connections: reader.or(&key("max_connections"), defaults.max_connections),
+            max_lifetime: Duration::from_secs(
+                reader.or(&key("max_lifetime_secs"), defaults.max_lifetime.as_secs()),
+            ),
+            acquire_timeout: Duration::from_secs(
+                reader.or(&key("acquire_timeout_secs"), defaults.acquire_timeout.as_secs()),
+            ),
+            idle_timeout: Duration::from_secs(
+                reader.or(&key("idle_timeout_secs"), defaults.idle_timeout.as_secs()),
+            ),
+        };
+
+        if pool.max_connections == 0 {
+            reader.invalid(&key("max_connections"), "must be greater than zero");
+        }
+        if pool.min_connections > pool.max_connections {
+            reader.invalid(
+                &key("min_connections"),
+                format!("must not exceed max_connections ({})", pool.max_connections),
+            );
+        }
+
+        pool
+    }
 }
 
 impl DatabaseConfig {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            host: env::get_var("DB_HOST")?,
-            port: env::get_var("DB_PORT")?,
-            username: env::get_var("DB_USER")?,
-            password: env::get_var("DB_PASSWORD")?,
-            database_name: env::get_var("DB_NAME")?,
-            ssl_mode: env::get_var_or("DB_SSL_MODE", "prefer".to_string()),
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let url = reader.optional_secret("database.url");
+        let host: Option<String> = reader.required("database.host");
+        let port: Option<u16> = reader.required("database.port");
+        let username: Option<String> = reader.required("database.username");
+        let database_name: Option<String> = reader.required("database.database_name");
+        let ssl_mode: String = reader.or("database.ssl_mode", "prefer".to_string());
+        let pool = PoolConfig::from_reader(reader, "database.pool", &PoolConfig::default());
+        let replicas = ReplicaConfig::from_reader(reader, &pool);
+        let health_interval_secs: u64 = reader.or("database.replica_health_interval_secs", 10);
+        let read_after_write_secs: u64 = reader.or("database.read_after_write_secs", 5);
+        let migrations: String = reader.or("database.migrations", "auto".to_string());
+        let migrations = MigrationMode::from_str(&migrations)
+            .inspect_err(|err| reader.invalid("database.migrations", err.clone()))
+            .ok();
+
+        // A full DATABASE_URL carries its own credentials.
+        let password = if url.is_some() {
+            Some(reader.optional_secret("database.password").unwrap_or_default())
+        } else {
+            reader.required_secret("database.password")
+        };
+
+        if let Some(url) = &url {
+            if !is_postgres_url(url.expose()) {
+                reader.invalid("database.url", "expected a postgres:// or postgresql:// URL");
+            }
+        }
+        if health_interval_secs == 0 {
+            reader.invalid("database.replica_health_interval_secs", "must be greater than zero");
+        }
+        if !SSL_MODES.contains(&ssl_mode.as_str()) {
+            reader.invalid(
+                "database.ssl_mode",
+                format!("expected one of {}, got `{}`", SSL_MODES.join(", "), ssl_mode),
+            );
+        }
+
+        Some(Self {
+            url,
+            host: host?,
+            port: port?,
+            username: username?,
+            password: password?,
+            database_name: database_name?,
+            ssl_mode,
+            pool,
+            replicas,
+            replica_health_interval: Duration::from_secs(health_interval_secs),
+            read_after_write: Duration::from_secs(read_after_write_secs),
+            migrations: migrations?,
         })
     }
 
-    pub fn connection_string(&self) -> String {
-        format!(
-            "postgres://{}:{}@{}:{}/{}?sslmode={}",
-            self.username, self.password, self.host,
-            self.port, self.database_name, self.ssl_mode
-        )
+    /// Builds connection options without ever formatting the password into
+    /// a connection string. An explicit password overrides one in the URL.
+    pub fn connect_options(&self) -> PgConnectOptions {
+        let ssl_mode = PgSslMode::from_str(&self.ssl_mode).unwrap_or_default();
+
+        let options = match &self.url {
+            Some(url) => PgConnectOptions::from_str(url.expose())
+                .unwrap_or_else(|_| PgConnectOptions::new()),
+            None => PgConnectOptions::new()
+                .host(&self.host)
+                .port(self.port)
+                .username(&self.username)
+                .database(&self.database_name)
+                .ssl_mode(ssl_mode),
+        };
+
+        if self.password.is_empty() {
+            options
+        } else {
+            options.password(self.password.expose())
+        }
     }
-}
\ No newline at end of file
+}
+
+impl ReplicaConfig {
+    /// Replicas come from `[[database.replicas]]` tables (each with a `url`
+    /// or `url_file`, an optional `name` and optional `pool` overrides) plus
+    /// the comma-separated `DATABASE_REPLICA_URLS` shorthand.
+    pub fn from_reader(reader: &mut ConfigReader, primary_pool: &PoolConfig) -> Vec<Self> {
+        let mut replicas = Vec::new();
+
+        for index in 0..reader.array_len("database.replicas") {
+            let prefix = format!("database.replicas[{}]", index);
+            let name = reader.or(&format!("{}.name", prefix), format!("replica-{}", index));
+            let url = reader.required_secret(&format!("{}.url", prefix));
+            let pool = PoolConfig::from_reader(reader, &format!("{}.pool", prefix), primary_pool);
+
+            if let Some(url) = url {
+                if !is_postgres_url(url.expose()) {
+                    reader.invalid(&format!("{}.url", prefix), "expected a postgres:// or postgresql:// URL");
+                }
+                replicas.push(Self { name, url, pool });
+            }
+        }
+
+        if let Some(urls) = reader.optional_secret("database.replica_urls") {
+            let offset = replicas.len();
+            for (index, url) in urls.expose().split(',').map(str::trim).filter(|u| !u.is_empty()).enumerate() {
+                if !is_postgres_url(url) {
+                    reader.invalid(
+                        "database.replica_urls",
+                        format!("entry {} is not a postgres:// or postgresql:// URL", index),
+                    );
+                }
+                replicas.push(Self {
+                    name: format!("replica-{}", offset + index),
+                    url: Secret::new(url.to_string()),
+                    pool: primary_pool.clone(),
+                });
+            }
+        }
+
+        replicas
+    }
+
+    pub fn connect_options(&self) -> PgConnectOptions {
+        PgConnectOptions::from_str(self.url.expose()).unwrap_or_else(|_| PgConnectOptions::new())
+    }
+}
+
+fn is_postgres_url(url: &str) -> bool {
+    (url.starts_with("postgres://") || url.starts_with("postgresql://"))
+        && PgConnectOptions::from_str(url).is_ok()
+}
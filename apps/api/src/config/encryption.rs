@@ -0,0 +1,65 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use super::loader::ConfigReader;
+use super::secret::Secret;
+
+/// Length of every encryption key, in bytes: AES-256 and HMAC-SHA256.
+pub const KEY_LENGTH: usize = 32;
+
+/// Keys for field-level encryption of data at rest. Each is 32 random
+/// bytes, base64-encoded; `cli encryption generate-key` prints one.
+#[derive(Debug, Clone)]
+pub struct EncryptionConfig {
+    /// Wraps the data key of every value encrypted from now on.
+    pub master_key: Secret<Vec<u8>>,
+    /// Earlier master keys. Values written under them stay readable until
+    /// `cli encryption re-encrypt` has moved them to `master_key`.
+    pub retired_master_keys: Vec<Secret<Vec<u8>>>,
+    /// Keys the HMAC blind indexes used to look up encrypted values.
+    /// Changing it breaks every stored index, so it is not rotated with
+    /// the master key.
+    pub blind_index_key: Secret<Vec<u8>>,
+}
+
+impl EncryptionConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let master_key = reader.required_secret("encryption.master_key");
+        let retired = reader.optional_secret("encryption.retired_master_keys");
+        let blind_index_key = reader.required_secret("encryption.blind_index_key");
+
+        let master_key = master_key.and_then(|key| decode_key(reader, "encryption.master_key", key.expose()));
+        let retired_master_keys = retired
+            .map(|keys| {
+                keys.expose()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .filter_map(|key| decode_key(reader, "encryption.retired_master_keys", key))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let blind_index_key =
+            blind_index_key.and_then(|key| decode_key(reader, "encryption.blind_index_key", key.expose()));
+
+        Some(Self {
+            master_key: master_key?,
+            retired_master_keys,
+            blind_index_key: blind_index_key?,
+        })
+    }
+}
+
+fn decode_key(reader: &mut ConfigReader, key: &str, value: &str) -> Option<Secret<Vec<u8>>> {
+    match STANDARD.decode(value.trim()) {
+        Ok(bytes) if bytes.len() == KEY_LENGTH => Some(Secret::new(bytes)),
+        Ok(bytes) => {
+            reader.invalid(key, format!("must decode to {} bytes, got {}", KEY_LENGTH, bytes.len()));
+            None
+        }
+        Err(_) => {
+            reader.invalid(key, "must be base64");
+            None
+        }
+    }
+}
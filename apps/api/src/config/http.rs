@@ -0,0 +1,141 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::time::Duration;
+
+use super::loader::ConfigReader;
+
+/// Layers the server applies to every request.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Requests still running after this long are answered with 408.
+    pub request_timeout: Duration,
+    /// Larger request bodies are refused with 413.
+    pub max_body_bytes: usize,
+    /// Compress responses for clients that accept gzip.
+    pub compression: bool,
+    pub cors: CorsConfig,
+}
+
+/// Cross-origin access for browsers. With no allowed origins no CORS
+/// headers are sent, so only same-origin pages can call the API.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: AllowedOrigins,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    pub allow_credentials: bool,
+    pub max_age: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// `*`: any origin, without credentials.
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+impl AllowedOrigins {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, AllowedOrigins::List(origins) if origins.is_empty())
+    }
+}
+
+impl HttpConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let timeout_secs: u64 = reader.or("http.request_timeout_secs", 30);
+        let max_body_bytes: usize = reader.or("http.max_body_bytes", 2 * 1024 * 1024);
+        let compression: bool = reader.or("http.compression", true);
+
+        if timeout_secs == 0 {
+            reader.invalid("http.request_timeout_secs", "must be greater than zero");
+        }
+        if max_body_bytes == 0 {
+            reader.invalid("http.max_body_bytes", "must be greater than zero");
+        }
+
+        Some(Self {
+            request_timeout: Duration::from_secs(timeout_secs),
+            max_body_bytes,
+            compression,
+            cors: CorsConfig::from_reader(reader),
+        })
+    }
+}
+
+impl CorsConfig {
+    fn from_reader(reader: &mut ConfigReader) -> Self {
+        let origins: Vec<String> = reader.or("http.cors.allowed_origins", Vec::new());
+        let methods: Vec<String> = reader.or(
+            "http.cors.allowed_methods",
+            ["GET", "POST", "PUT", "PATCH", "DELETE"].map(String::from).to_vec(),
+        );
+        let headers: Vec<String> = reader.or(
+            "http.cors.allowed_headers",
+            ["authorization", "content-type", "x-m5-device-label"].map(String::from).to_vec(),
+        );
+        let allow_credentials: bool = reader.or("http.cors.allow_credentials", false);
+        let max_age_secs: u64 = reader.or("http.cors.max_age_secs", 3600);
+
+        let allowed_origins = if origins.iter().any(|origin| origin == "*") {
+            if origins.len() > 1 {
+                reader.invalid("http.cors.allowed_origins", "`*` cannot be combined with other origins");
+            }
+            if allow_credentials {
+                reader.invalid("http.cors.allow_credentials", "cannot be enabled when any origin is allowed");
+            }
+            AllowedOrigins::Any
+        } else {
+            let mut allowed = Vec::with_capacity(origins.len());
+            for origin in &origins {
+                match parse_origin(origin) {
+                    Some(value) => allowed.push(value),
+                    None => reader.invalid(
+                        "http.cors.allowed_origins",
+                        format!("`{}` is not an origin like https://app.example.com", origin),
+                    ),
+                }
+            }
+            AllowedOrigins::List(allowed)
+        };
+
+        let mut allowed_methods = Vec::with_capacity(methods.len());
+        for method in &methods {
+            match Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes()) {
+                Ok(method) => allowed_methods.push(method),
+                Err(_) => reader.invalid(
+                    "http.cors.allowed_methods",
+                    format!("`{}` is not an HTTP method", method),
+                ),
+            }
+        }
+
+        let mut allowed_headers = Vec::with_capacity(headers.len());
+        for header in &headers {
+            match HeaderName::from_bytes(header.trim().as_bytes()) {
+                Ok(name) => allowed_headers.push(name),
+                Err(_) => reader.invalid(
+                    "http.cors.allowed_headers",
+                    format!("`{}` is not a header name", header),
+                ),
+            }
+        }
+
+        Self {
+            allowed_origins,
+            allowed_methods,
+            allowed_headers,
+            allow_credentials,
+            max_age: Duration::from_secs(max_age_secs),
+        }
+    }
+}
+
+/// Browsers send `Origin` as scheme, host and optional port, with no path
+/// or trailing slash; anything else would never match.
+fn parse_origin(origin: &str) -> Option<HeaderValue> {
+    let origin = origin.trim();
+    let (scheme, host) = origin.split_once("://")?;
+    if !matches!(scheme, "http" | "https") || host.is_empty() || host.contains('/') {
+        return None;
+    }
+    HeaderValue::from_str(origin).ok()
+}
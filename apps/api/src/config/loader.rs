@@ -0,0 +1,402 @@
+use config::{
+    builder::DefaultState, ConfigBuilder, Environment, File, Map, Source, Value, ValueKind,
+};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::path::PathBuf;
+use zeroize::Zeroize;
+
+use super::secret::Secret;
+
+pub const DEFAULT_CONFIG_DIR: &str = "config";
+pub const DEFAULT_PROFILE: &str = "development";
+pub const ENV_PREFIX: &str = "M5";
+
+/// Conventional environment variable names accepted for each config key, in
+/// addition to the generic `M5__SECTION__KEY` form. Every name also has a
+/// `{NAME}_FILE` variant that maps onto `{key}_file`.
+pub const ENV_ALIASES: &[(&str, &[&str])] = &[
+    ("app.host", &["APP_HOST", "SERVER_HOST"]),
+    ("app.port", &["APP_PORT", "SERVER_PORT"]),
+    ("app.environment", &["APP_ENV"]),
+    ("app.rate_limit_store", &["RATE_LIMIT_STORE"]),
+    ("runtime.log_filter", &["RUST_LOG"]),
+    ("auth.password_hashing.pepper", &["PASSWORD_PEPPER"]),
+    ("auth.api_key_pepper", &["API_KEY_PEPPER"]),
+    ("encryption.master_key", &["ENCRYPTION_MASTER_KEY"]),
+    ("encryption.retired_master_keys", &["ENCRYPTION_RETIRED_MASTER_KEYS"]),
+    ("encryption.blind_index_key", &["ENCRYPTION_BLIND_INDEX_KEY"]),
+    ("database.url", &["DATABASE_URL"]),
+    ("database.host", &["DB_HOST"]),
+    ("database.port", &["DB_PORT"]),
+    ("database.username", &["DB_USER"]),
+    ("database.password", &["DB_PASSWORD"]),
+    ("database.database_name", &["DB_NAME"]),
+    ("database.ssl_mode", &["DB_SSL_MODE"]),
+    ("database.migrations", &["DB_MIGRATIONS"]),
+    ("database.replica_urls", &["DATABASE_REPLICA_URLS"]),
+    ("database.pool.max_connections", &["DB_MAX_CONNECTIONS"]),
+    ("services.storage.backend", &["STORAGE_BACKEND"]),
+    ("services.storage.root", &["STORAGE_ROOT"]),
+    ("services.mail.transport", &["MAIL_TRANSPORT"]),
+    ("services.mail.from_email", &["MAIL_FROM_EMAIL"]),
+    ("services.mail.maildir", &["MAIL_MAILDIR"]),
+    ("services.aws.access_key_id", &["AWS_ACCESS_KEY_ID"]),
+    ("services.aws.secret_access_key", &["AWS_SECRET_ACCESS_KEY"]),
+    ("services.aws.region", &["AWS_REGION"]),
+    ("services.aws.s3_bucket", &["AWS_S3_BUCKET"]),
+    ("services.smtp.host", &["SMTP_HOST"]),
+    ("services.smtp.port", &["SMTP_PORT"]),
+    ("services.smtp.username", &["SMTP_USERNAME"]),
+    ("services.smtp.password", &["SMTP_PASSWORD"]),
+    ("services.smtp.from_email", &["SMTP_FROM_EMAIL"]),
+];
+
+/// Keys read through [`ConfigReader::required_secret`]; these may also be
+/// supplied as a path to a file holding the value (Docker/Kubernetes secrets).
+pub const SECRET_KEYS: &[&str] = &[
+    "database.url",
+    "database.password",
+    "database.replica_urls",
+    "auth.api_key_pepper",
+    "encryption.master_key",
+    "encryption.retired_master_keys",
+    "encryption.blind_index_key",
+    "services.aws.secret_access_key",
+    "services.smtp.password",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    pub config_dir: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub config_file: Option<PathBuf>,
+}
+
+impl LoadOptions {
+    /// Resolves options from the process: `APP_CONFIG_DIR`, `APP_ENV` and a
+    /// `--config <path>` / `--config=<path>` command line flag.
+    pub fn from_process() -> Self {
+        Self {
+            config_dir: std::env::var("APP_CONFIG_DIR").ok().map(PathBuf::from),
+            profile: std::env::var("APP_ENV").ok(),
+            config_file: config_flag(std::env::args()),
+        }
+    }
+
+    pub fn profile(&self) -> &str {
+        self.profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+}
+
+fn config_flag(args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    Missing,
+    Malformed(String),
+    Invalid(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub source: Option<String>,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = self.source.as_deref().unwrap_or("defaults");
+        match &self.kind {
+            IssueKind::Missing => {
+                write!(f, "{}: missing", self.key)?;
+                match env_aliases(&self.key) {
+                    [] => write!(f, " (set it in a config file)")?,
+                    names => write!(f, " (set {} or `{}` in a config file)", names.join(" / "), self.key)?,
+                }
+                if SECRET_KEYS.contains(&self.key.as_str()) {
+                    write!(f, "; a `_FILE` variant pointing at a secret file is also accepted")?;
+                }
+                Ok(())
+            }
+            IssueKind::Malformed(reason) => {
+                write!(f, "{}: malformed value from {}: {}", self.key, source, reason)
+            }
+            IssueKind::Invalid(reason) => {
+                write!(f, "{}: invalid value from {}: {}", self.key, source, reason)
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read configuration sources: {0}")]
+    Source(#[from] config::ConfigError),
+
+    #[error("invalid configuration ({} problem(s)):\n{}", .0.len(), format_issues(.0))]
+    Invalid(Vec<ConfigIssue>),
+}
+
+/// Strips the origin suffix `config` appends, since issues name it already.
+fn describe(err: config::ConfigError) -> String {
+    match err {
+        config::ConfigError::Type {
+            unexpected,
+            expected,
+            ..
+        } => format!("expected {}, got {}", expected, unexpected),
+        other => other.to_string(),
+    }
+}
+
+fn format_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("  - {}", issue))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn env_aliases(key: &str) -> &'static [&'static str] {
+    ENV_ALIASES
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, names)| *names)
+        .unwrap_or(&[])
+}
+
+/// Maps the conventional environment variable names onto config keys while
+/// keeping the variable name as the value's origin.
+#[derive(Debug, Clone, Default)]
+struct EnvAliases;
+
+impl Source for EnvAliases {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, config::ConfigError> {
+        let mut map = Map::new();
+        for (key, names) in ENV_ALIASES {
+            let file_names: Vec<String> = names.iter().map(|name| format!("{}_FILE", name)).collect();
+            insert_first_set(&mut map, key, names.iter().copied());
+            insert_first_set(&mut map, &format!("{}_file", key), file_names.iter().map(String::as_str));
+        }
+        Ok(map)
+    }
+}
+
+fn insert_first_set<'a>(map: &mut Map<String, Value>, key: &str, mut names: impl Iterator<Item = &'a str>) {
+    let found = names.find_map(|name| match std::env::var(name) {
+            Ok(value) if !value.is_empty() => Some((name, value)),
+            _ => None,
+        });
+    if let Some((name, value)) = found {
+        let origin = format!("environment variable {}", name);
+        map.insert(key.to_string(), Value::new(Some(&origin), ValueKind::String(value)));
+    }
+}
+
+fn layered_builder(options: &LoadOptions) -> ConfigBuilder<DefaultState> {
+    let dir = options
+        .config_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_DIR));
+
+    let mut builder = config::Config::builder()
+        .add_source(File::from(dir.join("default.toml")).required(false))
+        .add_source(File::from(dir.join(format!("{}.toml", options.profile()))).required(false))
+        .add_source(EnvAliases)
+        .add_source(Environment::with_prefix(ENV_PREFIX).prefix_separator("__").separator("__"));
+
+    if let Some(path) = &options.config_file {
+        builder = builder.add_source(File::from(path.as_path()).required(true));
+    }
+
+    builder
+}
+
+/// Reads typed values out of the merged layers, recording every problem
+/// instead of stopping at the first one.
+pub struct ConfigReader {
+    merged: config::Config,
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReader {
+    pub fn new(options: &LoadOptions) -> Result<Self, ConfigError> {
+        Ok(Self::from_config(layered_builder(options).build()?))
+    }
+
+    pub fn from_config(merged: config::Config) -> Self {
+        Self {
+            merged,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Walks the merged tree directly; `config::Config::get` round-trips
+    /// through serde and drops each value's origin.
+    fn lookup(&self, key: &str) -> Option<&Value> {
+        key.split('.').try_fold(&self.merged.cache, |value, segment| {
+            let (name, index) = match segment.strip_suffix(']').and_then(|s| s.split_once('[')) {
+                Some((name, index)) => (name, Some(index.parse::<usize>().ok()?)),
+                None => (segment, None),
+            };
+            let value = match &value.kind {
+                ValueKind::Table(table) => table.get(name)?,
+                _ => return None,
+            };
+            match (index, &value.kind) {
+                (None, _) => Some(value),
+                (Some(index), ValueKind::Array(items)) => items.get(index),
+                (Some(_), _) => None,
+            }
+        })
+    }
+
+    /// Number of entries in the array at `key`, or zero when it is absent.
+    pub fn array_len(&self, key: &str) -> usize {
+        match self.lookup(key).map(|value| &value.kind) {
+            Some(ValueKind::Array(items)) => items.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.lookup(key)
+            .is_some_and(|value| !matches!(value.kind, ValueKind::Nil))
+    }
+
+    pub fn source_of(&self, key: &str) -> Option<String> {
+        self.lookup(key)
+            .and_then(|value| value.origin().map(str::to_string))
+    }
+
+    /// Keys under `prefix` whose value comes from an environment variable,
+    /// with the variable's description. Such values outrank every config
+    /// file, so editing the files does not change them.
+    pub fn env_overrides(&self, prefix: &str) -> Vec<(String, String)> {
+        fn walk(key: String, value: &Value, found: &mut Vec<(String, String)>) {
+            match &value.kind {
+                ValueKind::Table(table) => {
+                    for (name, value) in table {
+                        walk(format!("{}.{}", key, name), value, found);
+                    }
+                }
+                _ => {
+                    if let Some(origin) = value.origin() {
+                        if origin.starts_with("environment variable") || origin == "the environment" {
+                            found.push((key, origin.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        if let Some(value) = self.lookup(prefix) {
+            walk(prefix.to_string(), value, &mut found);
+        }
+        found
+    }
+
+    pub fn required<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        match self.optional(key) {
+            Some(value) => Some(value),
+            None if !self.contains(key) => {
+                self.issue(key, IssueKind::Missing);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn optional<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        if !self.contains(key) {
+            return None;
+        }
+        let value = self.lookup(key)?.clone();
+        match value.try_deserialize::<T>() {
+            Ok(parsed) => Some(parsed),
+            Err(err) => {
+                self.issue(key, IssueKind::Malformed(describe(err)));
+                None
+            }
+        }
+    }
+
+    /// Reads a secret either inline from `key` or from the file named by
+    /// `{key}_file`, which takes precedence when both are set.
+    pub fn optional_secret(&mut self, key: &str) -> Option<Secret<String>> {
+        let file_key = format!("{}_file", key);
+        let Some(path) = self.optional::<String>(&file_key) else {
+            return self.optional::<String>(key).map(Secret::new);
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(mut contents) => {
+                let secret = Secret::new(contents.trim_end_matches(['\r', '\n']).to_string());
+                contents.zeroize();
+                Some(secret)
+            }
+            Err(err) => {
+                self.invalid(&file_key, format!("cannot read secret file `{}`: {}", path, err));
+                None
+            }
+        }
+    }
+
+    pub fn required_secret(&mut self, key: &str) -> Option<Secret<String>> {
+        let file_key = format!("{}_file", key);
+        match self.optional_secret(key) {
+            Some(secret) => Some(secret),
+            None if !self.contains(key) && !self.contains(&file_key) => {
+                self.issue(key, IssueKind::Missing);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn or<T: DeserializeOwned>(&mut self, key: &str, default: T) -> T {
+        self.optional(key).unwrap_or(default)
+    }
+
+    /// Records a semantic problem with an otherwise well-formed value.
+    pub fn invalid(&mut self, key: &str, reason: impl Into<String>) {
+        self.issue(key, IssueKind::Invalid(reason.into()));
+    }
+
+    fn issue(&mut self, key: &str, kind: IssueKind) {
+        self.issues.push(ConfigIssue {
+            key: key.to_string(),
+            source: self.source_of(key),
+            kind,
+        });
+    }
+
+    pub fn finish<T>(self, value: Option<T>) -> Result<T, ConfigError> {
+        match value {
+            Some(value) if self.issues.is_empty() => Ok(value),
+            _ => Err(ConfigError::Invalid(self.issues)),
+        }
+    }
+
+    pub fn merged(&self) -> &config::Config {
+        &self.merged
+    }
+}
@@ -1,25 +1,68 @@
 pub mod app;
+pub mod auth;
 pub mod database;
+pub mod encryption;
+pub mod http;
 pub mod services;
 pub mod env;
+pub mod loader;
+pub mod reload;
+pub mod runtime;
+pub mod secret;
 
 use app::AppConfig;
+use auth::AuthConfig;
 use database::DatabaseConfig;
+use encryption::EncryptionConfig;
+use http::HttpConfig;
+use loader::{ConfigError, ConfigReader, LoadOptions};
+use runtime::RuntimeConfig;
 use services::ServicesConfig;
 
+pub use secret::Secret;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub app: AppConfig,
+    pub http: HttpConfig,
+    pub auth: AuthConfig,
     pub database: DatabaseConfig,
+    pub encryption: EncryptionConfig,
     pub services: ServicesConfig,
+    pub runtime: RuntimeConfig,
 }
 
 impl Config {
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            app: AppConfig::from_env()?,
-            database: DatabaseConfig::from_env()?,
-            services: ServicesConfig::from_env()?,
+    /// Loads `config/default.toml`, `config/{APP_ENV}.toml`, environment
+    /// variables and an optional `--config` file, in increasing precedence.
+    pub fn load() -> Result<Self, ConfigError> {
+        env::init();
+        Self::load_with(&LoadOptions::from_process())
+    }
+
+    pub fn load_with(options: &LoadOptions) -> Result<Self, ConfigError> {
+        let mut reader = ConfigReader::new(options)?;
+        let config = Self::from_reader(&mut reader);
+        reader.finish(config)
+    }
+
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let app = AppConfig::from_reader(reader);
+        let http = HttpConfig::from_reader(reader);
+        let auth = AuthConfig::from_reader(reader);
+        let database = DatabaseConfig::from_reader(reader);
+        let encryption = EncryptionConfig::from_reader(reader);
+        let services = ServicesConfig::from_reader(reader);
+        let runtime = RuntimeConfig::from_reader(reader);
+
+        Some(Self {
+            app: app?,
+            http: http?,
+            auth: auth?,
+            database: database?,
+            encryption: encryption?,
+            services: services?,
+            runtime: runtime?,
         })
     }
 }
@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::loader::{ConfigReader, LoadOptions, DEFAULT_CONFIG_DIR};
+use super::runtime::RuntimeConfig;
+use super::Config;
+use crate::common::logging::LogHandle;
+use crate::infrastructure::security::audit::{Actor, AuditEvent, AuditLog};
+
+pub const FILE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub type RuntimeConfigRx = watch::Receiver<Arc<RuntimeConfig>>;
+pub type RuntimeConfigTx = watch::Sender<Arc<RuntimeConfig>>;
+
+pub fn channel(initial: RuntimeConfig) -> (RuntimeConfigTx, RuntimeConfigRx) {
+    watch::channel(Arc::new(initial))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReloadTrigger {
+    Signal,
+    FileChange,
+}
+
+impl ReloadTrigger {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReloadTrigger::Signal => "sighup",
+            ReloadTrigger::FileChange => "file_change",
+        }
+    }
+}
+
+/// Re-reads the layered configuration on SIGHUP or when a config file changes
+/// and publishes the runtime subset. A reload that fails validation leaves the
+/// previous values in place.
+pub struct RuntimeReloader {
+    options: LoadOptions,
+    sender: RuntimeConfigTx,
+    log_handle: LogHandle,
+    audit: AuditLog,
+}
+
+impl RuntimeReloader {
+    pub fn new(options: LoadOptions, sender: RuntimeConfigTx, log_handle: LogHandle, audit: AuditLog) -> Self {
+        Self {
+            options,
+            sender,
+            log_handle,
+            audit,
+        }
+    }
+
+    pub async fn reload(&self, trigger: ReloadTrigger) -> bool {
+        let next = match Config::load_with(&self.options) {
+            Ok(config) => config.runtime,
+            Err(err) => {
+                self.reject(trigger, err.to_string()).await;
+                return false;
+            }
+        };
+
+        let current = self.sender.borrow().clone();
+        let changed = next.changed_keys(&current);
+        if changed.is_empty() {
+            info!(trigger = trigger.as_str(), "Configuration reloaded; no runtime changes");
+            return false;
+        }
+
+        if next.log_filter != current.log_filter {
+            if let Err(err) = self.log_handle.set_filter(&next.log_filter) {
+                self.reject(trigger, format!("failed to apply log filter: {}", err)).await;
+                return false;
+            }
+        }
+
+        self.sender.send_replace(Arc::new(next));
+        let event = AuditEvent::ConfigReloaded {
+            trigger: trigger.as_str().to_string(),
+            changed,
+        };
+        self.audit.record(event.by(Actor::System)).await;
+        true
+    }
+
+    async fn reject(&self, trigger: ReloadTrigger, error: String) {
+        let event = AuditEvent::ConfigReloadRejected {
+            trigger: trigger.as_str().to_string(),
+            error,
+        };
+        self.audit.record(event.by(Actor::System)).await;
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run().await })
+    }
+
+    /// Reloads only pick up what the config files say, so point out values
+    /// the environment pins.
+    fn warn_env_overrides(&self) {
+        let Ok(reader) = ConfigReader::new(&self.options) else {
+            return;
+        };
+        for (key, origin) in reader.env_overrides("runtime") {
+            warn!(key = %key, "{} is set by {}, which overrides the config files; reloads will not change it", key, origin);
+        }
+    }
+
+    async fn run(self) {
+        self.warn_env_overrides();
+        let mut last_modified = self.watched_mtimes();
+        let mut poll = tokio::time::interval(FILE_POLL_INTERVAL);
+        poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(signal) => Some(signal),
+            Err(err) => {
+                warn!("Failed to install SIGHUP handler, only file changes will trigger reloads: {}", err);
+                None
+            }
+        };
+
+        loop {
+            #[cfg(unix)]
+            let sighup = async {
+                match hangup.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            #[cfg(not(unix))]
+            let sighup = std::future::pending::<Option<()>>();
+
+            tokio::select! {
+                Some(()) = sighup => {
+                    self.reload(ReloadTrigger::Signal).await;
+                }
+                _ = poll.tick() => {
+                    let modified = self.watched_mtimes();
+                    if modified != last_modified {
+                        last_modified = modified;
+                        self.reload(ReloadTrigger::FileChange).await;
+                    }
+                }
+                _ = self.sender.closed() => break,
+            }
+        }
+    }
+
+    fn watched_files(&self) -> Vec<PathBuf> {
+        let dir = self
+            .options
+            .config_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_DIR));
+
+        let mut files = vec![
+            dir.join("default.toml"),
+            dir.join(format!("{}.toml", self.options.profile())),
+        ];
+        files.extend(self.options.config_file.clone());
+        files
+    }
+
+    fn watched_mtimes(&self) -> Vec<Option<SystemTime>> {
+        self.watched_files()
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+}
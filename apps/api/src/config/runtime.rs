@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use tracing_subscriber::EnvFilter;
+
+use super::loader::ConfigReader;
+use crate::common::constants::{
+    DEFAULT_CACHE_TTL, MAX_CACHE_TTL, RATE_LIMIT_MAX_REQUESTS, RATE_LIMIT_SOURCE_IP_MAX_REQUESTS,
+    RATE_LIMIT_WINDOW, ROUTE_GROUPS,
+};
+
+pub const DEFAULT_LOG_FILTER: &str = "m5=info,tower_http=info,axum::rejection=trace";
+
+/// The subset of configuration that can change without a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    pub log_filter: String,
+    pub rate_limit: RateLimitSettings,
+    pub cache: CacheSettings,
+    pub features: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitSettings {
+    pub enabled: bool,
+    /// Applies to groups without an entry in `groups`.
+    pub default: RateLimitQuota,
+    pub groups: BTreeMap<String, RateLimitQuota>,
+    /// Per source IP across every group, checked before authentication so
+    /// a flood of bad credentials is turned away before any is verified.
+    pub source_ip: RateLimitQuota,
+}
+
+/// `max_requests` per `window_secs` on average, of which up to `burst` may
+/// arrive at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitQuota {
+    pub enabled: bool,
+    pub window_secs: u64,
+    pub max_requests: u32,
+    pub burst: u32,
+}
+
+impl RateLimitSettings {
+    /// `None` when requests in `group` are not limited.
+    pub fn quota(&self, group: &str) -> Option<RateLimitQuota> {
+        let quota = self.groups.get(group).copied().unwrap_or(self.default);
+        (self.enabled && quota.enabled).then_some(quota)
+    }
+
+    /// `None` when source IPs are not limited.
+    pub fn source_ip_quota(&self) -> Option<RateLimitQuota> {
+        (self.enabled && self.source_ip.enabled).then_some(self.source_ip)
+    }
+
+    fn from_reader(reader: &mut ConfigReader) -> Self {
+        let enabled = reader.or("runtime.rate_limit.enabled", true);
+        let default = RateLimitQuota::from_reader(reader, "runtime.rate_limit", None);
+        let mut groups = BTreeMap::new();
+        for group in ROUTE_GROUPS {
+            let prefix = format!("runtime.rate_limit.groups.{}", group);
+            if reader.contains(&prefix) {
+                groups.insert(group.to_string(), RateLimitQuota::from_reader(reader, &prefix, Some(&default)));
+            }
+        }
+        let source_ip_base = RateLimitQuota {
+            enabled: true,
+            window_secs: RATE_LIMIT_WINDOW,
+            max_requests: RATE_LIMIT_SOURCE_IP_MAX_REQUESTS,
+            burst: RATE_LIMIT_SOURCE_IP_MAX_REQUESTS,
+        };
+        let source_ip = RateLimitQuota::from_reader(reader, "runtime.rate_limit.source_ip", Some(&source_ip_base));
+        Self {
+            enabled,
+            default,
+            groups,
+            source_ip,
+        }
+    }
+}
+
+impl RateLimitQuota {
+    /// Unset values fall back to `base`, then to the built-in defaults.
+    fn from_reader(reader: &mut ConfigReader, prefix: &str, base: Option<&RateLimitQuota>) -> Self {
+        let key = |name: &str| format!("{}.{}", prefix, name);
+        let enabled = reader.or(&key("enabled"), base.is_none_or(|base| base.enabled));
+        let window_secs = reader.or(&key("window_secs"), base.map_or(RATE_LIMIT_WINDOW, |base| base.window_secs));
+        let max_requests = reader.or(
+            &key("max_requests"),
+            base.map_or(RATE_LIMIT_MAX_REQUESTS, |base| base.max_requests),
+        );
+        // An unset burst inherits the base's only if max_requests does too.
+        let default_burst = match base {
+            Some(base) if !reader.contains(&key("max_requests")) => base.burst,
+            _ => max_requests,
+        };
+        let burst = reader.or(&key("burst"), default_burst);
+
+        if window_secs == 0 {
+            reader.invalid(&key("window_secs"), "must be greater than zero");
+        }
+        if max_requests == 0 {
+            reader.invalid(&key("max_requests"), "must be greater than zero");
+        }
+        if burst == 0 {
+            reader.invalid(&key("burst"), "must be greater than zero");
+        }
+
+        Self {
+            enabled,
+            window_secs,
+            max_requests,
+            burst,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSettings {
+    pub default_ttl_secs: u64,
+    pub max_ttl_secs: u64,
+}
+
+impl RuntimeConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let log_filter: String = reader.or("runtime.log_filter", DEFAULT_LOG_FILTER.to_string());
+        let rate_limit = RateLimitSettings::from_reader(reader);
+        let default_ttl_secs = reader.or("runtime.cache.default_ttl_secs", DEFAULT_CACHE_TTL);
+        let max_ttl_secs = reader.or("runtime.cache.max_ttl_secs", MAX_CACHE_TTL);
+        let features = reader.or("runtime.features", BTreeMap::new());
+
+        if let Err(err) = EnvFilter::try_new(&log_filter) {
+            reader.invalid("runtime.log_filter", err.to_string());
+        }
+        if default_ttl_secs > max_ttl_secs {
+            reader.invalid(
+                "runtime.cache.default_ttl_secs",
+                format!("must not exceed runtime.cache.max_ttl_secs ({})", max_ttl_secs),
+            );
+        }
+
+        Some(Self {
+            log_filter,
+            rate_limit,
+            cache: CacheSettings {
+                default_ttl_secs,
+                max_ttl_secs,
+            },
+            features,
+        })
+    }
+
+    pub fn feature_enabled(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+
+    /// Names of the top-level settings that differ from `other`.
+    pub fn changed_keys(&self, other: &Self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if self.log_filter != other.log_filter {
+            keys.push("runtime.log_filter".to_string());
+        }
+        if self.rate_limit != other.rate_limit {
+            keys.push("runtime.rate_limit".to_string());
+        }
+        if self.cache != other.cache {
+            keys.push("runtime.cache".to_string());
+        }
+        let names = self.features.keys().chain(other.features.keys());
+        for name in names.collect::<std::collections::BTreeSet<_>>() {
+            if self.features.get(name) != other.features.get(name) {
+                keys.push(format!("runtime.features.{}", name));
+            }
+        }
+        keys
+    }
+}
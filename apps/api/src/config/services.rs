@@ -1,15 +1,36 @@
-use super::env;
+use std::path::PathBuf;
+
+use super::loader::ConfigReader;
+use super::secret::Secret;
+
+pub const STORAGE_BACKENDS: &[&str] = &["disabled", "filesystem", "s3"];
+pub const MAIL_TRANSPORTS: &[&str] = &["disabled", "log", "maildir", "smtp"];
 
 #[derive(Debug, Clone)]
 pub struct ServicesConfig {
-    pub aws: AwsConfig,
-    pub smtp: SmtpConfig,
+    pub storage: StorageConfig,
+    pub mail: MailConfig,
+}
+
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    Disabled,
+    Filesystem { root: PathBuf },
+    S3(AwsConfig),
+}
+
+#[derive(Debug, Clone)]
+pub enum MailConfig {
+    Disabled,
+    Log { from_email: String },
+    Maildir { path: PathBuf, from_email: String },
+    Smtp(SmtpConfig),
 }
 
 #[derive(Debug, Clone)]
 pub struct AwsConfig {
     pub access_key_id: String,
-    pub secret_access_key: String,
+    pub secret_access_key: Secret<String>,
     pub region: String,
     pub s3_bucket: String,
 }
@@ -19,38 +40,126 @@ pub struct SmtpConfig {
     pub host: String,
     pub port: u16,
     pub username: String,
-    pub password: String,
+    pub password: Secret<String>,
     pub from_email: String,
 }
 
 impl ServicesConfig {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            aws: AwsConfig::from_env()?,
-            smtp: SmtpConfig::from_env()?,
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let storage = StorageConfig::from_reader(reader);
+        let mail = MailConfig::from_reader(reader);
+
+        Some(Self {
+            storage: storage?,
+            mail: mail?,
         })
     }
 }
 
+impl StorageConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let backend: String = reader.or("services.storage.backend", "disabled".to_string());
+
+        match backend.as_str() {
+            "disabled" => Some(Self::Disabled),
+            "filesystem" => reader
+                .required("services.storage.root")
+                .map(|root| Self::Filesystem { root }),
+            "s3" => AwsConfig::from_reader(reader).map(Self::S3),
+            other => {
+                reader.invalid(
+                    "services.storage.backend",
+                    format!("expected one of {}, got `{}`", STORAGE_BACKENDS.join(", "), other),
+                );
+                None
+            }
+        }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Filesystem { .. } => "filesystem",
+            Self::S3(_) => "s3",
+        }
+    }
+}
+
+impl MailConfig {
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let transport: String = reader.or("services.mail.transport", "disabled".to_string());
+
+        match transport.as_str() {
+            "disabled" => Some(Self::Disabled),
+            "log" => from_email(reader, "services.mail.from_email")
+                .map(|from_email| Self::Log { from_email }),
+            "maildir" => {
+                let path = reader.required("services.mail.maildir");
+                let from_email = from_email(reader, "services.mail.from_email");
+                Some(Self::Maildir {
+                    path: path?,
+                    from_email: from_email?,
+                })
+            }
+            "smtp" => SmtpConfig::from_reader(reader).map(Self::Smtp),
+            other => {
+                reader.invalid(
+                    "services.mail.transport",
+                    format!("expected one of {}, got `{}`", MAIL_TRANSPORTS.join(", "), other),
+                );
+                None
+            }
+        }
+    }
+
+    pub fn transport(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Log { .. } => "log",
+            Self::Maildir { .. } => "maildir",
+            Self::Smtp(_) => "smtp",
+        }
+    }
+}
+
 impl AwsConfig {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            access_key_id: env::get_var("AWS_ACCESS_KEY_ID")?,
-            secret_access_key: env::get_var("AWS_SECRET_ACCESS_KEY")?,
-            region: env::get_var_or("AWS_REGION", "us-east-1".to_string()),
-            s3_bucket: env::get_var("AWS_S3_BUCKET")?,
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let access_key_id = reader.required("services.aws.access_key_id");
+        let secret_access_key = reader.required_secret("services.aws.secret_access_key");
+        let region = reader.or("services.aws.region", "us-east-1".to_string());
+        let s3_bucket = reader.required("services.aws.s3_bucket");
+
+        Some(Self {
+            access_key_id: access_key_id?,
+            secret_access_key: secret_access_key?,
+            region,
+            s3_bucket: s3_bucket?,
         })
     }
 }
 
 impl SmtpConfig {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            host: env::get_var_or("SMTP_HOST", "smtp.gmail.com".to_string()),
-            port: env::get_var_or("SMTP_PORT", 587),
-            username: env::get_var("SMTP_USERNAME")?,
-            password: env::get_var("SMTP_PASSWORD")?,
-            from_email: env::get_var("SMTP_FROM_EMAIL")?,
+    pub fn from_reader(reader: &mut ConfigReader) -> Option<Self> {
+        let host = reader.or("services.smtp.host", "smtp.gmail.com".to_string());
+        let port = reader.or("services.smtp.port", 587);
+        let username = reader.required("services.smtp.username");
+        let password = reader.required_secret("services.smtp.password");
+        let from_email = from_email(reader, "services.smtp.from_email");
+
+        Some(Self {
+            host,
+            port,
+            username: username?,
+            password: password?,
+            from_email: from_email?,
         })
     }
-}
\ No newline at end of file
+}
+
+fn from_email(reader: &mut ConfigReader, key: &str) -> Option<String> {
+    let email: String = reader.required(key)?;
+    if crate::common::validation::validate_email(&email).is_err() {
+        reader.invalid(key, "not a valid email address");
+    }
+    Some(email)
+}
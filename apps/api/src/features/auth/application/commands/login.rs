@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{
+    Login, LoginResponse, MfaChallengeResponse, TokenResponse,
+};
+use crate::features::users::domain::models::{normalize_email, User};
+use crate::features::users::ports::repositories::UserRepository;
+use crate::features::users::ports::services::{hash_blocking, verify_blocking, PasswordHasher};
+use crate::infrastructure::jobs::JobQueue;
+use crate::infrastructure::security::audit::{Actor, AuditEvent, AuditLog, LoginFailure, LoginMethod};
+use crate::infrastructure::security::jwt::TokenService;
+use crate::infrastructure::security::lockout::{LoginThrottle, ThrottleScope};
+use crate::infrastructure::security::mfa::MfaStore;
+use crate::infrastructure::security::refresh_tokens::RefreshTokenStore;
+use crate::infrastructure::security::sessions::ClientInfo;
+
+const INVALID_CREDENTIALS: &str = "invalid email or password";
+
+/// Job that tells the owner of an account it was locked after repeated
+/// failed logins.
+pub const ACCOUNT_LOCKED_JOB: &str = "mail.account_locked";
+
+pub struct LoginHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) hasher: Arc<dyn PasswordHasher>,
+    pub(crate) failed_logins: FailedLogins,
+    pub(crate) audit: AuditLog,
+    pub(crate) sessions: SessionIssuer,
+}
+
+/// Counts failed password and second-factor attempts against the account
+/// and source IP, and refuses further attempts while either is blocked.
+#[derive(Clone)]
+pub struct FailedLogins {
+    pub(crate) throttle: LoginThrottle,
+    /// Queue for lockout notices; `None` when mail is disabled and nothing
+    /// would send them.
+    pub(crate) lockout_notices: Option<JobQueue>,
+    pub(crate) audit: AuditLog,
+}
+
+/// Finishes a login once the first factor checked out: accounts with 2FA
+/// get an MFA challenge, everyone else a new session.
+#[derive(Clone)]
+pub struct SessionIssuer {
+    pub(crate) tokens: TokenService,
+    pub(crate) refresh_tokens: RefreshTokenStore,
+    pub(crate) mfa: MfaStore,
+    pub(crate) audit: AuditLog,
+    pub(crate) refresh_token_ttl: chrono::Duration,
+    pub(crate) mfa_pending_ttl: chrono::Duration,
+}
+
+#[async_trait]
+impl CommandHandler<Login> for LoginHandler {
+    type Output = LoginResponse;
+
+    async fn handle(&self, command: Login) -> Result<LoginResponse> {
+        let request = command.request;
+        let client = command.client;
+        let email = normalize_email(&request.email).ok();
+        let account_key = email.clone().unwrap_or_else(|| request.email.trim().to_lowercase());
+        let mut keys = vec![(ThrottleScope::Account, account_key.as_str())];
+        if let Some(ip) = client.ip_address.as_deref() {
+            keys.push((ThrottleScope::Ip, ip));
+        }
+
+        // Checked before the password so a blocked key learns nothing from
+        // further guesses.
+        self.failed_logins.ensure_not_blocked(&keys, &client).await?;
+
+        let user = match &email {
+            Some(email) => self.users.find_by_email(email).await?,
+            None => None,
+        };
+
+        // Unknown accounts still pay for a hash verification so response
+        // time does not reveal which emails are registered.
+        let Some(user) = user else {
+            verify_blocking(&self.hasher, request.password.expose(), self.dummy_hash().await).await;
+            self.failed_logins.record(&keys, None, LoginFailure::InvalidCredentials, &client).await?;
+            return Err(AppError::Authentication(INVALID_CREDENTIALS.to_string()));
+        };
+        if !verify_blocking(&self.hasher, request.password.expose(), user.password_hash()).await {
+            self.failed_logins.record(&keys, Some(&user), LoginFailure::InvalidCredentials, &client).await?;
+            return Err(AppError::Authentication(INVALID_CREDENTIALS.to_string()));
+        }
+        if !user.is_active() {
+            self.audit.record(
+                AuditEvent::LoginFailed {
+                    account: Some(account_key),
+                    user_id: Some(user.id().as_str().to_string()),
+                    reason: LoginFailure::AccountDisabled,
+                }
+                .by(Actor::Anonymous)
+                .from_ip(client.ip_address.clone()),
+            ).await;
+            return Err(AppError::Authentication("account is disabled".to_string()));
+        }
+        if self.hasher.needs_rehash(user.password_hash()) {
+            self.upgrade_hash(&user, request.password.expose()).await;
+        }
+
+        let response = self.sessions.begin(user.id().as_str(), &client, LoginMethod::Password).await?;
+        // With 2FA the count stands until the second factor checks out, so
+        // the password alone cannot reset the budget for guessing codes.
+        if let LoginResponse::Tokens(_) = &response {
+            self.failed_logins.clear_account(&account_key).await;
+        }
+        Ok(response)
+    }
+}
+
+impl SessionIssuer {
+    pub(crate) async fn begin(&self, user_id: &str, client: &ClientInfo, method: LoginMethod) -> Result<LoginResponse> {
+        if self.mfa.is_enabled(user_id).await? {
+            let token = self.mfa.create_challenge(user_id, self.mfa_pending_ttl).await?;
+            tracing::info!(user_id = %user_id, "First factor accepted; awaiting second factor");
+            return Ok(LoginResponse::MfaRequired(MfaChallengeResponse::new(
+                token,
+                self.mfa_pending_ttl.num_seconds().max(0) as u64,
+            )));
+        }
+
+        let refresh = self
+            .refresh_tokens
+            .start_session(user_id, self.refresh_token_ttl, false, client)
+            .await?;
+        let access = self.tokens.issue(user_id, &refresh.session.id, false)?;
+
+        self.audit.record(
+            AuditEvent::LoginSucceeded {
+                user_id: user_id.to_string(),
+                session_id: refresh.session.id.clone(),
+                method,
+            }
+            .by(Actor::User(user_id.to_string()))
+            .from_ip(client.ip_address.clone()),
+        ).await;
+        Ok(LoginResponse::Tokens(TokenResponse::new(access, refresh)))
+    }
+}
+
+impl FailedLogins {
+    /// Fails with a rate limit error while any of `keys` is blocked.
+    pub(crate) async fn ensure_not_blocked(&self, keys: &[(ThrottleScope, &str)], client: &ClientInfo) -> Result<()> {
+        let Some(block) = self.throttle.blocked(keys).await? else {
+            return Ok(());
+        };
+        let retry_after_secs = block.retry_after_secs();
+        self.audit.record(
+            AuditEvent::LoginBlocked {
+                scope: block.scope,
+                key: block.key,
+                retry_after_secs,
+            }
+            .by(Actor::Anonymous)
+            .from_ip(client.ip_address.clone()),
+        ).await;
+        Err(AppError::RateLimit(retry_after_secs))
+    }
+
+    /// Records a failed attempt and counts it against each of `keys`,
+    /// locking those that reach their threshold.
+    pub(crate) async fn record(
+        &self,
+        keys: &[(ThrottleScope, &str)],
+        user: Option<&User>,
+        reason: LoginFailure,
+        client: &ClientInfo,
+    ) -> Result<()> {
+        let user_id = user.map(|user| user.id().as_str().to_string());
+        let account = keys
+            .iter()
+            .find(|(scope, _)| *scope == ThrottleScope::Account)
+            .map(|(_, key)| key.to_string());
+        let audit = |event: AuditEvent| self.audit.record(event.by(Actor::Anonymous).from_ip(client.ip_address.clone()));
+        audit(AuditEvent::LoginFailed {
+            account,
+            user_id: user_id.clone(),
+            reason,
+        })
+        .await;
+
+        for &(scope, key) in keys {
+            let outcome = self.throttle.record_failure(scope, key).await?;
+            if !outcome.locked {
+                continue;
+            }
+            audit(match scope {
+                ThrottleScope::Account => AuditEvent::AccountLocked {
+                    account: key.to_string(),
+                    user_id: user_id.clone(),
+                    failures: outcome.failures,
+                    locked_until: outcome.blocked_until,
+                },
+                ThrottleScope::Ip => AuditEvent::IpLocked {
+                    ip_address: key.to_string(),
+                    failures: outcome.failures,
+                    locked_until: outcome.blocked_until,
+                },
+            })
+            .await;
+
+            if let (ThrottleScope::Account, Some(user), Some(jobs)) = (scope, user, &self.lockout_notices) {
+                let payload = serde_json::json!({
+                    "user_id": user.id().as_str(),
+                    "failures": outcome.failures,
+                    "locked_until": outcome.blocked_until,
+                    "ip_address": client.ip_address,
+                });
+                // The lockout stands even if the notice cannot be queued.
+                if let Err(e) = jobs.enqueue(ACCOUNT_LOCKED_JOB, payload).await {
+                    tracing::error!(user_id = %user.id().as_str(), error = %e, "Failed to queue account lockout notice");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Forgets the account's failures once the user has fully signed in.
+    /// The source IP keeps its count: one valid account must not reset the
+    /// budget of an IP guessing others. Failing to clear does not fail the
+    /// login.
+    pub(crate) async fn clear_account(&self, account: &str) {
+        if let Err(e) = self.throttle.clear(ThrottleScope::Account, account).await {
+            tracing::warn!(error = %e, "Failed to clear account login throttle");
+        }
+    }
+}
+
+impl LoginHandler {
+    /// Re-hashes the just-verified password under the current policy. A
+    /// failure leaves the old hash in place and does not fail the login.
+    async fn upgrade_hash(&self, user: &User, password: &str) {
+        let upgraded = match hash_blocking(&self.hasher, password).await {
+            Ok(hash) => self.users.replace_password_hash(user.id(), user.password_hash(), &hash).await,
+            Err(e) => Err(e),
+        };
+        match upgraded {
+            Ok(true) => tracing::info!(user_id = %user.id().as_str(), "Upgraded password hash to current policy"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(user_id = %user.id().as_str(), error = %e, "Failed to upgrade password hash"),
+        }
+    }
+
+    async fn dummy_hash(&self) -> &'static str {
+        static DUMMY_HASH: OnceCell<String> = OnceCell::const_new();
+        DUMMY_HASH
+            .get_or_init(|| async {
+                hash_blocking(&self.hasher, "dummy-password-for-timing")
+                    .await
+                    .unwrap_or_default()
+            })
+            .await
+    }
+}
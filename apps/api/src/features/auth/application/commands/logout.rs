@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::LogoutRequest;
+use crate::infrastructure::security::refresh_tokens::RefreshTokenStore;
+use crate::infrastructure::security::sessions::SessionStatusCache;
+
+pub struct LogoutHandler {
+    pub(crate) refresh_tokens: RefreshTokenStore,
+    pub(crate) session_status: SessionStatusCache,
+}
+
+#[async_trait]
+impl CommandHandler<LogoutRequest> for LogoutHandler {
+    type Output = ();
+
+    /// Logging out with an unknown or already revoked token succeeds, so
+    /// clients can always discard their tokens.
+    async fn handle(&self, request: LogoutRequest) -> Result<()> {
+        if let Some(session_id) = self
+            .refresh_tokens
+            .revoke_session(request.refresh_token.expose(), "logout")
+            .await?
+        {
+            self.session_status.deny([&session_id]);
+            tracing::info!(session_id = %session_id, "Session logged out");
+        }
+        Ok(())
+    }
+}
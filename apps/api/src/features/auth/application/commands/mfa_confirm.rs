@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{ConfirmMfa, RecoveryCodesResponse};
+use crate::infrastructure::security::mfa::MfaStore;
+use crate::infrastructure::security::totp;
+
+pub struct ConfirmMfaHandler {
+    pub(crate) mfa: MfaStore,
+    pub(crate) recovery_codes: usize,
+}
+
+#[async_trait]
+impl CommandHandler<ConfirmMfa> for ConfirmMfaHandler {
+    type Output = RecoveryCodesResponse;
+
+    async fn handle(&self, command: ConfirmMfa) -> Result<RecoveryCodesResponse> {
+        let pending = self
+            .mfa
+            .secret(&command.user_id)
+            .await?
+            .filter(|secret| !secret.enabled)
+            .ok_or_else(|| AppError::Conflict("no two-factor enrollment is pending".to_string()))?;
+
+        let step = totp::verify(
+            pending.secret.expose(),
+            &command.code,
+            chrono::Utc::now().timestamp(),
+            pending.last_used_step,
+        )
+        .ok_or_else(|| AppError::InvalidInput("invalid verification code".to_string()))?;
+
+        let codes = self
+            .mfa
+            .enable(&command.user_id, step, self.recovery_codes)
+            .await?
+            .ok_or_else(|| AppError::Conflict("two-factor authentication is already enabled".to_string()))?;
+
+        tracing::info!(user_id = %command.user_id, "Two-factor authentication enabled");
+        Ok(RecoveryCodesResponse {
+            recovery_codes: codes.iter().map(|code| code.expose().clone()).collect(),
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::mfa_verify::verify_second_factor;
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::DisableMfa;
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::features::users::ports::services::{verify_blocking, PasswordHasher};
+use crate::infrastructure::security::mfa::MfaStore;
+
+pub struct DisableMfaHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) hasher: Arc<dyn PasswordHasher>,
+    pub(crate) mfa: MfaStore,
+}
+
+#[async_trait]
+impl CommandHandler<DisableMfa> for DisableMfaHandler {
+    type Output = ();
+
+    /// Re-authenticates with the password and a second factor, so a stolen
+    /// access token alone cannot strip 2FA from an account.
+    async fn handle(&self, command: DisableMfa) -> Result<()> {
+        let user = self
+            .users
+            .find_by_id(&UserId::parse(&command.user_id)?)
+            .await?
+            .ok_or_else(|| AppError::NotFound("user".to_string()))?;
+        if !verify_blocking(&self.hasher, command.request.password.expose(), user.password_hash()).await {
+            return Err(AppError::Authentication("invalid password".to_string()));
+        }
+        if !self.mfa.is_enabled(&command.user_id).await? {
+            return Err(AppError::Conflict("two-factor authentication is not enabled".to_string()));
+        }
+
+        let request = &command.request;
+        let verified = verify_second_factor(
+            &self.mfa,
+            &command.user_id,
+            request.code.as_deref(),
+            request.recovery_code.as_ref(),
+        )
+        .await?;
+        if !verified {
+            return Err(AppError::Authentication("invalid verification code".to_string()));
+        }
+
+        self.mfa.disable(&command.user_id).await?;
+        tracing::info!(user_id = %command.user_id, "Two-factor authentication disabled");
+        Ok(())
+    }
+}
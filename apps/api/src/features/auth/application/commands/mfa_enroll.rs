@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{EnrollMfa, MfaEnrollmentResponse};
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::mfa::MfaStore;
+use crate::infrastructure::security::totp;
+
+pub struct EnrollMfaHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) mfa: MfaStore,
+    pub(crate) issuer: String,
+}
+
+#[async_trait]
+impl CommandHandler<EnrollMfa> for EnrollMfaHandler {
+    type Output = MfaEnrollmentResponse;
+
+    /// Starts (or restarts) enrollment. 2FA is not active until the secret
+    /// is confirmed with a code.
+    async fn handle(&self, command: EnrollMfa) -> Result<MfaEnrollmentResponse> {
+        let user_id = UserId::parse(&command.user_id)?;
+        let user = self
+            .users
+            .find_by_id(&user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("user".to_string()))?;
+
+        let secret = totp::generate_secret();
+        if !self.mfa.begin_enrollment(user_id.as_str(), &secret).await? {
+            return Err(AppError::Conflict(
+                "two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        Ok(MfaEnrollmentResponse {
+            provisioning_uri: totp::provisioning_uri(&self.issuer, user.email(), &secret),
+            secret,
+        })
+    }
+}
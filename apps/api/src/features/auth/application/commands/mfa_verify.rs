@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::login::FailedLogins;
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::config::Secret;
+use crate::features::auth::application::dtos::{TokenResponse, VerifyMfa};
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::audit::{Actor, AuditEvent, AuditLog, LoginFailure, LoginMethod};
+use crate::infrastructure::security::jwt::TokenService;
+use crate::infrastructure::security::lockout::ThrottleScope;
+use crate::infrastructure::security::mfa::MfaStore;
+use crate::infrastructure::security::refresh_tokens::RefreshTokenStore;
+use crate::infrastructure::security::sessions::ClientInfo;
+use crate::infrastructure::security::totp;
+
+const INVALID_CODE: &str = "invalid verification code";
+
+pub struct VerifyMfaHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) mfa: MfaStore,
+    pub(crate) tokens: TokenService,
+    pub(crate) refresh_tokens: RefreshTokenStore,
+    pub(crate) failed_logins: FailedLogins,
+    pub(crate) audit: AuditLog,
+    pub(crate) refresh_token_ttl: chrono::Duration,
+}
+
+#[async_trait]
+impl CommandHandler<VerifyMfa> for VerifyMfaHandler {
+    type Output = TokenResponse;
+
+    async fn handle(&self, command: VerifyMfa) -> Result<TokenResponse> {
+        let request = command.request;
+        let client = command.client;
+        let user_id = self
+            .mfa
+            .attempt_challenge(request.mfa_token.expose())
+            .await?
+            .ok_or_else(|| AppError::Authentication("mfa token is invalid or expired".to_string()))?;
+
+        let Some(user) = self.users.find_by_id(&UserId::parse(&user_id)?).await? else {
+            self.record_disabled(&user_id, &client).await;
+            return Err(AppError::Authentication("account is disabled".to_string()));
+        };
+        // Codes are throttled like passwords, under the same keys, so a
+        // stolen password does not buy unlimited guesses at the code.
+        let mut keys = vec![(ThrottleScope::Account, user.email())];
+        if let Some(ip) = client.ip_address.as_deref() {
+            keys.push((ThrottleScope::Ip, ip));
+        }
+        self.failed_logins.ensure_not_blocked(&keys, &client).await?;
+
+        let verified =
+            verify_second_factor(&self.mfa, &user_id, request.code.as_deref(), request.recovery_code.as_ref())
+                .await?;
+        // Consuming last means two requests racing with one challenge
+        // cannot both get a session.
+        if !verified || !self.mfa.consume_challenge(request.mfa_token.expose()).await? {
+            self.failed_logins
+                .record(&keys, Some(&user), LoginFailure::InvalidSecondFactor, &client)
+                .await?;
+            return Err(AppError::Authentication(INVALID_CODE.to_string()));
+        }
+        self.failed_logins.clear_account(user.email()).await;
+
+        if !user.is_active() {
+            self.record_disabled(&user_id, &client).await;
+            return Err(AppError::Authentication("account is disabled".to_string()));
+        }
+
+        let refresh = self
+            .refresh_tokens
+            .start_session(&user_id, self.refresh_token_ttl, true, &client)
+            .await?;
+        let access = self.tokens.issue(&user_id, &refresh.session.id, true)?;
+
+        self.audit.record(
+            AuditEvent::LoginSucceeded {
+                user_id: user_id.clone(),
+                session_id: refresh.session.id.clone(),
+                method: LoginMethod::SecondFactor,
+            }
+            .by(Actor::User(user_id.clone()))
+            .from_ip(client.ip_address.clone()),
+        ).await;
+        Ok(TokenResponse::new(access, refresh))
+    }
+}
+
+impl VerifyMfaHandler {
+    async fn record_disabled(&self, user_id: &str, client: &ClientInfo) {
+        let event = AuditEvent::LoginFailed {
+            account: None,
+            user_id: Some(user_id.to_string()),
+            reason: LoginFailure::AccountDisabled,
+        };
+        self.audit.record(event.by(Actor::Anonymous).from_ip(client.ip_address.clone())).await;
+    }
+}
+
+/// Checks a TOTP code, or burns a recovery code when no TOTP code is given.
+pub(crate) async fn verify_second_factor(
+    mfa: &MfaStore,
+    user_id: &str,
+    code: Option<&str>,
+    recovery_code: Option<&Secret<String>>,
+) -> Result<bool> {
+    match (code, recovery_code) {
+        (Some(code), _) => {
+            let Some(secret) = mfa.secret(user_id).await?.filter(|secret| secret.enabled) else {
+                return Ok(false);
+            };
+            let step = totp::verify(
+                secret.secret.expose(),
+                code,
+                chrono::Utc::now().timestamp(),
+                secret.last_used_step,
+            );
+            match step {
+                Some(step) => Ok(mfa.record_step(user_id, step).await?),
+                None => Ok(false),
+            }
+        }
+        (None, Some(recovery_code)) => Ok(mfa.use_recovery_code(user_id, recovery_code.expose()).await?),
+        (None, None) => Err(AppError::InvalidInput(
+            "provide either `code` or `recovery_code`".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,31 @@
+pub mod login;
+pub mod logout;
+pub mod mfa_confirm;
+pub mod mfa_disable;
+pub mod mfa_enroll;
+pub mod mfa_verify;
+pub mod oidc_authorize;
+pub mod oidc_callback;
+pub mod oidc_unlink;
+pub mod refresh;
+pub mod session_force_logout;
+pub mod session_rename;
+pub mod session_revoke;
+pub mod session_revoke_others;
+pub mod unlock_login;
+
+pub use login::{FailedLogins, LoginHandler, SessionIssuer};
+pub use logout::LogoutHandler;
+pub use mfa_confirm::ConfirmMfaHandler;
+pub use mfa_disable::DisableMfaHandler;
+pub use mfa_enroll::EnrollMfaHandler;
+pub use mfa_verify::VerifyMfaHandler;
+pub use oidc_authorize::StartOidcLoginHandler;
+pub use oidc_callback::CompleteOidcLoginHandler;
+pub use oidc_unlink::UnlinkIdentityHandler;
+pub use refresh::RefreshHandler;
+pub use session_force_logout::ForceLogoutHandler;
+pub use session_rename::RenameSessionHandler;
+pub use session_revoke::RevokeSessionHandler;
+pub use session_revoke_others::RevokeOtherSessionsHandler;
+pub use unlock_login::UnlockLoginHandler;
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::{OidcAuthorizationResponse, StartOidcLogin};
+use crate::infrastructure::security::identities::IdentityStore;
+use crate::infrastructure::security::oidc::{pkce_challenge, OidcProviders};
+
+pub struct StartOidcLoginHandler {
+    pub(crate) providers: OidcProviders,
+    pub(crate) identities: IdentityStore,
+    pub(crate) state_ttl: chrono::Duration,
+}
+
+#[async_trait]
+impl CommandHandler<StartOidcLogin> for StartOidcLoginHandler {
+    type Output = OidcAuthorizationResponse;
+
+    async fn handle(&self, command: StartOidcLogin) -> Result<OidcAuthorizationResponse> {
+        let provider = self.providers.get(&command.provider)?;
+        let pending = self
+            .identities
+            .begin_authorization(provider.name(), command.link_user_id.as_deref(), self.state_ttl)
+            .await?;
+
+        let url = provider
+            .authorization_url(
+                pending.state.expose(),
+                &pending.nonce,
+                &pkce_challenge(pending.code_verifier.expose()),
+            )
+            .await?;
+
+        Ok(OidcAuthorizationResponse {
+            authorization_url: url.to_string(),
+            expires_in: self.state_ttl.num_seconds().max(0) as u64,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::login::SessionIssuer;
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{CompleteOidcLogin, OidcCallbackResponse};
+use crate::features::users::domain::models::{normalize_email, User, UserId};
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::audit::LoginMethod;
+use crate::infrastructure::security::identities::{IdentityStore, LinkedIdentity};
+use crate::infrastructure::security::oidc::{ExternalIdentity, OidcProvider, OidcProviders};
+
+pub struct CompleteOidcLoginHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) providers: OidcProviders,
+    pub(crate) identities: IdentityStore,
+    pub(crate) sessions: SessionIssuer,
+}
+
+#[async_trait]
+impl CommandHandler<CompleteOidcLogin> for CompleteOidcLoginHandler {
+    type Output = OidcCallbackResponse;
+
+    async fn handle(&self, command: CompleteOidcLogin) -> Result<OidcCallbackResponse> {
+        let provider = self.providers.get(&command.provider)?;
+        let returned = self
+            .identities
+            .take_authorization(provider.name(), command.request.state.expose())
+            .await?
+            .ok_or_else(|| AppError::Authentication("authorization state is invalid or expired".to_string()))?;
+
+        let id_token = provider
+            .exchange_code(command.request.code.expose(), &returned.code_verifier)
+            .await?;
+        let identity = provider.validate_id_token(&id_token, &returned.nonce).await?;
+
+        match returned.link_user_id {
+            Some(user_id) => {
+                // Without this check, a victim tricked into finishing an
+                // attacker's link request would attach their identity to
+                // the attacker's account.
+                if command.caller_id.as_deref() != Some(user_id.as_str()) {
+                    return Err(AppError::Authentication(
+                        "link requests must be completed by the user who started them".to_string(),
+                    ));
+                }
+                let linked = self.link(&provider, &identity, &user_id).await?;
+                tracing::info!(user_id = %user_id, provider = %provider.name(), "Linked external identity");
+                Ok(OidcCallbackResponse::Linked { linked })
+            }
+            None => {
+                let user = self.resolve(&provider, &identity).await?;
+                if !user.is_active() {
+                    return Err(AppError::Authentication("account is disabled".to_string()));
+                }
+                self.identities
+                    .record_login(provider.name(), &identity.subject, identity.email.as_deref())
+                    .await?;
+                Ok(OidcCallbackResponse::Login(self.sessions.begin(user.id().as_str(), &command.client, LoginMethod::Oidc).await?))
+            }
+        }
+    }
+}
+
+impl CompleteOidcLoginHandler {
+    async fn link(
+        &self,
+        provider: &OidcProvider,
+        identity: &ExternalIdentity,
+        user_id: &str,
+    ) -> Result<LinkedIdentity> {
+        self.identities
+            .link(provider.name(), &identity.subject, user_id, identity.email.as_deref())
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict(format!(
+                    "this {} account is linked to another user, or yours already has a different one",
+                    provider.config().display_name
+                ))
+            })
+    }
+
+    /// The local account for `identity`: the one it is linked to or, if the
+    /// provider vouches for the email, the account with that email.
+    async fn resolve(&self, provider: &OidcProvider, identity: &ExternalIdentity) -> Result<User> {
+        if let Some(linked) = self.identities.find(provider.name(), &identity.subject).await? {
+            return self
+                .users
+                .find_by_id(&UserId::parse(&linked.user_id)?)
+                .await?
+                .ok_or_else(|| AppError::Authentication("linked account no longer exists".to_string()));
+        }
+
+        let no_account = || {
+            AppError::Authentication(format!(
+                "no account is linked to this {} identity",
+                provider.config().display_name
+            ))
+        };
+        if !provider.config().link_by_email || !identity.email_verified {
+            return Err(no_account());
+        }
+        let Some(email) = identity.email.as_deref().and_then(|email| normalize_email(email).ok()) else {
+            return Err(no_account());
+        };
+        let user = self.users.find_by_email(&email).await?.ok_or_else(no_account)?;
+
+        self.link(provider, identity, user.id().as_str()).await?;
+        tracing::info!(user_id = %user.id(), provider = %provider.name(), "Linked external identity by verified email");
+        Ok(user)
+    }
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::UnlinkIdentity;
+use crate::infrastructure::security::identities::IdentityStore;
+
+pub struct UnlinkIdentityHandler {
+    pub(crate) identities: IdentityStore,
+}
+
+#[async_trait]
+impl CommandHandler<UnlinkIdentity> for UnlinkIdentityHandler {
+    type Output = ();
+
+    /// Every account has a password, so unlinking never locks anyone out.
+    async fn handle(&self, command: UnlinkIdentity) -> Result<()> {
+        if !self.identities.unlink(&command.user_id, &command.provider).await? {
+            return Err(AppError::NotFound(format!("linked `{}` identity", command.provider)));
+        }
+        tracing::info!(user_id = %command.user_id, provider = %command.provider, "Unlinked external identity");
+        Ok(())
+    }
+}
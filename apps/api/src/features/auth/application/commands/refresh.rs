@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{Refresh, TokenResponse};
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::jwt::TokenService;
+use crate::infrastructure::security::refresh_tokens::{RefreshOutcome, RefreshTokenStore};
+use crate::infrastructure::security::sessions::SessionStatusCache;
+
+pub struct RefreshHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) tokens: TokenService,
+    pub(crate) refresh_tokens: RefreshTokenStore,
+    pub(crate) session_status: SessionStatusCache,
+}
+
+#[async_trait]
+impl CommandHandler<Refresh> for RefreshHandler {
+    type Output = TokenResponse;
+
+    async fn handle(&self, command: Refresh) -> Result<TokenResponse> {
+        let token = command.request.refresh_token.expose();
+        let refresh = match self.refresh_tokens.rotate(token, &command.client).await? {
+            RefreshOutcome::Rotated(refresh) => refresh,
+            RefreshOutcome::Reused { session_id, .. } => {
+                self.session_status.deny([&session_id]);
+                return Err(AppError::Authentication(
+                    "refresh token was already used; the session has been revoked".to_string(),
+                ))
+            }
+            RefreshOutcome::Rejected => {
+                return Err(AppError::Authentication("invalid or expired refresh token".to_string()))
+            }
+        };
+
+        // A user disabled mid-session keeps no way to mint new access tokens.
+        let user_id = UserId::parse(&refresh.session.user_id)?;
+        let active = self.users.find_by_id(&user_id).await?.is_some_and(|user| user.is_active());
+        if !active {
+            return Err(AppError::Authentication("account is disabled".to_string()));
+        }
+
+        let access = self
+            .tokens
+            .issue(&refresh.session.user_id, &refresh.session.id, refresh.session.mfa_verified)?;
+        Ok(TokenResponse::new(access, refresh))
+    }
+}
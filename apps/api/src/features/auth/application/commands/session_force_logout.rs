@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{ForceLogout, RevokedSessionsResponse};
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::sessions::{SessionStatusCache, SessionStore};
+
+/// Reason recorded on sessions ended by an operator.
+pub const ADMIN_REVOKE_REASON: &str = "admin_revoked";
+
+pub struct ForceLogoutHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) sessions: SessionStore,
+    pub(crate) session_status: SessionStatusCache,
+}
+
+#[async_trait]
+impl CommandHandler<ForceLogout> for ForceLogoutHandler {
+    type Output = RevokedSessionsResponse;
+
+    /// Ends every session of the user. Their access tokens stop working
+    /// right away here and within `auth.session_check_interval_secs` on
+    /// other instances.
+    async fn handle(&self, command: ForceLogout) -> Result<RevokedSessionsResponse> {
+        if self.users.find_by_id(&UserId::parse(&command.user_id)?).await?.is_none() {
+            return Err(AppError::NotFound(format!("user `{}`", command.user_id)));
+        }
+        let revoked = self
+            .sessions
+            .revoke_all_for_user(&command.user_id, None, ADMIN_REVOKE_REASON)
+            .await?;
+        self.session_status.deny(&revoked);
+        tracing::warn!(user_id = %command.user_id, revoked = revoked.len(), "User force-logged out");
+        Ok(RevokedSessionsResponse { revoked: revoked.len() })
+    }
+}
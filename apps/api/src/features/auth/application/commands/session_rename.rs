@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::RenameSession;
+use crate::infrastructure::security::sessions::{ClientInfo, Session, SessionStore};
+
+pub struct RenameSessionHandler {
+    pub(crate) sessions: SessionStore,
+}
+
+#[async_trait]
+impl CommandHandler<RenameSession> for RenameSessionHandler {
+    type Output = Session;
+
+    /// Sets or, with a blank label, clears the device label of one of the
+    /// user's live sessions.
+    async fn handle(&self, command: RenameSession) -> Result<Session> {
+        let label = ClientInfo::new(command.device_label.as_deref(), None, None).device_label;
+        self.sessions
+            .set_device_label(&command.user_id, &command.session_id, label.as_deref())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("session `{}`", command.session_id)))
+    }
+}
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::RevokeSession;
+use crate::infrastructure::security::sessions::{SessionStatusCache, SessionStore};
+
+pub struct RevokeSessionHandler {
+    pub(crate) sessions: SessionStore,
+    pub(crate) session_status: SessionStatusCache,
+}
+
+#[async_trait]
+impl CommandHandler<RevokeSession> for RevokeSessionHandler {
+    type Output = ();
+
+    /// Ends one of the user's own sessions. Other users' sessions look
+    /// the same as unknown ones.
+    async fn handle(&self, command: RevokeSession) -> Result<()> {
+        if !self
+            .sessions
+            .revoke_owned(&command.user_id, &command.session_id, "user_revoked")
+            .await?
+        {
+            return Err(AppError::NotFound(format!("active session `{}`", command.session_id)));
+        }
+        self.session_status.deny([&command.session_id]);
+        tracing::info!(user_id = %command.user_id, session_id = %command.session_id, "Session revoked by its owner");
+        Ok(())
+    }
+}
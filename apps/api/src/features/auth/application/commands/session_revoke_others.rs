@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::Result;
+use crate::features::auth::application::dtos::{RevokeOtherSessions, RevokedSessionsResponse};
+use crate::infrastructure::security::sessions::{SessionStatusCache, SessionStore};
+
+pub struct RevokeOtherSessionsHandler {
+    pub(crate) sessions: SessionStore,
+    pub(crate) session_status: SessionStatusCache,
+}
+
+#[async_trait]
+impl CommandHandler<RevokeOtherSessions> for RevokeOtherSessionsHandler {
+    type Output = RevokedSessionsResponse;
+
+    async fn handle(&self, command: RevokeOtherSessions) -> Result<RevokedSessionsResponse> {
+        let revoked = self
+            .sessions
+            .revoke_all_for_user(&command.user_id, command.current_session_id.as_deref(), "user_revoked_others")
+            .await?;
+        self.session_status.deny(&revoked);
+        tracing::info!(user_id = %command.user_id, revoked = revoked.len(), "Other sessions revoked by their owner");
+        Ok(RevokedSessionsResponse { revoked: revoked.len() })
+    }
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::common::errors::{AppError, Result};
+use crate::features::auth::application::dtos::{Unlock, UnlockLogin, UnlockResponse};
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::security::audit::{Actor, AuditEvent, AuditLog};
+use crate::infrastructure::security::lockout::{LoginThrottle, ThrottleScope};
+
+pub struct UnlockLoginHandler {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) throttle: LoginThrottle,
+    pub(crate) audit: AuditLog,
+}
+
+#[async_trait]
+impl CommandHandler<UnlockLogin> for UnlockLoginHandler {
+    type Output = UnlockResponse;
+
+    /// Clears the failed-login count along with any delay or lockout, so
+    /// the next attempt is judged afresh.
+    async fn handle(&self, command: UnlockLogin) -> Result<UnlockResponse> {
+        let actor = command.actor_id.map_or(Actor::System, Actor::User);
+        let (unlocked, event) = match command.target {
+            Unlock::User { user_id } => {
+                let Some(user) = self.users.find_by_id(&UserId::parse(&user_id)?).await? else {
+                    return Err(AppError::NotFound(format!("user `{}`", user_id)));
+                };
+                let unlocked = self.throttle.clear(ThrottleScope::Account, user.email()).await?;
+                (unlocked, AuditEvent::AccountUnlocked { user_id, cleared: unlocked })
+            }
+            Unlock::Ip { ip_address } => {
+                let ip: IpAddr = ip_address
+                    .parse()
+                    .map_err(|_| AppError::InvalidInput(format!("`{}` is not an IP address", ip_address)))?;
+                let unlocked = self.throttle.clear(ThrottleScope::Ip, &ip.to_string()).await?;
+                let event = AuditEvent::IpUnlocked {
+                    ip_address: ip.to_string(),
+                    cleared: unlocked,
+                };
+                (unlocked, event)
+            }
+        };
+        self.audit.record(event.by(actor)).await;
+        Ok(UnlockResponse { unlocked })
+    }
+}
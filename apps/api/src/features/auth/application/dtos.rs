@@ -0,0 +1,259 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::constants::JWT_TOKEN_PREFIX;
+use crate::config::Secret;
+use crate::infrastructure::security::identities::LinkedIdentity;
+use crate::infrastructure::security::jwt::AccessToken;
+use crate::infrastructure::security::mfa::MAX_CHALLENGE_ATTEMPTS;
+use crate::infrastructure::security::refresh_tokens::IssuedRefreshToken;
+use crate::infrastructure::security::sessions::{ClientInfo, Session};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: Secret<String>,
+}
+
+/// A login attempt together with the device it comes from.
+#[derive(Debug, Clone)]
+pub struct Login {
+    pub request: LoginRequest,
+    pub client: ClientInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: Secret<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Refresh {
+    pub request: RefreshRequest,
+    pub client: ClientInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Secret<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+    pub refresh_token: String,
+    pub refresh_token_expires_at: DateTime<Utc>,
+}
+
+impl TokenResponse {
+    pub fn new(access: AccessToken, refresh: IssuedRefreshToken) -> Self {
+        Self {
+            access_token: access.token,
+            token_type: JWT_TOKEN_PREFIX.trim(),
+            expires_in: access.expires_in,
+            refresh_token: refresh.token.expose().clone(),
+            refresh_token_expires_at: refresh.expires_at,
+        }
+    }
+}
+
+/// Login either completes, or stops after the password step for accounts
+/// with 2FA and hands back a short-lived token for [`VerifyMfaRequest`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(TokenResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: u64,
+    pub max_attempts: i32,
+}
+
+impl MfaChallengeResponse {
+    pub fn new(mfa_token: Secret<String>, expires_in: u64) -> Self {
+        Self {
+            mfa_required: true,
+            mfa_token: mfa_token.expose().clone(),
+            expires_in,
+            max_attempts: MAX_CHALLENGE_ATTEMPTS,
+        }
+    }
+}
+
+/// Second login step: the pending token plus a TOTP code or, when the
+/// authenticator is lost, one recovery code.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerifyMfaRequest {
+    pub mfa_token: Secret<String>,
+    pub code: Option<String>,
+    pub recovery_code: Option<Secret<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyMfa {
+    pub request: VerifyMfaRequest,
+    pub client: ClientInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnrollMfa {
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MfaEnrollmentResponse {
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code.
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfirmMfaRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfirmMfa {
+    pub user_id: String,
+    pub code: String,
+}
+
+/// Shown once; only hashes are kept.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Turning 2FA off needs the password and a current second factor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DisableMfaRequest {
+    pub password: Secret<String>,
+    pub code: Option<String>,
+    pub recovery_code: Option<Secret<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DisableMfa {
+    pub user_id: String,
+    pub request: DisableMfaRequest,
+}
+
+/// Starts an OIDC authorization request, for signing in or, with
+/// `link_user_id`, for linking another identity to a signed-in user.
+#[derive(Debug, Clone)]
+pub struct StartOidcLogin {
+    pub provider: String,
+    pub link_user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OidcAuthorizationResponse {
+    /// Send the browser here. It returns to the provider's configured
+    /// redirect URI with `code` and `state`.
+    pub authorization_url: String,
+    pub expires_in: u64,
+}
+
+/// What the provider appended to the redirect URI.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: Secret<String>,
+    pub state: Secret<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompleteOidcLogin {
+    pub provider: String,
+    /// The signed-in caller, if any. Link requests must be completed by
+    /// the user who started them.
+    pub caller_id: Option<String>,
+    pub request: OidcCallbackRequest,
+    pub client: ClientInfo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum OidcCallbackResponse {
+    Login(LoginResponse),
+    Linked { linked: LinkedIdentity },
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlinkIdentity {
+    pub user_id: String,
+    pub provider: String,
+}
+
+/// A session as shown to its owner.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: Session,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameSessionRequest {
+    pub device_label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenameSession {
+    pub user_id: String,
+    pub session_id: String,
+    pub device_label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RevokeSession {
+    pub user_id: String,
+    pub session_id: String,
+}
+
+/// Signs out every other device of the user.
+#[derive(Debug, Clone)]
+pub struct RevokeOtherSessions {
+    pub user_id: String,
+    pub current_session_id: Option<String>,
+}
+
+/// Operator action: end every session of a user.
+#[derive(Debug, Clone)]
+pub struct ForceLogout {
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevokedSessionsResponse {
+    pub revoked: usize,
+}
+
+/// Operator action: lift the failed-login lockout of an account or a
+/// source IP.
+#[derive(Debug, Clone)]
+pub enum Unlock {
+    User { user_id: String },
+    Ip { ip_address: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlockLogin {
+    pub target: Unlock,
+    /// The operator, when the request came through the API.
+    pub actor_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlockResponse {
+    /// False when there was nothing to lift.
+    pub unlocked: bool,
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::types::JsonValue;
+use std::sync::Arc;
+
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::jobs::JobHandler;
+use crate::infrastructure::services::mail::{Email, Mailer};
+
+/// Payload of [`ACCOUNT_LOCKED_JOB`](super::commands::login::ACCOUNT_LOCKED_JOB).
+#[derive(Debug, Deserialize)]
+struct AccountLocked {
+    user_id: String,
+    failures: u32,
+    locked_until: Option<DateTime<Utc>>,
+    ip_address: Option<String>,
+}
+
+/// Tells the owner of an account that it was locked after repeated failed
+/// logins. The address is looked up when the job runs, so a notice goes to
+/// the current email and none is sent for a user deleted since.
+pub struct AccountLockedNotice {
+    pub(crate) users: Arc<dyn UserRepository>,
+    pub(crate) mailer: Arc<dyn Mailer>,
+}
+
+#[async_trait]
+impl JobHandler for AccountLockedNotice {
+    async fn run(&self, payload: &JsonValue) -> anyhow::Result<()> {
+        let notice: AccountLocked = serde_json::from_value(payload.clone())?;
+        let Some(user) = self.users.find_by_id(&UserId::parse(&notice.user_id)?).await? else {
+            return Ok(());
+        };
+
+        let until = notice
+            .locked_until
+            .map_or_else(|| "an administrator unlocks it".to_string(), |until| until.to_rfc2822());
+        let body = format!(
+            "Hello {},\n\n\
+             Sign-in to your account was locked after {} failed password attempts{}.\n\
+             It stays locked until {}.\n\n\
+             If these attempts were not yours, change your password once you can sign in again.\n",
+            user.name(),
+            notice.failures,
+            notice.ip_address.map(|ip| format!(" from {}", ip)).unwrap_or_default(),
+            until,
+        );
+        self.mailer
+            .send(&Email {
+                to: user.email().to_string(),
+                subject: "Your account has been locked".to_string(),
+                body,
+            })
+            .await
+    }
+}
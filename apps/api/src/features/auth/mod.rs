@@ -0,0 +1,303 @@
+pub mod application;
+
+use std::sync::Arc;
+
+use crate::config::auth::{AuthConfig, MfaConfig};
+use crate::features::users::UsersModule;
+use crate::infrastructure::database::connection::DatabasePool;
+use crate::infrastructure::jobs::{JobQueue, JobRunner};
+use crate::infrastructure::security::api_keys::ApiKeyStore;
+use crate::infrastructure::security::audit::AuditLog;
+use crate::infrastructure::security::encryption::Encryptor;
+use crate::infrastructure::security::identities::IdentityStore;
+use crate::infrastructure::security::jwt::TokenService;
+use crate::infrastructure::security::lockout::LoginThrottle;
+use crate::infrastructure::security::mfa::MfaStore;
+use crate::infrastructure::security::oidc::OidcProviders;
+use crate::infrastructure::security::rbac::RbacStore;
+use crate::infrastructure::security::refresh_tokens::RefreshTokenStore;
+use crate::infrastructure::security::request_signing::RequestVerifier;
+use crate::infrastructure::security::sessions::{SessionStatusCache, SessionStore};
+use crate::infrastructure::services::mail::Mailer;
+use application::commands::{
+    CompleteOidcLoginHandler, ConfirmMfaHandler, DisableMfaHandler, EnrollMfaHandler, FailedLogins,
+    ForceLogoutHandler, LoginHandler, LogoutHandler, RefreshHandler, RenameSessionHandler,
+    RevokeOtherSessionsHandler, RevokeSessionHandler, SessionIssuer, StartOidcLoginHandler,
+    UnlinkIdentityHandler, UnlockLoginHandler, VerifyMfaHandler,
+};
+use application::commands::login::ACCOUNT_LOCKED_JOB;
+use application::notifications::AccountLockedNotice;
+
+/// Login, token refresh, logout and session management. Access tokens are JWTs from
+/// [`TokenService`]; refresh tokens are opaque and rotated on every use.
+/// Failed password and second-factor attempts are throttled by
+/// [`LoginThrottle`]; logins and lockouts are recorded in the [`AuditLog`]. Users may
+/// also sign in through external OIDC providers. Machine clients
+/// authenticate with API keys instead.
+#[derive(Clone)]
+pub struct AuthModule {
+    users: UsersModule,
+    tokens: TokenService,
+    refresh_tokens: RefreshTokenStore,
+    sessions: SessionStore,
+    session_status: SessionStatusCache,
+    throttle: LoginThrottle,
+    jobs: JobQueue,
+    mailer: Option<Arc<dyn Mailer>>,
+    audit: AuditLog,
+    rbac: RbacStore,
+    api_keys: ApiKeyStore,
+    request_signing: RequestVerifier,
+    mfa: MfaStore,
+    mfa_config: MfaConfig,
+    oidc: OidcProviders,
+    identities: IdentityStore,
+    oidc_state_ttl: chrono::Duration,
+    refresh_token_ttl: chrono::Duration,
+}
+
+impl AuthModule {
+    /// Wires the Postgres-backed stores on `pool`. Sessions and API keys
+    /// send their listings to replicas; the other stores use the primary.
+    pub fn postgres(
+        users: UsersModule,
+        tokens: TokenService,
+        pool: DatabasePool,
+        audit: AuditLog,
+        encryptor: Encryptor,
+        mailer: Option<Arc<dyn Mailer>>,
+        config: &AuthConfig,
+    ) -> anyhow::Result<Self> {
+        let primary = pool.primary().clone();
+        Ok(Self {
+            users,
+            tokens,
+            refresh_tokens: RefreshTokenStore::new(primary.clone()),
+            sessions: SessionStore::new(pool.clone()),
+            session_status: SessionStatusCache::new(config.session_check_interval),
+            throttle: LoginThrottle::new(primary.clone(), config.lockout.clone()),
+            jobs: JobQueue::new(primary.clone()),
+            mailer,
+            audit,
+            rbac: RbacStore::new(primary.clone()),
+            api_keys: ApiKeyStore::new(pool, config.api_key_pepper.clone()),
+            request_signing: RequestVerifier::new(&config.request_signing, primary.clone()),
+            mfa: MfaStore::new(primary.clone(), encryptor.clone()),
+            mfa_config: config.mfa.clone(),
+            oidc: OidcProviders::new(&config.oidc)?,
+            identities: IdentityStore::new(primary, encryptor),
+            oidc_state_ttl: chrono::Duration::from_std(config.oidc.state_ttl).unwrap_or(chrono::Duration::MAX),
+            refresh_token_ttl: chrono::Duration::from_std(config.refresh_token_ttl)
+                .unwrap_or(chrono::Duration::MAX),
+        })
+    }
+
+    pub fn tokens(&self) -> &TokenService {
+        &self.tokens
+    }
+
+    pub fn sessions(&self) -> &SessionStore {
+        &self.sessions
+    }
+
+    /// Whether the session behind an access token is still live. See
+    /// [`SessionStatusCache`] for how quickly revocations apply.
+    pub async fn session_active(&self, session_id: &str) -> anyhow::Result<bool> {
+        self.session_status.is_active(&self.sessions, session_id).await
+    }
+
+    /// Ends the user's sessions, except `keep`, along with their refresh
+    /// token families, and denies their access tokens like
+    /// [`force_logout`](Self::force_logout) does. Returns how many ended.
+    pub async fn end_sessions(&self, user_id: &str, keep: Option<&str>, reason: &str) -> anyhow::Result<usize> {
+        let revoked = self.sessions.revoke_all_for_user(user_id, keep, reason).await?;
+        self.session_status.deny(&revoked);
+        Ok(revoked.len())
+    }
+
+    pub fn login_throttle(&self) -> &LoginThrottle {
+        &self.throttle
+    }
+
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    pub fn rbac(&self) -> &RbacStore {
+        &self.rbac
+    }
+
+    pub fn api_keys(&self) -> &ApiKeyStore {
+        &self.api_keys
+    }
+
+    pub fn request_signing(&self) -> &RequestVerifier {
+        &self.request_signing
+    }
+
+    pub fn login(&self) -> LoginHandler {
+        LoginHandler {
+            users: self.users.repository.clone(),
+            hasher: self.users.hasher.clone(),
+            failed_logins: self.failed_logins(),
+            audit: self.audit.clone(),
+            sessions: self.session_issuer(),
+        }
+    }
+
+    /// Registers handlers for the jobs this module queues.
+    pub fn register_jobs(&self, runner: JobRunner) -> JobRunner {
+        match &self.mailer {
+            Some(mailer) => runner.register(
+                ACCOUNT_LOCKED_JOB,
+                AccountLockedNotice {
+                    users: self.users.repository.clone(),
+                    mailer: mailer.clone(),
+                },
+            ),
+            None => runner,
+        }
+    }
+
+    fn failed_logins(&self) -> FailedLogins {
+        FailedLogins {
+            throttle: self.throttle.clone(),
+            lockout_notices: self.mailer.as_ref().map(|_| self.jobs.clone()),
+            audit: self.audit.clone(),
+        }
+    }
+
+    fn session_issuer(&self) -> SessionIssuer {
+        SessionIssuer {
+            tokens: self.tokens.clone(),
+            refresh_tokens: self.refresh_tokens.clone(),
+            mfa: self.mfa.clone(),
+            audit: self.audit.clone(),
+            refresh_token_ttl: self.refresh_token_ttl,
+            mfa_pending_ttl: chrono::Duration::from_std(self.mfa_config.pending_ttl)
+                .unwrap_or(chrono::Duration::MAX),
+        }
+    }
+
+    pub fn refresh(&self) -> RefreshHandler {
+        RefreshHandler {
+            users: self.users.repository.clone(),
+            tokens: self.tokens.clone(),
+            refresh_tokens: self.refresh_tokens.clone(),
+            session_status: self.session_status.clone(),
+        }
+    }
+
+    pub fn mfa(&self) -> &MfaStore {
+        &self.mfa
+    }
+
+    pub fn enroll_mfa(&self) -> EnrollMfaHandler {
+        EnrollMfaHandler {
+            users: self.users.repository.clone(),
+            mfa: self.mfa.clone(),
+            issuer: self.mfa_config.issuer.clone(),
+        }
+    }
+
+    pub fn confirm_mfa(&self) -> ConfirmMfaHandler {
+        ConfirmMfaHandler {
+            mfa: self.mfa.clone(),
+            recovery_codes: self.mfa_config.recovery_codes,
+        }
+    }
+
+    pub fn verify_mfa(&self) -> VerifyMfaHandler {
+        VerifyMfaHandler {
+            users: self.users.repository.clone(),
+            mfa: self.mfa.clone(),
+            tokens: self.tokens.clone(),
+            refresh_tokens: self.refresh_tokens.clone(),
+            failed_logins: self.failed_logins(),
+            audit: self.audit.clone(),
+            refresh_token_ttl: self.refresh_token_ttl,
+        }
+    }
+
+    pub fn disable_mfa(&self) -> DisableMfaHandler {
+        DisableMfaHandler {
+            users: self.users.repository.clone(),
+            hasher: self.users.hasher.clone(),
+            mfa: self.mfa.clone(),
+        }
+    }
+
+    pub fn oidc(&self) -> &OidcProviders {
+        &self.oidc
+    }
+
+    pub fn identities(&self) -> &IdentityStore {
+        &self.identities
+    }
+
+    pub fn start_oidc_login(&self) -> StartOidcLoginHandler {
+        StartOidcLoginHandler {
+            providers: self.oidc.clone(),
+            identities: self.identities.clone(),
+            state_ttl: self.oidc_state_ttl,
+        }
+    }
+
+    pub fn complete_oidc_login(&self) -> CompleteOidcLoginHandler {
+        CompleteOidcLoginHandler {
+            users: self.users.repository.clone(),
+            providers: self.oidc.clone(),
+            identities: self.identities.clone(),
+            sessions: self.session_issuer(),
+        }
+    }
+
+    pub fn unlink_identity(&self) -> UnlinkIdentityHandler {
+        UnlinkIdentityHandler {
+            identities: self.identities.clone(),
+        }
+    }
+
+    pub fn logout(&self) -> LogoutHandler {
+        LogoutHandler {
+            refresh_tokens: self.refresh_tokens.clone(),
+            session_status: self.session_status.clone(),
+        }
+    }
+
+    pub fn rename_session(&self) -> RenameSessionHandler {
+        RenameSessionHandler {
+            sessions: self.sessions.clone(),
+        }
+    }
+
+    pub fn revoke_session(&self) -> RevokeSessionHandler {
+        RevokeSessionHandler {
+            sessions: self.sessions.clone(),
+            session_status: self.session_status.clone(),
+        }
+    }
+
+    pub fn revoke_other_sessions(&self) -> RevokeOtherSessionsHandler {
+        RevokeOtherSessionsHandler {
+            sessions: self.sessions.clone(),
+            session_status: self.session_status.clone(),
+        }
+    }
+
+    pub fn force_logout(&self) -> ForceLogoutHandler {
+        ForceLogoutHandler {
+            users: self.users.repository.clone(),
+            sessions: self.sessions.clone(),
+            session_status: self.session_status.clone(),
+        }
+    }
+
+    pub fn unlock_login(&self) -> UnlockLoginHandler {
+        UnlockLoginHandler {
+            users: self.users.repository.clone(),
+            throttle: self.throttle.clone(),
+            audit: self.audit.clone(),
+        }
+    }
+}
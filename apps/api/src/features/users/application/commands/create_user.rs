@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::application::event::EventBus;
+use crate::common::errors::Result;
+use crate::common::validation::ValidateExt;
+use crate::features::users::application::dtos::{CreateUserRequest, UserResponse};
+use crate::features::users::domain::commands::RegisterUser;
+use crate::features::users::domain::events::UserEvent;
+use crate::features::users::domain::models::{normalize_email, User};
+use crate::features::users::domain::password_policy::PasswordPolicy;
+use crate::features::users::domain::services::ensure_email_available;
+use crate::features::users::ports::repositories::UserRepository;
+use crate::features::users::ports::services::{check_blocking, hash_blocking, PasswordHasher};
+
+pub struct CreateUserHandler {
+    repository: Arc<dyn UserRepository>,
+    hasher: Arc<dyn PasswordHasher>,
+    policy: PasswordPolicy,
+    events: EventBus,
+}
+
+impl CreateUserHandler {
+    pub fn new(
+        repository: Arc<dyn UserRepository>,
+        hasher: Arc<dyn PasswordHasher>,
+        policy: PasswordPolicy,
+        events: EventBus,
+    ) -> Self {
+        Self {
+            repository,
+            hasher,
+            policy,
+            events,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CreateUserRequest> for CreateUserHandler {
+    type Output = UserResponse;
+
+    async fn handle(&self, request: CreateUserRequest) -> Result<UserResponse> {
+        request.validate_into_app_error()?;
+        let command = RegisterUser::from(request);
+
+        let email = normalize_email(&command.email)?;
+        ensure_email_available(self.repository.as_ref(), &email, None).await?;
+        check_blocking(&self.policy, &command.password, &email, &command.name).await?;
+
+        let password_hash = hash_blocking(&self.hasher, &command.password).await?;
+        let user = User::register(&email, &command.name, password_hash)?;
+        self.repository.insert(&user).await?;
+
+        self.events.publish(&UserEvent::Created {
+            user_id: user.id().clone(),
+            email: user.email().to_string(),
+        });
+        Ok(UserResponse::from(&user))
+    }
+}
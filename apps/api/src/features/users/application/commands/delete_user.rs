@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::application::event::EventBus;
+use crate::common::errors::Result;
+use crate::features::users::domain::errors::UserError;
+use crate::features::users::domain::events::UserEvent;
+use crate::features::users::domain::models::UserId;
+use crate::features::users::ports::repositories::UserRepository;
+
+pub struct DeleteUser {
+    pub id: String,
+}
+
+pub struct DeleteUserHandler {
+    repository: Arc<dyn UserRepository>,
+    events: EventBus,
+}
+
+impl DeleteUserHandler {
+    pub fn new(repository: Arc<dyn UserRepository>, events: EventBus) -> Self {
+        Self { repository, events }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<DeleteUser> for DeleteUserHandler {
+    type Output = ();
+
+    async fn handle(&self, command: DeleteUser) -> Result<()> {
+        let id = UserId::parse(&command.id)?;
+        if !self.repository.delete(&id).await? {
+            return Err(UserError::NotFound(id.to_string()).into());
+        }
+
+        self.events.publish(&UserEvent::Deleted { user_id: id });
+        Ok(())
+    }
+}
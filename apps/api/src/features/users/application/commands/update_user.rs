@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::CommandHandler;
+use crate::application::event::EventBus;
+use crate::common::errors::{AppError, Result};
+use crate::common::validation::ValidateExt;
+use crate::features::users::application::dtos::{UpdateUserRequest, UserResponse};
+use crate::features::users::domain::commands::UpdateUser;
+use crate::features::users::domain::events::UserEvent;
+use crate::features::users::domain::models::normalize_email;
+use crate::features::users::domain::password_policy::PasswordPolicy;
+use crate::features::users::domain::services::{ensure_email_available, load};
+use crate::features::users::ports::repositories::UserRepository;
+use crate::features::users::ports::services::{check_blocking, hash_blocking, verify_blocking, PasswordHasher};
+
+pub struct UpdateUserCommand {
+    pub id: String,
+    pub changes: UpdateUserRequest,
+    /// Whether a password change must be confirmed with `current_password`:
+    /// set when users change their own, not for administrator resets.
+    pub confirm_password: bool,
+}
+
+pub struct UpdateUserHandler {
+    repository: Arc<dyn UserRepository>,
+    hasher: Arc<dyn PasswordHasher>,
+    policy: PasswordPolicy,
+    events: EventBus,
+}
+
+impl UpdateUserHandler {
+    pub fn new(
+        repository: Arc<dyn UserRepository>,
+        hasher: Arc<dyn PasswordHasher>,
+        policy: PasswordPolicy,
+        events: EventBus,
+    ) -> Self {
+        Self {
+            repository,
+            hasher,
+            policy,
+            events,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<UpdateUserCommand> for UpdateUserHandler {
+    type Output = UserResponse;
+
+    async fn handle(&self, command: UpdateUserCommand) -> Result<UserResponse> {
+        command.changes.validate_into_app_error()?;
+        let current_password = command.changes.current_password.clone();
+        let changes = UpdateUser::from(command.changes);
+        if changes.is_empty() {
+            return Err(AppError::InvalidInput("no fields to update".to_string()));
+        }
+
+        let mut user = load(self.repository.as_ref(), &command.id).await?;
+        let was_active = user.is_active();
+        let mut fields = Vec::new();
+
+        if let Some(email) = &changes.email {
+            let email = normalize_email(email)?;
+            if email != user.email() {
+                ensure_email_available(self.repository.as_ref(), &email, Some(user.id())).await?;
+                user.change_email(&email)?;
+                fields.push("email");
+            }
+        }
+        if let Some(name) = &changes.name {
+            user.rename(name)?;
+            fields.push("name");
+        }
+        if let Some(password) = &changes.password {
+            if command.confirm_password {
+                let current = current_password.as_deref().ok_or_else(|| {
+                    AppError::InvalidInput("current_password is required to change the password".to_string())
+                })?;
+                if !verify_blocking(&self.hasher, current, user.password_hash()).await {
+                    return Err(AppError::Authentication("invalid current password".to_string()));
+                }
+            }
+            check_blocking(&self.policy, password, user.email(), user.name()).await?;
+            user.set_password_hash(hash_blocking(&self.hasher, password).await?);
+            fields.push("password");
+        }
+        if let Some(active) = changes.is_active {
+            user.set_active(active);
+            fields.push("is_active");
+        }
+
+        self.repository.update(&user).await?;
+
+        self.events.publish(&UserEvent::Updated {
+            user_id: user.id().clone(),
+            fields,
+        });
+        if was_active && !user.is_active() {
+            self.events.publish(&UserEvent::Deactivated {
+                user_id: user.id().clone(),
+            });
+        }
+        Ok(UserResponse::from(&user))
+    }
+}
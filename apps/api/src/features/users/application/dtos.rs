@@ -0,0 +1,60 @@
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::common::constants::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::common::validation::{validate_email, MAX_NAME_LENGTH};
+
+// `validator` compares lengths as u64.
+const NAME_MAX_LENGTH: u64 = MAX_NAME_LENGTH as u64;
+
+#[derive(Debug, Clone, Deserialize, Validate, InputObject)]
+pub struct CreateUserRequest {
+    #[validate(custom(function = "validate_email"))]
+    pub email: String,
+    #[validate(length(min = 1, max = "NAME_MAX_LENGTH"))]
+    pub name: String,
+    /// Checked against the configured password policy by the handler.
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Validate, InputObject)]
+pub struct UpdateUserRequest {
+    #[validate(custom(function = "validate_email"))]
+    pub email: Option<String>,
+    #[validate(length(min = 1, max = "NAME_MAX_LENGTH"))]
+    pub name: Option<String>,
+    /// Checked against the configured password policy by the handler.
+    pub password: Option<String>,
+    /// Required with `password` when users change their own password.
+    pub current_password: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct UserResponse {
+    pub id: String,
+    pub email: String,
+    pub name: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListUsersQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl ListUsersQuery {
+    /// 1-based page number.
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> u32 {
+        self.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+}
@@ -0,0 +1,37 @@
+use super::dtos::{CreateUserRequest, UpdateUserRequest, UserResponse};
+use crate::features::users::domain::commands::{RegisterUser, UpdateUser};
+use crate::features::users::domain::models::User;
+
+impl From<&User> for UserResponse {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            email: user.email().to_string(),
+            name: user.name().to_string(),
+            is_active: user.is_active(),
+            created_at: user.created_at(),
+            updated_at: user.updated_at(),
+        }
+    }
+}
+
+impl From<CreateUserRequest> for RegisterUser {
+    fn from(request: CreateUserRequest) -> Self {
+        Self {
+            email: request.email,
+            name: request.name,
+            password: request.password,
+        }
+    }
+}
+
+impl From<UpdateUserRequest> for UpdateUser {
+    fn from(request: UpdateUserRequest) -> Self {
+        Self {
+            email: request.email,
+            name: request.name,
+            password: request.password,
+            is_active: request.is_active,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod commands;
+pub mod dtos;
+pub mod mappers;
+pub mod queries;
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::QueryHandler;
+use crate::common::errors::Result;
+use crate::features::users::application::dtos::UserResponse;
+use crate::features::users::domain::services::load;
+use crate::features::users::ports::repositories::UserRepository;
+
+pub struct GetUser {
+    pub id: String,
+}
+
+pub struct GetUserHandler {
+    repository: Arc<dyn UserRepository>,
+}
+
+impl GetUserHandler {
+    pub fn new(repository: Arc<dyn UserRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetUser> for GetUserHandler {
+    type Output = UserResponse;
+
+    async fn handle(&self, query: GetUser) -> Result<UserResponse> {
+        let user = load(self.repository.as_ref(), &query.id).await?;
+        Ok(UserResponse::from(&user))
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::command::QueryHandler;
+use crate::common::errors::Result;
+use crate::common::types::{PagedResponse, Pagination};
+use crate::features::users::application::dtos::{ListUsersQuery, UserResponse};
+use crate::features::users::ports::repositories::UserRepository;
+
+pub struct ListUsersHandler {
+    repository: Arc<dyn UserRepository>,
+}
+
+impl ListUsersHandler {
+    pub fn new(repository: Arc<dyn UserRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListUsersQuery> for ListUsersHandler {
+    type Output = PagedResponse<UserResponse>;
+
+    async fn handle(&self, query: ListUsersQuery) -> Result<PagedResponse<UserResponse>> {
+        let (page, per_page) = (query.page(), query.per_page());
+        let offset = u64::from(page - 1) * u64::from(per_page);
+        let (users, total) = self.repository.list(offset, per_page).await?;
+
+        Ok(PagedResponse {
+            data: users.iter().map(UserResponse::from).collect(),
+            pagination: Pagination {
+                page,
+                per_page,
+                total,
+                total_pages: total.div_ceil(u64::from(per_page)) as u32,
+            },
+        })
+    }
+}
@@ -0,0 +1,5 @@
+pub mod get_user;
+pub mod list_users;
+
+pub use get_user::{GetUser, GetUserHandler};
+pub use list_users::ListUsersHandler;
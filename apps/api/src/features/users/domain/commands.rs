@@ -0,0 +1,23 @@
+/// Intent to create an account. The password is plaintext until the
+/// application layer hashes it.
+#[derive(Debug, Clone)]
+pub struct RegisterUser {
+    pub email: String,
+    pub name: String,
+    pub password: String,
+}
+
+/// Partial update; `None` leaves a field unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateUser {
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub password: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+impl UpdateUser {
+    pub fn is_empty(&self) -> bool {
+        self.email.is_none() && self.name.is_none() && self.password.is_none() && self.is_active.is_none()
+    }
+}
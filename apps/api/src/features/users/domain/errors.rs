@@ -0,0 +1,60 @@
+use thiserror::Error;
+
+use super::password_policy::PasswordViolation;
+use crate::common::errors::{AppError, ValidationError};
+
+#[derive(Debug, Error)]
+pub enum UserError {
+    #[error("invalid {field}: {code}")]
+    Invalid { field: &'static str, code: String },
+
+    #[error("password does not meet the policy ({} violation(s))", .0.len())]
+    WeakPassword(Vec<PasswordViolation>),
+
+    #[error("a user with email {0} already exists")]
+    EmailTaken(String),
+
+    #[error("user {0} not found")]
+    NotFound(String),
+
+    #[error("password hashing failed: {0}")]
+    Hashing(String),
+
+    #[error(transparent)]
+    Repository(#[from] anyhow::Error),
+}
+
+impl UserError {
+    pub fn invalid(field: &'static str, code: impl Into<String>) -> Self {
+        Self::Invalid {
+            field,
+            code: code.into(),
+        }
+    }
+}
+
+impl From<UserError> for AppError {
+    fn from(error: UserError) -> Self {
+        match error {
+            UserError::Invalid { field, code } => AppError::validation_error(vec![ValidationError {
+                field: field.to_string(),
+                message: crate::common::validation::get_error_message(&code, field),
+                code,
+            }]),
+            UserError::WeakPassword(violations) => AppError::validation_error(
+                violations
+                    .iter()
+                    .map(|violation| ValidationError {
+                        field: "password".to_string(),
+                        code: violation.code().to_string(),
+                        message: violation.message(),
+                    })
+                    .collect(),
+            ),
+            UserError::EmailTaken(_) => AppError::Conflict(error.to_string()),
+            UserError::NotFound(_) => AppError::NotFound(error.to_string()),
+            UserError::Hashing(message) => AppError::Internal(anyhow::anyhow!(message)),
+            UserError::Repository(source) => AppError::Internal(source),
+        }
+    }
+}
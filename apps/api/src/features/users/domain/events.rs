@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+use super::models::UserId;
+use crate::application::event::DomainEvent;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserEvent {
+    Created { user_id: UserId, email: String },
+    Updated { user_id: UserId, fields: Vec<&'static str> },
+    Deactivated { user_id: UserId },
+    Deleted { user_id: UserId },
+}
+
+impl DomainEvent for UserEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            UserEvent::Created { .. } => "user.created",
+            UserEvent::Updated { .. } => "user.updated",
+            UserEvent::Deactivated { .. } => "user.deactivated",
+            UserEvent::Deleted { .. } => "user.deleted",
+        }
+    }
+}
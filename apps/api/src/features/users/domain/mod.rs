@@ -0,0 +1,6 @@
+pub mod commands;
+pub mod errors;
+pub mod events;
+pub mod models;
+pub mod password_policy;
+pub mod services;
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::errors::UserError;
+use crate::common::validation::{validate_email, MAX_NAME_LENGTH};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UserId(String);
+
+impl UserId {
+    pub fn generate() -> Self {
+        Self(cuid::cuid2())
+    }
+
+    pub fn parse(id: &str) -> Result<Self, UserError> {
+        if !cuid::is_cuid2(id) {
+            return Err(UserError::NotFound(id.to_string()));
+        }
+        Ok(Self(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Wraps an id read back from storage, which was validated on the way in.
+    pub(crate) fn from_trusted(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The user aggregate. Fields are private so every change goes through a
+/// method that re-checks the email and name invariants.
+#[derive(Debug, Clone)]
+pub struct User {
+    id: UserId,
+    email: String,
+    name: String,
+    password_hash: String,
+    is_active: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl User {
+    /// Creates a new active user. `password_hash` must come from a
+    /// [`PasswordHasher`](crate::features::users::ports::services::PasswordHasher)
+    /// after the
+    /// [`PasswordPolicy`](super::password_policy::PasswordPolicy) accepted the plaintext.
+    pub fn register(email: &str, name: &str, password_hash: String) -> Result<Self, UserError> {
+        let now = Utc::now();
+        Ok(Self {
+            id: UserId::generate(),
+            email: normalize_email(email)?,
+            name: normalize_name(name)?,
+            password_hash,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Rebuilds a user from storage without re-running validation.
+    pub fn restore(
+        id: UserId,
+        email: String,
+        name: String,
+        password_hash: String,
+        is_active: bool,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            email,
+            name,
+            password_hash,
+            is_active,
+            created_at,
+            updated_at,
+        }
+    }
+
+    pub fn change_email(&mut self, email: &str) -> Result<(), UserError> {
+        self.email = normalize_email(email)?;
+        self.touch();
+        Ok(())
+    }
+
+    pub fn rename(&mut self, name: &str) -> Result<(), UserError> {
+        self.name = normalize_name(name)?;
+        self.touch();
+        Ok(())
+    }
+
+    pub fn set_password_hash(&mut self, password_hash: String) {
+        self.password_hash = password_hash;
+        self.touch();
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.is_active = active;
+        self.touch();
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+
+    pub fn id(&self) -> &UserId {
+        &self.id
+    }
+
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn password_hash(&self) -> &str {
+        &self.password_hash
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
+/// Emails are compared case-insensitively, so they are stored lowercased.
+pub fn normalize_email(email: &str) -> Result<String, UserError> {
+    let email = email.trim().to_lowercase();
+    validate_email(&email).map_err(|e| UserError::invalid("email", e.code))?;
+    Ok(email)
+}
+
+fn normalize_name(name: &str) -> Result<String, UserError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(UserError::invalid("name", "required"));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(UserError::invalid("name", "name_too_long"));
+    }
+    Ok(name.to_string())
+}
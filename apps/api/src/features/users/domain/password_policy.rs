@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use super::errors::UserError;
+use crate::config::auth::PasswordPolicyConfig;
+use crate::features::users::ports::services::BreachedPasswords;
+
+/// Shorter fragments of an email or name are too common to reject.
+const MIN_PERSONAL_FRAGMENT: usize = 3;
+
+/// One way a password falls short of the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    MissingUppercase,
+    MissingLowercase,
+    MissingDigit,
+    MissingSymbol,
+    ContainsEmail,
+    ContainsName,
+    Breached,
+}
+
+impl PasswordViolation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordViolation::TooShort { .. } => "password_too_short",
+            PasswordViolation::TooLong { .. } => "password_too_long",
+            PasswordViolation::MissingUppercase => "password_missing_uppercase",
+            PasswordViolation::MissingLowercase => "password_missing_lowercase",
+            PasswordViolation::MissingDigit => "password_missing_digit",
+            PasswordViolation::MissingSymbol => "password_missing_symbol",
+            PasswordViolation::ContainsEmail => "password_contains_email",
+            PasswordViolation::ContainsName => "password_contains_name",
+            PasswordViolation::Breached => "password_breached",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            PasswordViolation::TooShort { min } => format!("Password must be at least {} characters", min),
+            PasswordViolation::TooLong { max } => format!("Password must not exceed {} characters", max),
+            PasswordViolation::MissingUppercase => "Password must contain an uppercase letter".to_string(),
+            PasswordViolation::MissingLowercase => "Password must contain a lowercase letter".to_string(),
+            PasswordViolation::MissingDigit => "Password must contain a number".to_string(),
+            PasswordViolation::MissingSymbol => "Password must contain a special character".to_string(),
+            PasswordViolation::ContainsEmail => "Password must not contain your email address".to_string(),
+            PasswordViolation::ContainsName => "Password must not contain your name".to_string(),
+            PasswordViolation::Breached => {
+                "This password has appeared in a data breach; choose a different one".to_string()
+            }
+        }
+    }
+}
+
+/// The configured rules for new passwords, checked before hashing. Every
+/// broken rule is reported, not just the first.
+#[derive(Clone, Default)]
+pub struct PasswordPolicy {
+    config: PasswordPolicyConfig,
+    breached: Option<Arc<dyn BreachedPasswords>>,
+}
+
+impl PasswordPolicy {
+    pub fn new(config: PasswordPolicyConfig, breached: Option<Arc<dyn BreachedPasswords>>) -> Self {
+        Self { config, breached }
+    }
+
+    /// Fails with [`UserError::WeakPassword`] listing every violation for
+    /// an account with this `email` and `name`.
+    pub fn check(&self, password: &str, email: &str, name: &str) -> Result<(), UserError> {
+        let violations = self.violations(password, email, name);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(UserError::WeakPassword(violations))
+        }
+    }
+
+    pub fn violations(&self, password: &str, email: &str, name: &str) -> Vec<PasswordViolation> {
+        let config = &self.config;
+        let mut violations = Vec::new();
+
+        let length = password.chars().count();
+        if length < config.min_length {
+            violations.push(PasswordViolation::TooShort { min: config.min_length });
+        }
+        if length > config.max_length {
+            violations.push(PasswordViolation::TooLong { max: config.max_length });
+        }
+        if config.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+        if config.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+        if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+        if config.require_symbol && !password.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PasswordViolation::MissingSymbol);
+        }
+
+        if config.reject_personal_info {
+            let password = password.to_lowercase();
+            let contains = |fragment: &str| {
+                fragment.chars().count() >= MIN_PERSONAL_FRAGMENT && password.contains(&fragment.to_lowercase())
+            };
+            let local_part = email.split('@').next().unwrap_or_default();
+            if contains(local_part) {
+                violations.push(PasswordViolation::ContainsEmail);
+            }
+            if name.split(|c: char| !c.is_alphanumeric()).any(contains) {
+                violations.push(PasswordViolation::ContainsName);
+            }
+        }
+
+        // Past the length limit nothing is worth hashing for the lookup.
+        if length <= config.max_length && self.is_breached(password) {
+            violations.push(PasswordViolation::Breached);
+        }
+        violations
+    }
+
+    /// A corpus that cannot be read lets the password through rather than
+    /// blocking every signup.
+    fn is_breached(&self, password: &str) -> bool {
+        let Some(breached) = &self.breached else {
+            return false;
+        };
+        match breached.occurrences(password) {
+            Ok(count) => count >= self.config.breached_min_count,
+            Err(e) => {
+                tracing::error!(error = %e, "Breached password lookup failed; skipping the check");
+                false
+            }
+        }
+    }
+}
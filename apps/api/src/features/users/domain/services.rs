@@ -0,0 +1,24 @@
+use super::errors::UserError;
+use super::models::{User, UserId};
+use crate::features::users::ports::repositories::UserRepository;
+
+/// Fails with [`UserError::EmailTaken`] if another user already owns `email`.
+/// The unique index still backs this up against concurrent registrations.
+pub async fn ensure_email_available(
+    repository: &dyn UserRepository,
+    email: &str,
+    except: Option<&UserId>,
+) -> Result<(), UserError> {
+    match repository.find_by_email(email).await? {
+        Some(existing) if Some(existing.id()) != except => Err(UserError::EmailTaken(email.to_string())),
+        _ => Ok(()),
+    }
+}
+
+pub async fn load(repository: &dyn UserRepository, id: &str) -> Result<User, UserError> {
+    let id = UserId::parse(id)?;
+    repository
+        .find_by_id(&id)
+        .await?
+        .ok_or_else(|| UserError::NotFound(id.to_string()))
+}
@@ -0,0 +1,2 @@
+pub mod repositories;
+pub mod services;
@@ -0,0 +1,3 @@
+pub mod user_repository;
+
+pub use user_repository::{InMemoryUserRepository, PgUserRepository};
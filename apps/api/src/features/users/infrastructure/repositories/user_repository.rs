@@ -0,0 +1,315 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::features::users::domain::errors::UserError;
+use crate::features::users::domain::models::{User, UserId};
+use crate::features::users::ports::repositories::UserRepository;
+use crate::infrastructure::database::connection::DatabasePool;
+use crate::infrastructure::security::encryption::{BlindIndex, Encrypted, EncryptedColumn, Encryptor};
+
+/// Unique indexes on the email: the blind index of encrypted emails and
+/// `lower(email)` of rows not yet re-encrypted.
+const USERS_EMAIL_KEYS: &[&str] = &["users_email_bidx_key", "users_email_key"];
+
+/// Where emails are kept, encrypted, with a blind index for lookups.
+/// Emails stored before encryption sit in `email` until
+/// `cli encryption re-encrypt` moves them.
+pub const EMAIL_COLUMN: EncryptedColumn = EncryptedColumn {
+    table: "users",
+    key: "id",
+    column: "email_encrypted",
+    context: "users.email",
+    legacy_plaintext: Some("email"),
+    blind_index: Some(BlindIndex {
+        column: "email_bidx",
+        normalize: normalize_indexed_email,
+    }),
+};
+
+fn normalize_indexed_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+#[derive(FromRow)]
+struct UserRow {
+    id: String,
+    email: Option<String>,
+    email_encrypted: Option<Encrypted<String>>,
+    name: String,
+    password_hash: String,
+    is_active: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+const USER_COLUMNS: &str = "id, email, email_encrypted, name, password_hash, is_active, created_at, updated_at";
+
+pub struct PgUserRepository {
+    pool: DatabasePool,
+    encryptor: Encryptor,
+}
+
+impl PgUserRepository {
+    pub fn new(pool: DatabasePool, encryptor: Encryptor) -> Self {
+        Self { pool, encryptor }
+    }
+
+    fn restore(&self, row: UserRow) -> Result<User, UserError> {
+        let email = match (row.email_encrypted, row.email) {
+            (Some(encrypted), _) => self
+                .encryptor
+                .decrypt(EMAIL_COLUMN.context, &encrypted)
+                .map_err(UserError::Repository)?,
+            (None, Some(plaintext)) => plaintext,
+            (None, None) => return Err(UserError::Repository(anyhow::anyhow!("user {} has no email", row.id))),
+        };
+        Ok(User::restore(
+            UserId::from_trusted(row.id),
+            email,
+            row.name,
+            row.password_hash,
+            row.is_active,
+            row.created_at,
+            row.updated_at,
+        ))
+    }
+
+    /// The encrypted email of `user` and its blind index.
+    fn seal_email(&self, user: &User) -> Result<(Encrypted<String>, Vec<u8>), UserError> {
+        let encrypted = self
+            .encryptor
+            .encrypt(EMAIL_COLUMN.context, &user.email().to_string())
+            .map_err(UserError::Repository)?;
+        let index = self.email_index(user.email());
+        Ok((encrypted, index))
+    }
+
+    fn email_index(&self, email: &str) -> Vec<u8> {
+        EMAIL_COLUMN
+            .blind_index(&self.encryptor, email)
+            .expect("the email column has a blind index")
+    }
+}
+
+/// Maps the unique-email violations onto the domain error; everything else
+/// is an infrastructure failure. An email left in plaintext is only checked
+/// against encrypted ones by the lookup in
+/// [`ensure_email_available`](crate::features::users::domain::services::ensure_email_available)
+/// until it is re-encrypted.
+fn write_error(error: sqlx::Error, email: &str) -> UserError {
+    match &error {
+        sqlx::Error::Database(db) if db.constraint().is_some_and(|name| USERS_EMAIL_KEYS.contains(&name)) => {
+            UserError::EmailTaken(email.to_string())
+        }
+        _ => UserError::Repository(error.into()),
+    }
+}
+
+fn read_error(error: sqlx::Error) -> UserError {
+    UserError::Repository(error.into())
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepository {
+    async fn insert(&self, user: &User) -> Result<(), UserError> {
+        let (email, email_index) = self.seal_email(user)?;
+        sqlx::query(
+            "INSERT INTO users (id, email_encrypted, email_bidx, name, password_hash, is_active, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(user.id().as_str())
+        .bind(&email)
+        .bind(&email_index)
+        .bind(user.name())
+        .bind(user.password_hash())
+        .bind(user.is_active())
+        .bind(user.created_at())
+        .bind(user.updated_at())
+        .execute(self.pool.write())
+        .await
+        .map_err(|e| write_error(e, user.email()))?;
+        Ok(())
+    }
+
+    async fn update(&self, user: &User) -> Result<(), UserError> {
+        let (email, email_index) = self.seal_email(user)?;
+        let result = sqlx::query(
+            "UPDATE users
+             SET email = NULL, email_encrypted = $2, email_bidx = $3, name = $4, password_hash = $5,
+                 is_active = $6, updated_at = $7
+             WHERE id = $1",
+        )
+        .bind(user.id().as_str())
+        .bind(&email)
+        .bind(&email_index)
+        .bind(user.name())
+        .bind(user.password_hash())
+        .bind(user.is_active())
+        .bind(user.updated_at())
+        .execute(self.pool.write())
+        .await
+        .map_err(|e| write_error(e, user.email()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserError::NotFound(user.id().to_string()));
+        }
+        Ok(())
+    }
+
+    async fn replace_password_hash(&self, id: &UserId, current: &str, new: &str) -> Result<bool, UserError> {
+        let result = sqlx::query("UPDATE users SET password_hash = $3 WHERE id = $1 AND password_hash = $2")
+            .bind(id.as_str())
+            .bind(current)
+            .bind(new)
+            .execute(self.pool.write())
+            .await
+            .map_err(read_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn delete(&self, id: &UserId) -> Result<bool, UserError> {
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id.as_str())
+            .execute(self.pool.write())
+            .await
+            .map_err(read_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn find_by_id(&self, id: &UserId) -> Result<Option<User>, UserError> {
+        let row: Option<UserRow> = sqlx::query_as(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
+            .bind(id.as_str())
+            .fetch_optional(self.pool.read())
+            .await
+            .map_err(read_error)?;
+        row.map(|row| self.restore(row)).transpose()
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserError> {
+        let row: Option<UserRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM users WHERE email_bidx = $1 OR lower(email) = lower($2)",
+            USER_COLUMNS
+        ))
+        .bind(self.email_index(email))
+        .bind(email)
+        .fetch_optional(self.pool.read())
+        .await
+        .map_err(read_error)?;
+        row.map(|row| self.restore(row)).transpose()
+    }
+
+    async fn list(&self, offset: u64, limit: u32) -> Result<(Vec<User>, u64), UserError> {
+        let rows: Vec<UserRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM users ORDER BY created_at, id OFFSET $1 LIMIT $2",
+            USER_COLUMNS
+        ))
+        .bind(offset as i64)
+        .bind(limit as i64)
+        .fetch_all(self.pool.read())
+        .await
+        .map_err(read_error)?;
+
+        let total: i64 = sqlx::query_scalar("SELECT count(*) FROM users")
+            .fetch_one(self.pool.read())
+            .await
+            .map_err(read_error)?;
+
+        let users = rows
+            .into_iter()
+            .map(|row| self.restore(row))
+            .collect::<Result<_, _>>()?;
+        Ok((users, total as u64))
+    }
+}
+
+/// Process-local repository for tests and for running the API without a
+/// database. Enforces the same email uniqueness as the Postgres index.
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: RwLock<HashMap<UserId, User>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn email_taken(users: &HashMap<UserId, User>, user: &User) -> bool {
+        users
+            .values()
+            .any(|other| other.id() != user.id() && other.email().eq_ignore_ascii_case(user.email()))
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn insert(&self, user: &User) -> Result<(), UserError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        if Self::email_taken(&users, user) {
+            return Err(UserError::EmailTaken(user.email().to_string()));
+        }
+        users.insert(user.id().clone(), user.clone());
+        Ok(())
+    }
+
+    async fn update(&self, user: &User) -> Result<(), UserError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        if !users.contains_key(user.id()) {
+            return Err(UserError::NotFound(user.id().to_string()));
+        }
+        if Self::email_taken(&users, user) {
+            return Err(UserError::EmailTaken(user.email().to_string()));
+        }
+        users.insert(user.id().clone(), user.clone());
+        Ok(())
+    }
+
+    async fn replace_password_hash(&self, id: &UserId, current: &str, new: &str) -> Result<bool, UserError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        let Some(user) = users.get_mut(id).filter(|user| user.password_hash() == current) else {
+            return Ok(false);
+        };
+        *user = User::restore(
+            user.id().clone(),
+            user.email().to_string(),
+            user.name().to_string(),
+            new.to_string(),
+            user.is_active(),
+            user.created_at(),
+            user.updated_at(),
+        );
+        Ok(true)
+    }
+
+    async fn delete(&self, id: &UserId) -> Result<bool, UserError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        Ok(users.remove(id).is_some())
+    }
+
+    async fn find_by_id(&self, id: &UserId) -> Result<Option<User>, UserError> {
+        let users = self.users.read().unwrap_or_else(|e| e.into_inner());
+        Ok(users.get(id).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserError> {
+        let users = self.users.read().unwrap_or_else(|e| e.into_inner());
+        Ok(users.values().find(|user| user.email().eq_ignore_ascii_case(email)).cloned())
+    }
+
+    async fn list(&self, offset: u64, limit: u32) -> Result<(Vec<User>, u64), UserError> {
+        let users = self.users.read().unwrap_or_else(|e| e.into_inner());
+        let mut all: Vec<&User> = users.values().collect();
+        all.sort_by(|a, b| (a.created_at(), a.id().as_str()).cmp(&(b.created_at(), b.id().as_str())));
+
+        let page = all
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        Ok((page, users.len() as u64))
+    }
+}
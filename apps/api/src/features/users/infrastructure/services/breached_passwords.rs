@@ -0,0 +1,131 @@
+use anyhow::{anyhow, bail, Context, Result};
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::features::users::ports::services::BreachedPasswords;
+
+/// Longest line accepted: 40 hex digits, `:`, a count and a line ending.
+const MAX_LINE_LENGTH: usize = 64;
+/// Shorter prefixes would match too many unrelated passwords.
+const MIN_PREFIX_LENGTH: usize = 10;
+const SHA1_HEX_LENGTH: usize = 40;
+
+/// Breached passwords from a sorted file of hex SHA-1 hashes, or of hash
+/// prefixes of one fixed length, one per line with an optional `:count`.
+/// Lookups binary-search the file on disk, so a corpus of any size costs
+/// a few small reads per check and is never loaded into memory.
+pub struct BreachedPasswordFile {
+    file: Mutex<File>,
+    len: u64,
+    prefix_length: usize,
+}
+
+impl BreachedPasswordFile {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening breached password file `{}`", path.display()))?;
+        let len = file.metadata()?.len();
+        let mut corpus = Self {
+            file: Mutex::new(file),
+            len,
+            prefix_length: SHA1_HEX_LENGTH,
+        };
+
+        let (_, _, first) = corpus
+            .line_from(0)?
+            .ok_or_else(|| anyhow!("breached password file `{}` is empty", path.display()))?;
+        let prefix = first.split(':').next().unwrap_or_default();
+        if !(MIN_PREFIX_LENGTH..=SHA1_HEX_LENGTH).contains(&prefix.len()) {
+            bail!(
+                "breached password file `{}` must hold SHA-1 hashes or prefixes of {} to {} hex digits",
+                path.display(),
+                MIN_PREFIX_LENGTH,
+                SHA1_HEX_LENGTH
+            );
+        }
+        corpus.prefix_length = prefix.len();
+        corpus.parse(&first).with_context(|| format!("reading `{}`", path.display()))?;
+        Ok(corpus)
+    }
+
+    /// The first line starting at or after `offset`, as its start, its
+    /// length including the newline, and its text.
+    fn line_from(&self, offset: u64) -> Result<Option<(u64, u64, String)>> {
+        // Reading from the byte before `offset` finds a line that starts
+        // exactly there.
+        let read_from = offset.saturating_sub(1);
+        let mut buf = Vec::with_capacity(2 * MAX_LINE_LENGTH);
+        {
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.seek(SeekFrom::Start(read_from))?;
+            (&mut *file).take(2 * MAX_LINE_LENGTH as u64).read_to_end(&mut buf)?;
+        }
+
+        let skip = if offset == 0 {
+            0
+        } else {
+            match buf.iter().position(|&b| b == b'\n') {
+                Some(newline) => newline + 1,
+                None => return Ok(None),
+            }
+        };
+        let start = read_from + skip as u64;
+        if start >= self.len {
+            return Ok(None);
+        }
+        let rest = &buf[skip..];
+        let end = match rest.iter().position(|&b| b == b'\n') {
+            Some(newline) => newline,
+            None if start + rest.len() as u64 >= self.len => rest.len(),
+            None => bail!("line at offset {} is longer than {} bytes", start, MAX_LINE_LENGTH),
+        };
+        let line = std::str::from_utf8(&rest[..end])
+            .map_err(|_| anyhow!("line at offset {} is not text", start))?
+            .trim_end_matches('\r')
+            .to_string();
+        Ok(Some((start, end as u64 + 1, line)))
+    }
+
+    /// Splits a line into its uppercase hash prefix and count.
+    fn parse(&self, line: &str) -> Result<(String, u64)> {
+        let (hash, count) = match line.split_once(':') {
+            Some((hash, count)) => (hash, count.trim().parse().map_err(|_| anyhow!("bad count in `{}`", line))?),
+            None => (line, 1),
+        };
+        if hash.len() != self.prefix_length || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("expected {} hex digits, got `{}`", self.prefix_length, line);
+        }
+        Ok((hash.to_ascii_uppercase(), count))
+    }
+}
+
+impl BreachedPasswords for BreachedPasswordFile {
+    fn occurrences(&self, password: &str) -> Result<u64> {
+        let digest = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let target = &digest[..self.prefix_length];
+
+        // Invariant: a line holding `target` starts in [lo, hi).
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let Some((start, length, line)) = self.line_from(mid)? else {
+                hi = mid;
+                continue;
+            };
+            if start >= hi {
+                hi = mid;
+                continue;
+            }
+            let (hash, count) = self.parse(&line)?;
+            match hash.as_str().cmp(target) {
+                Ordering::Equal => return Ok(count),
+                Ordering::Less => lo = start + length,
+                Ordering::Greater => hi = mid,
+            }
+        }
+        Ok(0)
+    }
+}
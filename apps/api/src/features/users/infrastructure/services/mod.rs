@@ -0,0 +1,35 @@
+mod breached_passwords;
+
+pub use breached_passwords::BreachedPasswordFile;
+
+use crate::common::security::PasswordHashing;
+use crate::config::auth::PasswordHashingConfig;
+use crate::features::users::domain::errors::UserError;
+use crate::features::users::ports::services::PasswordHasher;
+
+/// Argon2id via `common::security`, with the configured cost and pepper.
+#[derive(Clone, Default)]
+pub struct Argon2PasswordHasher {
+    hashing: PasswordHashing,
+}
+
+impl Argon2PasswordHasher {
+    pub fn new(config: &PasswordHashingConfig) -> Result<Self, UserError> {
+        let hashing = PasswordHashing::new(config).map_err(|e| UserError::Hashing(e.to_string()))?;
+        Ok(Self { hashing })
+    }
+}
+
+impl PasswordHasher for Argon2PasswordHasher {
+    fn hash(&self, password: &str) -> Result<String, UserError> {
+        self.hashing.hash(password).map_err(|e| UserError::Hashing(e.to_string()))
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> bool {
+        self.hashing.verify(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        self.hashing.needs_rehash(hash)
+    }
+}
@@ -0,0 +1,104 @@
+pub mod application;
+pub mod domain;
+pub mod infrastructure;
+pub mod ports;
+
+use std::sync::Arc;
+
+use crate::application::event::EventBus;
+use crate::config::auth::AuthConfig;
+use crate::infrastructure::database::connection::DatabasePool;
+use crate::infrastructure::security::encryption::Encryptor;
+use application::commands::{CreateUserHandler, DeleteUserHandler, UpdateUserHandler};
+use application::queries::{GetUserHandler, ListUsersHandler};
+use infrastructure::repositories::{InMemoryUserRepository, PgUserRepository};
+use domain::password_policy::PasswordPolicy;
+use infrastructure::services::{Argon2PasswordHasher, BreachedPasswordFile};
+use ports::repositories::UserRepository;
+use ports::services::{BreachedPasswords, PasswordHasher};
+
+/// Wiring for the users feature: holds the adapters and hands out
+/// use-case handlers to the API layers.
+#[derive(Clone)]
+pub struct UsersModule {
+    pub repository: Arc<dyn UserRepository>,
+    pub hasher: Arc<dyn PasswordHasher>,
+    policy: PasswordPolicy,
+    events: EventBus,
+}
+
+impl UsersModule {
+    pub fn new(
+        repository: Arc<dyn UserRepository>,
+        hasher: Arc<dyn PasswordHasher>,
+        policy: PasswordPolicy,
+        events: EventBus,
+    ) -> Self {
+        Self {
+            repository,
+            hasher,
+            policy,
+            events,
+        }
+    }
+
+    /// Fails when the hashing parameters are unusable or the breached
+    /// password corpus cannot be opened.
+    pub fn postgres(
+        pool: DatabasePool,
+        config: &AuthConfig,
+        encryptor: Encryptor,
+        events: EventBus,
+    ) -> anyhow::Result<Self> {
+        let policy = &config.password_policy;
+        let breached = match &policy.breached_passwords {
+            Some(path) => Some(Arc::new(BreachedPasswordFile::open(path)?) as Arc<dyn BreachedPasswords>),
+            None => None,
+        };
+        Ok(Self::new(
+            Arc::new(PgUserRepository::new(pool, encryptor)),
+            Arc::new(Argon2PasswordHasher::new(&config.password_hashing)?),
+            PasswordPolicy::new(policy.clone(), breached),
+            events,
+        ))
+    }
+
+    pub fn in_memory(events: EventBus) -> Self {
+        Self::new(
+            Arc::new(InMemoryUserRepository::new()),
+            Arc::new(Argon2PasswordHasher::default()),
+            PasswordPolicy::default(),
+            events,
+        )
+    }
+
+    pub fn create_user(&self) -> CreateUserHandler {
+        CreateUserHandler::new(
+            self.repository.clone(),
+            self.hasher.clone(),
+            self.policy.clone(),
+            self.events.clone(),
+        )
+    }
+
+    pub fn update_user(&self) -> UpdateUserHandler {
+        UpdateUserHandler::new(
+            self.repository.clone(),
+            self.hasher.clone(),
+            self.policy.clone(),
+            self.events.clone(),
+        )
+    }
+
+    pub fn delete_user(&self) -> DeleteUserHandler {
+        DeleteUserHandler::new(self.repository.clone(), self.events.clone())
+    }
+
+    pub fn get_user(&self) -> GetUserHandler {
+        GetUserHandler::new(self.repository.clone())
+    }
+
+    pub fn list_users(&self) -> ListUsersHandler {
+        ListUsersHandler::new(self.repository.clone())
+    }
+}
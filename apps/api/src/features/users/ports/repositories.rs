@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::features::users::domain::errors::UserError;
+use crate::features::users::domain::models::{User, UserId};
+
+/// Persistence port for the user aggregate. `insert` and `update` report a
+/// duplicate email as [`UserError::EmailTaken`].
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn insert(&self, user: &User) -> Result<(), UserError>;
+
+    async fn update(&self, user: &User) -> Result<(), UserError>;
+
+    /// Swaps the stored hash only if it is still `current`, so an upgrade
+    /// never overwrites a concurrent password change. Returns whether it
+    /// was swapped.
+    async fn replace_password_hash(&self, id: &UserId, current: &str, new: &str) -> Result<bool, UserError>;
+
+    /// Returns `false` when no user had this id.
+    async fn delete(&self, id: &UserId) -> Result<bool, UserError>;
+
+    async fn find_by_id(&self, id: &UserId) -> Result<Option<User>, UserError>;
+
+    /// `email` must already be normalized.
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserError>;
+
+    /// One page ordered by creation time, plus the total number of users.
+    async fn list(&self, offset: u64, limit: u32) -> Result<(Vec<User>, u64), UserError>;
+}
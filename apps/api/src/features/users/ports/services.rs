@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::features::users::domain::errors::UserError;
+use crate::features::users::domain::password_policy::PasswordPolicy;
+
+#[cfg_attr(test, mockall::automock)]
+pub trait PasswordHasher: Send + Sync {
+    fn hash(&self, password: &str) -> Result<String, UserError>;
+
+    fn verify(&self, password: &str, hash: &str) -> bool;
+
+    /// Whether `hash`, already verified, should be replaced with a fresh
+    /// hash of the password under the current policy.
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
+
+/// Runs [`PasswordHasher::hash`] on the blocking pool: a hash costs tens of
+/// milliseconds of CPU, which would otherwise stall an async worker.
+pub async fn hash_blocking(hasher: &Arc<dyn PasswordHasher>, password: &str) -> Result<String, UserError> {
+    let hasher = hasher.clone();
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hasher.hash(&password))
+        .await
+        .map_err(|e| UserError::Hashing(e.to_string()))?
+}
+
+/// Runs [`PasswordHasher::verify`] on the blocking pool, like [`hash_blocking`].
+pub async fn verify_blocking(hasher: &Arc<dyn PasswordHasher>, password: &str, hash: &str) -> bool {
+    let hasher = hasher.clone();
+    let (password, hash) = (password.to_string(), hash.to_string());
+    tokio::task::spawn_blocking(move || hasher.verify(&password, &hash))
+        .await
+        .unwrap_or(false)
+}
+
+/// Runs [`PasswordPolicy::check`] on the blocking pool: the breached
+/// password lookup reads its corpus from disk.
+pub async fn check_blocking(policy: &PasswordPolicy, password: &str, email: &str, name: &str) -> Result<(), UserError> {
+    let policy = policy.clone();
+    let (password, email, name) = (password.to_string(), email.to_string(), name.to_string());
+    tokio::task::spawn_blocking(move || policy.check(&password, &email, &name))
+        .await
+        .map_err(|e| UserError::Repository(e.into()))?
+}
+
+/// A corpus of passwords exposed in breaches.
+#[cfg_attr(test, mockall::automock)]
+pub trait BreachedPasswords: Send + Sync {
+    /// How often `password` appears in the corpus; zero if it does not.
+    fn occurrences(&self, password: &str) -> anyhow::Result<u64>;
+}
@@ -1,41 +1,235 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
 use anyhow::Result;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
+use crate::config::database::PoolConfig;
 use crate::config::Config;
 
-pub type DatabasePool = PgPool;
+/// Writers are forgotten once every this many writes after their window.
+const PRUNE_EVERY: u64 = 1024;
 
-const MIN_CONNECTIONS: u32 = 2;
-const MAX_CONNECTIONS: u32 = 30;
-const MAX_LIFETIME: Duration = Duration::from_secs(30 * 60);
-const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
-const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+tokio::task_local! {
+    static SCOPE: Scope;
+}
+
+/// What [`DatabasePool::scope`] tracks for one request.
+struct Scope {
+    principal: Option<String>,
+    pinned: AtomicBool,
+}
+
+/// Primary pool plus zero or more read replicas.
+///
+/// Writes always go to the primary. Reads are spread round-robin over the
+/// healthy replicas and fall back to the primary when none are healthy.
+/// Inside [`DatabasePool::scope`], which the API enters for every request,
+/// reads follow a write to the primary for the rest of the request, and
+/// for `database.read_after_write_secs` for the same principal, so callers
+/// observe their own writes.
+#[derive(Clone)]
+pub struct DatabasePool {
+    primary: PgPool,
+    replicas: Arc<Vec<Replica>>,
+    next_replica: Arc<AtomicUsize>,
+    recent_writes: Arc<RecentWrites>,
+}
+
+struct Replica {
+    name: String,
+    pool: PgPool,
+    healthy: AtomicBool,
+}
+
+/// When each principal last wrote, in this process.
+#[derive(Default)]
+struct RecentWrites {
+    window: Duration,
+    writers: Mutex<HashMap<String, Instant>>,
+    writes: AtomicU64,
+}
+
+impl RecentWrites {
+    fn record(&self, principal: &str) {
+        let now = Instant::now();
+        let mut writers = self.writers.lock().unwrap_or_else(|e| e.into_inner());
+        if self.writes.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+            writers.retain(|_, wrote| now.duration_since(*wrote) < self.window);
+        }
+        writers.insert(principal.to_string(), now);
+    }
+
+    fn is_recent(&self, principal: &str) -> bool {
+        let writers = self.writers.lock().unwrap_or_else(|e| e.into_inner());
+        writers.get(principal).is_some_and(|wrote| wrote.elapsed() < self.window)
+    }
+}
+
+impl DatabasePool {
+    pub fn new(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replicas: Arc::new(Vec::new()),
+            next_replica: Arc::new(AtomicUsize::new(0)),
+            recent_writes: Arc::new(RecentWrites::default()),
+        }
+    }
+
+    /// Runs `request` as one unit of read-your-writes consistency on behalf
+    /// of `principal` (e.g. `user:{id}`), or of nobody in particular.
+    pub async fn scope<F: Future>(&self, principal: Option<String>, request: F) -> F::Output {
+        let pinned = !self.replicas.is_empty()
+            && principal
+                .as_deref()
+                .is_some_and(|principal| self.recent_writes.is_recent(principal));
+        let scope = Scope {
+            principal,
+            pinned: AtomicBool::new(pinned),
+        };
+        SCOPE.scope(scope, request).await
+    }
+
+    /// Pool for statements that modify data.
+    pub fn write(&self) -> &PgPool {
+        if !self.replicas.is_empty() {
+            let _ = SCOPE.try_with(|scope| {
+                scope.pinned.store(true, Ordering::Relaxed);
+                if let Some(principal) = &scope.principal {
+                    self.recent_writes.record(principal);
+                }
+            });
+        }
+        &self.primary
+    }
+
+    /// Pool for read-only statements.
+    pub fn read(&self) -> &PgPool {
+        if self.replicas.is_empty() || self.is_pinned() {
+            return &self.primary;
+        }
+
+        let len = self.replicas.len();
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        (0..len)
+            .map(|offset| &self.replicas[(start + offset) % len])
+            .find(|replica| replica.healthy.load(Ordering::Relaxed))
+            .map(|replica| &replica.pool)
+            .unwrap_or(&self.primary)
+    }
+
+    pub fn primary(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// Whether reads in the current scope go to the primary.
+    pub fn is_pinned(&self) -> bool {
+        SCOPE
+            .try_with(|scope| scope.pinned.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    pub fn healthy_replicas(&self) -> usize {
+        self.replicas
+            .iter()
+            .filter(|replica| replica.healthy.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Periodically probes every replica and takes failing ones out of the
+    /// read rotation until they recover.
+    pub fn spawn_health_checks(&self, interval: Duration) -> Option<JoinHandle<()>> {
+        if self.replicas.is_empty() {
+            return None;
+        }
+
+        let replicas = self.replicas.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for replica in replicas.iter() {
+                    let healthy = sqlx::query("SELECT 1").execute(&replica.pool).await.is_ok();
+                    let was_healthy = replica.healthy.swap(healthy, Ordering::Relaxed);
+                    if healthy != was_healthy {
+                        if healthy {
+                            info!(replica = %replica.name, "Read replica back in rotation");
+                        } else {
+                            warn!(replica = %replica.name, "Read replica failed health check; routing reads elsewhere");
+                        }
+                    }
+                }
+            }
+        }))
+    }
+
+    pub async fn close(&self) {
+        self.primary.close().await;
+        for replica in self.replicas.iter() {
+            replica.pool.close().await;
+        }
+    }
+}
+
+fn pool_options(pool: &PoolConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .min_connections(pool.min_connections)
+        .max_connections(pool.max_connections)
+        .max_lifetime(Some(pool.max_lifetime))
+        .acquire_timeout(pool.acquire_timeout)
+        .idle_timeout(Some(pool.idle_timeout))
+}
 
 pub async fn create_pool(config: &Config) -> Result<DatabasePool> {
     info!("Initializing database connection pool...");
 
-    let pool = PgPoolOptions::new()
-        .min_connections(MIN_CONNECTIONS)
-        .max_connections(MAX_CONNECTIONS)
-        .max_lifetime(Some(MAX_LIFETIME))
-        .acquire_timeout(ACQUIRE_TIMEOUT)
-        .idle_timeout(Some(IDLE_TIMEOUT))
-        .connect(&config.database.connection_string())
+    let database = &config.database;
+    let primary = pool_options(&database.pool)
+        .connect_with(database.connect_options())
         .await?;
 
-    check_database_connection(&pool).await?;
+    check_database_connection(&primary).await?;
+
+    // Replicas connect lazily so an unavailable replica never blocks boot;
+    // they join the read rotation after their first successful health check.
+    let replicas = database
+        .replicas
+        .iter()
+        .map(|replica| Replica {
+            name: replica.name.clone(),
+            pool: pool_options(&replica.pool).connect_lazy_with(replica_options(replica.connect_options())),
+            healthy: AtomicBool::new(false),
+        })
+        .collect::<Vec<_>>();
 
     info!(
-        "Database connection pool initialized with {} max connections",
-        MAX_CONNECTIONS
+        "Database connection pool initialized with {} max connections and {} read replica(s)",
+        database.pool.max_connections,
+        replicas.len()
     );
 
+    let pool = DatabasePool {
+        replicas: Arc::new(replicas),
+        recent_writes: Arc::new(RecentWrites {
+            window: database.read_after_write,
+            ..RecentWrites::default()
+        }),
+        ..DatabasePool::new(primary)
+    };
+    pool.spawn_health_checks(database.replica_health_interval);
+
     Ok(pool)
 }
 
-pub async fn check_database_connection(pool: &DatabasePool) -> Result<()> {
+fn replica_options(options: PgConnectOptions) -> PgConnectOptions {
+    options.options([("default_transaction_read_only", "on")])
+}
+
+pub async fn check_database_connection(pool: &PgPool) -> Result<()> {
     match sqlx::query("SELECT 1").execute(pool).await {
         Ok(_) => {
             info!("Database connection test successful");
@@ -52,4 +246,4 @@ pub async fn close_pool(pool: DatabasePool) {
     info!("Closing database connection pool...");
     pool.close().await;
     info!("Database connection pool closed");
-}
\ No newline at end of file
+}
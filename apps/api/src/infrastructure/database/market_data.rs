@@ -0,0 +1,164 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+/// Rows per multi-row insert, well under Postgres' 65535 bind parameters.
+const BATCH_SIZE: usize = 5000;
+
+/// An asset and daily bars for it. The asset is created on first sight;
+/// afterwards its descriptive columns are left alone.
+#[derive(Debug, Clone)]
+pub struct AssetBars {
+    pub symbol: String,
+    pub name: String,
+    pub asset_class: String,
+    pub currency: String,
+    pub exchange: Option<String>,
+    pub bars: Vec<PriceBar>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PriceBar {
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+/// Observations of one indicator, created on first sight.
+#[derive(Debug, Clone)]
+pub struct MacroSeries {
+    pub indicator: String,
+    pub unit: String,
+    pub source: String,
+    pub observations: Vec<MacroObservation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MacroObservation {
+    pub country_code: String,
+    pub observed_on: NaiveDate,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SentimentItem {
+    pub external_id: String,
+    pub text: String,
+    pub score: f64,
+    pub metrics: Option<serde_json::Value>,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Writes pushed by ingestion clients. Every write is an upsert, so a
+/// client can resend a batch after a timeout without duplicating rows.
+#[derive(Clone)]
+pub struct MarketDataStore {
+    pool: PgPool,
+}
+
+impl MarketDataStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the asset's id.
+    pub async fn upsert_bars(&self, asset: &AssetBars) -> Result<String> {
+        let mut tx = self.pool.begin().await?;
+        // The no-op update makes RETURNING yield the id of an existing row.
+        let asset_id: String = sqlx::query_scalar(
+            "INSERT INTO assets (id, symbol, name, asset_class, currency, exchange)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (symbol) DO UPDATE SET symbol = EXCLUDED.symbol
+             RETURNING id",
+        )
+        .bind(cuid::cuid2())
+        .bind(&asset.symbol)
+        .bind(&asset.name)
+        .bind(&asset.asset_class)
+        .bind(&asset.currency)
+        .bind(&asset.exchange)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for chunk in asset.bars.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Postgres> =
+                QueryBuilder::new("INSERT INTO price_bars (asset_id, bar_date, open, high, low, close, volume) ");
+            query.push_values(chunk, |mut row, bar| {
+                row.push_bind(&asset_id)
+                    .push_bind(bar.date)
+                    .push_bind(bar.open)
+                    .push_bind(bar.high)
+                    .push_bind(bar.low)
+                    .push_bind(bar.close)
+                    .push_bind(bar.volume);
+            });
+            query.push(
+                " ON CONFLICT (asset_id, bar_date) DO UPDATE
+                  SET open = EXCLUDED.open, high = EXCLUDED.high, low = EXCLUDED.low,
+                      close = EXCLUDED.close, volume = EXCLUDED.volume",
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(asset_id)
+    }
+
+    pub async fn upsert_macro(&self, series: &MacroSeries) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO macro_indicators (code, name, unit, source) VALUES ($1, $1, $2, $3)
+             ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(&series.indicator)
+        .bind(&series.unit)
+        .bind(&series.source)
+        .execute(&mut *tx)
+        .await?;
+
+        for chunk in series.observations.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Postgres> =
+                QueryBuilder::new("INSERT INTO macro_observations (indicator_code, country_code, observed_on, value) ");
+            query.push_values(chunk, |mut row, observation| {
+                row.push_bind(&series.indicator)
+                    .push_bind(&observation.country_code)
+                    .push_bind(observation.observed_on)
+                    .push_bind(observation.value);
+            });
+            query.push(
+                " ON CONFLICT (indicator_code, country_code, observed_on) DO UPDATE SET value = EXCLUDED.value",
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn upsert_sentiment(&self, source: &str, query_text: &str, items: &[SentimentItem]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for chunk in items.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO sentiment_items (id, source, external_id, query, text, score, metrics, published_at) ",
+            );
+            query.push_values(chunk, |mut row, item| {
+                row.push_bind(cuid::cuid2())
+                    .push_bind(source)
+                    .push_bind(&item.external_id)
+                    .push_bind(query_text)
+                    .push_bind(&item.text)
+                    .push_bind(item.score)
+                    .push_bind(&item.metrics)
+                    .push_bind(item.published_at);
+            });
+            query.push(
+                " ON CONFLICT (source, external_id) DO UPDATE
+                  SET text = EXCLUDED.text, score = EXCLUDED.score, metrics = EXCLUDED.metrics",
+            );
+            query.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -1,12 +1,313 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use sqlx::migrate::{AppliedMigration, Migrate, Migration, Migrator};
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::info;
 
+use crate::config::database::MigrationMode;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub const MIGRATIONS_DIR: &str = "migrations";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the local file no longer matches what was run.
+    ChecksumMismatch,
+    /// Recorded in the database with no matching local file.
+    Missing,
+    /// Started but never completed successfully.
+    Dirty,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::ChecksumMismatch => "checksum mismatch",
+            MigrationState::Missing => "missing locally",
+            MigrationState::Dirty => "dirty",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub reversible: bool,
+}
+
+fn up_migrations() -> impl Iterator<Item = &'static Migration> {
+    MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+}
+
+fn down_migration(version: i64) -> Option<&'static Migration> {
+    MIGRATOR
+        .iter()
+        .find(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+/// Reports every local migration as pending when the migrations table
+/// does not exist yet, without creating it.
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>> {
+    let mut conn = pool.acquire().await?;
+    collect_status(&mut conn).await
+}
+
+async fn migrations_table_exists(conn: &mut sqlx::PgConnection) -> Result<bool> {
+    Ok(sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(conn)
+        .await?)
+}
+
+async fn collect_status(conn: &mut sqlx::PgConnection) -> Result<Vec<MigrationStatus>> {
+    let (applied, dirty) = if migrations_table_exists(conn).await? {
+        (conn.list_applied_migrations().await?, conn.dirty_version().await?)
+    } else {
+        (Vec::new(), None)
+    };
+
+    let applied_by_version: HashMap<i64, &AppliedMigration> =
+        applied.iter().map(|m| (m.version, m)).collect();
+
+    let mut statuses: Vec<MigrationStatus> = up_migrations()
+        .map(|migration| {
+            let state = match applied_by_version.get(&migration.version) {
+                _ if dirty == Some(migration.version) => MigrationState::Dirty,
+                Some(a) if a.checksum != migration.checksum => MigrationState::ChecksumMismatch,
+                Some(_) => MigrationState::Applied,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+                reversible: down_migration(migration.version).is_some(),
+            }
+        })
+        .collect();
+
+    for a in &applied {
+        if !MIGRATOR.version_exists(a.version) {
+            statuses.push(MigrationStatus {
+                version: a.version,
+                description: String::new(),
+                state: MigrationState::Missing,
+                reversible: false,
+            });
+        }
+    }
+
+    statuses.sort_by_key(|s| s.version);
+    Ok(statuses)
+}
+
+/// Fails unless every local migration is applied, unchanged and clean.
+/// Only reads: a database that was never migrated fails rather than
+/// getting an empty migrations table.
+pub async fn verify(pool: &PgPool) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    if !migrations_table_exists(&mut conn).await? {
+        bail!("database schema is not current: no migrations have been applied");
+    }
+    let problems: Vec<String> = collect_status(&mut conn)
+        .await?
+        .into_iter()
+        .filter(|s| s.state != MigrationState::Applied)
+        .map(|s| format!("{} {} ({})", s.version, s.description, s.state.as_str()))
+        .collect();
+
+    if !problems.is_empty() {
+        bail!("database schema is not current: {}", problems.join(", "));
+    }
+
+    info!("Database schema is current");
+    Ok(())
+}
+
+fn ensure_consistent(statuses: &[MigrationStatus]) -> Result<()> {
+    if let Some(s) = statuses.iter().find(|s| s.state == MigrationState::Dirty) {
+        bail!("migration {} is dirty; fix the database manually before continuing", s.version);
+    }
+    if let Some(s) = statuses.iter().find(|s| s.state == MigrationState::Missing) {
+        bail!("migration {} was applied but is missing from {}", s.version, MIGRATIONS_DIR);
+    }
+    if let Some(s) = statuses.iter().find(|s| s.state == MigrationState::ChecksumMismatch) {
+        bail!(
+            "migration {} ({}) was modified after being applied; restore the original file",
+            s.version,
+            s.description
+        );
+    }
+    Ok(())
+}
+
+/// Runs `f` while holding the migration advisory lock so concurrently
+/// starting instances apply migrations exactly once.
+async fn with_lock<T, F>(pool: &PgPool, f: F) -> Result<T>
+where
+    F: for<'c> FnOnce(&'c mut sqlx::PgConnection) -> futures::future::BoxFuture<'c, Result<T>>,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    conn.lock().await?;
+    let result = f(&mut conn).await;
+    conn.unlock().await?;
+    result
+}
+
+/// Applies all pending migrations, returning the versions that ran (or, with
+/// `dry_run`, would run).
+pub async fn up(pool: &PgPool, dry_run: bool) -> Result<Vec<&'static Migration>> {
+    apply_pending(pool, dry_run, None).await
+}
+
+async fn apply_pending(
+    pool: &PgPool,
+    dry_run: bool,
+    only: Option<i64>,
+) -> Result<Vec<&'static Migration>> {
+    with_lock(pool, move |conn| {
+        Box::pin(async move {
+            let statuses = collect_status(conn).await?;
+            ensure_consistent(&statuses)?;
+
+            let pending: Vec<&'static Migration> = up_migrations()
+                .filter(|m| only.is_none_or(|version| version == m.version))
+                .filter(|m| {
+                    statuses
+                        .iter()
+                        .any(|s| s.version == m.version && s.state == MigrationState::Pending)
+                })
+                .collect();
+
+            if !dry_run {
+                for migration in &pending {
+                    let elapsed = conn.apply(migration).await?;
+                    log_step("Applied", migration, elapsed);
+                }
+            }
+            Ok(pending)
+        })
+    })
+    .await
+}
+
+/// Reverts the last `count` applied migrations, newest first.
+pub async fn down(pool: &PgPool, count: usize, dry_run: bool) -> Result<Vec<&'static Migration>> {
+    with_lock(pool, move |conn| {
+        Box::pin(async move {
+            let statuses = collect_status(conn).await?;
+            ensure_consistent(&statuses)?;
+
+            let targets = statuses
+                .iter()
+                .rev()
+                .filter(|s| s.state == MigrationState::Applied)
+                .take(count)
+                .map(|s| {
+                    down_migration(s.version).ok_or_else(|| {
+                        anyhow!("migration {} ({}) has no down script", s.version, s.description)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if !dry_run {
+                for migration in &targets {
+                    let elapsed = conn.revert(migration).await?;
+                    log_step("Reverted", migration, elapsed);
+                }
+            }
+            Ok(targets)
+        })
+    })
+    .await
+}
+
+/// Reverts and re-applies the most recent migration.
+pub async fn redo(pool: &PgPool, dry_run: bool) -> Result<Vec<&'static Migration>> {
+    let mut steps = down(pool, 1, dry_run).await?;
+    let Some(reverted) = steps.first().map(|m| m.version) else {
+        return Ok(steps);
+    };
+
+    if dry_run {
+        steps.extend(up_migrations().filter(|m| m.version == reverted));
+    } else {
+        steps.extend(apply_pending(pool, false, Some(reverted)).await?);
+    }
+    Ok(steps)
+}
+
+fn log_step(action: &str, migration: &Migration, elapsed: Duration) {
+    info!(
+        version = migration.version,
+        description = %migration.description,
+        elapsed_ms = elapsed.as_millis() as u64,
+        "{} migration",
+        action
+    );
+}
+
+/// Creates `{timestamp}_{description}.sql`, or an `.up.sql`/`.down.sql` pair
+/// when `reversible` is set.
+pub fn create(dir: &Path, description: &str, reversible: bool) -> Result<Vec<PathBuf>> {
+    let slug: String = description
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if slug.trim_matches('_').is_empty() {
+        bail!("migration description must contain at least one letter or digit");
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let stem = format!("{}_{}", chrono::Utc::now().format("%Y%m%d%H%M%S"), slug);
+
+    let files = if reversible {
+        vec![
+            (dir.join(format!("{}.up.sql", stem)), "-- Add up migration script here\n"),
+            (dir.join(format!("{}.down.sql", stem)), "-- Add down migration script here\n"),
+        ]
+    } else {
+        vec![(dir.join(format!("{}.sql", stem)), "-- Add migration script here\n")]
+    };
+
+    for (path, contents) in &files {
+        std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    }
+
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
-    sqlx::migrate!("./migrations")
-        .run(pool)
-        .await?;
+    let applied = up(pool, false).await?;
 
-    info!("Database migrations completed successfully");
+    info!(
+        "Database migrations completed successfully ({} applied)",
+        applied.len()
+    );
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Boot-time migration behaviour selected by `database.migrations`.
+pub async fn prepare_schema(pool: &PgPool, mode: MigrationMode) -> Result<()> {
+    match mode {
+        MigrationMode::Auto => run_migrations(pool).await,
+        MigrationMode::VerifyOnly => verify(pool).await,
+        MigrationMode::Skip => {
+            info!("Skipping database migrations");
+            Ok(())
+        }
+    }
+}
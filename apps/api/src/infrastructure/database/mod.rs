@@ -1,2 +1,4 @@
 pub mod connection;
-pub mod migrations;
\ No newline at end of file
+pub mod market_data;
+pub mod migrations;
+pub mod seed;
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use chrono::{Datelike, Months, NaiveDate};
+use fake::rand::rngs::StdRng;
+use fake::rand::{Rng, SeedableRng};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use super::market::{fnv1a, SERIES_EPOCH};
+use super::{standard_normal, BATCH_SIZE};
+
+#[derive(Debug, Clone, Copy)]
+enum Model {
+    /// Geometric growth, for levels such as GDP.
+    Growth,
+    /// Mean-reverting AR(1) around the starting value, for rates.
+    Rate { floor: f64 },
+}
+
+struct Indicator {
+    code: &'static str,
+    name: &'static str,
+    unit: &'static str,
+    months_between: u32,
+    model: Model,
+}
+
+/// Codes follow the IMF datamapper ids the scraper pulls.
+const INDICATORS: &[Indicator] = &[
+    Indicator {
+        code: "NGDPD",
+        name: "GDP, current prices",
+        unit: "billions of USD",
+        months_between: 12,
+        model: Model::Growth,
+    },
+    Indicator {
+        code: "PCPIPCH",
+        name: "Inflation rate, average consumer prices",
+        unit: "annual percent change",
+        months_between: 1,
+        model: Model::Rate { floor: -5.0 },
+    },
+    Indicator {
+        code: "LUR",
+        name: "Unemployment rate",
+        unit: "percent of total labor force",
+        months_between: 1,
+        model: Model::Rate { floor: 0.5 },
+    },
+    Indicator {
+        code: "NGDP_RPCH",
+        name: "Real GDP growth",
+        unit: "annual percent change",
+        months_between: 3,
+        model: Model::Rate { floor: -15.0 },
+    },
+];
+
+/// ISO alpha-3 code and each indicator's level at `SERIES_EPOCH`, in
+/// `INDICATORS` order.
+const COUNTRIES: &[(&str, [f64; 4])] = &[
+    ("USA", [10_250.0, 3.4, 4.0, 4.1]),
+    ("CHN", [1_210.0, 0.4, 3.1, 8.5]),
+    ("JPN", [4_970.0, -0.7, 4.7, 2.8]),
+    ("DEU", [1_950.0, 1.4, 7.9, 3.0]),
+    ("GBR", [1_660.0, 0.8, 5.4, 3.5]),
+    ("IND", [470.0, 4.0, 7.3, 7.6]),
+    ("FRA", [1_360.0, 1.8, 8.5, 3.9]),
+    ("BRA", [655.0, 7.0, 9.9, 4.4]),
+    ("IDN", [180.0, 3.7, 6.1, 4.9]),
+    ("ZAF", [150.0, 5.3, 25.0, 4.2]),
+];
+
+/// Simulates from the start of the epoch year so each observation is stable
+/// across runs, keeping only dates inside `[start, end]`.
+fn series(rng: &mut StdRng, indicator: &Indicator, level: f64, start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, f64)> {
+    let first = NaiveDate::from_ymd_opt(SERIES_EPOCH.year(), 1, 1).unwrap_or(SERIES_EPOCH);
+    let years_per_step = indicator.months_between as f64 / 12.0;
+    let mut value = level;
+
+    (0..)
+        .map_while(|n| first.checked_add_months(Months::new(n * indicator.months_between)))
+        .take_while(|date| *date <= end)
+        .filter_map(|date| {
+            value = match indicator.model {
+                Model::Growth => value * ((0.04 * years_per_step) + 0.03 * years_per_step.sqrt() * standard_normal(rng)).exp(),
+                Model::Rate { floor } => (level + 0.9 * (value - level) + 0.35 * standard_normal(rng)).max(floor),
+            };
+            (date >= start).then(|| (date, (value * 100.0).round() / 100.0))
+        })
+        .collect()
+}
+
+pub async fn seed(
+    pool: &PgPool,
+    rng: &mut StdRng,
+    countries: usize,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<u64> {
+    let mut query: QueryBuilder<Postgres> =
+        QueryBuilder::new("INSERT INTO macro_indicators (code, name, unit, source) ");
+    query.push_values(INDICATORS, |mut row, indicator| {
+        row.push_bind(indicator.code)
+            .push_bind(indicator.name)
+            .push_bind(indicator.unit)
+            .push_bind("imf");
+    });
+    query.push(" ON CONFLICT (code) DO NOTHING");
+    query.build().execute(pool).await?;
+
+    let series_seed: u64 = rng.random();
+    let mut rows = Vec::new();
+    for (country, levels) in COUNTRIES.iter().take(countries) {
+        for (indicator, level) in INDICATORS.iter().zip(levels) {
+            let salt = fnv1a(&format!("{}/{}", country, indicator.code));
+            let mut series_rng = StdRng::seed_from_u64(series_seed ^ salt);
+            for (date, value) in series(&mut series_rng, indicator, *level, start, end) {
+                rows.push((indicator.code, *country, date, value));
+            }
+        }
+    }
+
+    let mut inserted = 0;
+    for chunk in rows.chunks(BATCH_SIZE) {
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO macro_observations (indicator_code, country_code, observed_on, value) ",
+        );
+        query.push_values(chunk, |mut row, (code, country, date, value)| {
+            row.push_bind(*code).push_bind(*country).push_bind(*date).push_bind(*value);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        inserted += query.build().execute(pool).await?.rows_affected();
+    }
+
+    Ok(inserted)
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Weekday};
+use fake::rand::rngs::StdRng;
+use fake::rand::{Rng, SeedableRng};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+
+use super::{days_between, standard_normal, BATCH_SIZE};
+
+/// Every series is simulated from this date so a bar for a given day is the
+/// same no matter when the seeder runs; only the requested window is stored.
+pub const SERIES_EPOCH: NaiveDate = match NaiveDate::from_ymd_opt(2000, 1, 3) {
+    Some(date) => date,
+    None => panic!("invalid series epoch"),
+};
+
+/// Chance that an otherwise open trading day has no bar (halts, feed gaps).
+const MISSING_BAR_PROBABILITY: f64 = 0.002;
+
+#[derive(Debug, Clone)]
+pub struct AssetSpec {
+    pub symbol: String,
+    pub name: String,
+    pub asset_class: &'static str,
+    pub currency: &'static str,
+    pub exchange: Option<&'static str>,
+    pub start_price: f64,
+    /// Annualised drift and volatility of the geometric Brownian motion.
+    pub drift: f64,
+    pub volatility: f64,
+    pub base_volume: f64,
+}
+
+/// symbol, name, class, exchange, price at `SERIES_EPOCH`, drift, volatility, volume
+type CatalogEntry = (&'static str, &'static str, &'static str, Option<&'static str>, f64, f64, f64, f64);
+
+const CATALOG: &[CatalogEntry] = &[
+    ("AAPL", "Apple Inc.", "equity", Some("NASDAQ"), 1.0, 0.25, 0.32, 9.0e7),
+    ("MSFT", "Microsoft Corporation", "equity", Some("NASDAQ"), 30.0, 0.12, 0.27, 3.0e7),
+    ("NVDA", "NVIDIA Corporation", "equity", Some("NASDAQ"), 0.5, 0.30, 0.50, 4.0e8),
+    ("JPM", "JPMorgan Chase & Co.", "equity", Some("NYSE"), 40.0, 0.08, 0.30, 1.0e7),
+    ("XOM", "Exxon Mobil Corporation", "equity", Some("NYSE"), 40.0, 0.05, 0.25, 1.5e7),
+    ("SPY", "SPDR S&P 500 ETF Trust", "etf", Some("NYSEARCA"), 140.0, 0.07, 0.18, 8.0e7),
+    ("GC=F", "Gold Futures", "commodity", Some("COMEX"), 280.0, 0.08, 0.16, 2.0e5),
+    ("CL=F", "Crude Oil Futures", "commodity", Some("NYMEX"), 25.0, 0.04, 0.38, 4.0e5),
+    ("EURUSD=X", "Euro / US Dollar", "forex", None, 1.0, 0.0, 0.08, 0.0),
+    ("BTC-USD", "Bitcoin", "crypto", None, 0.1, 0.60, 0.80, 3.0e10),
+    ("ETH-USD", "Ethereum", "crypto", None, 1.0, 0.40, 0.90, 1.5e10),
+];
+
+pub fn catalog() -> Vec<AssetSpec> {
+    CATALOG
+        .iter()
+        .map(
+            |&(symbol, name, asset_class, exchange, start_price, drift, volatility, base_volume)| AssetSpec {
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                asset_class,
+                currency: "USD",
+                exchange,
+                start_price,
+                drift,
+                volatility,
+                base_volume,
+            },
+        )
+        .collect()
+}
+
+fn synthetic(rng: &mut StdRng, index: usize) -> AssetSpec {
+    AssetSpec {
+        symbol: format!("SYN{:04}", index),
+        name: format!("Synthetic Asset {:04}", index),
+        asset_class: "equity",
+        currency: "USD",
+        exchange: Some("SYNTH"),
+        start_price: rng.random_range(5.0..200.0),
+        drift: rng.random_range(-0.05..0.20),
+        volatility: rng.random_range(0.15..0.70),
+        base_volume: rng.random_range(1.0e5..5.0e7),
+    }
+}
+
+/// Crypto trades every day, FX closes on weekends, New Year and Christmas,
+/// and everything else follows the NYSE weekend and holiday calendar.
+fn is_trading_day(asset_class: &str, day: NaiveDate) -> bool {
+    if asset_class == "crypto" {
+        return true;
+    }
+    if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        return false;
+    }
+    if asset_class == "forex" {
+        return !(day.month() == 1 && day.day() == 1 || day.month() == 12 && day.day() == 25);
+    }
+    !is_exchange_holiday(day)
+}
+
+fn is_exchange_holiday(day: NaiveDate) -> bool {
+    let (month, dom) = (day.month(), day.day());
+    let nth_weekday = |weekday: Weekday, n: u32| day.weekday() == weekday && (dom - 1) / 7 + 1 == n;
+    let last_weekday = |weekday: Weekday| {
+        day.weekday() == weekday && (day + chrono::Days::new(7)).month() != month
+    };
+
+    matches!((month, dom), (1, 1) | (6, 19) | (7, 4) | (12, 25))
+        || (month == 1 && nth_weekday(Weekday::Mon, 3))
+        || (month == 2 && nth_weekday(Weekday::Mon, 3))
+        || (month == 5 && last_weekday(Weekday::Mon))
+        || (month == 9 && nth_weekday(Weekday::Mon, 1))
+        || (month == 11 && nth_weekday(Weekday::Thu, 4))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bar {
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+/// Daily bars from a geometric Brownian motion. Time advances in calendar
+/// days, so weekend and holiday gaps accumulate variance and show up as
+/// opening gaps on the next session.
+pub fn simulate(spec: &AssetSpec, seed: u64, start: NaiveDate, end: NaiveDate) -> Vec<Bar> {
+    let mut rng = StdRng::seed_from_u64(seed ^ fnv1a(&spec.symbol));
+    let dt = 1.0 / 365.0;
+    let mut price = spec.start_price;
+    let mut elapsed_days = 0.0;
+    let mut bars = Vec::new();
+
+    for day in days_between(SERIES_EPOCH, end) {
+        elapsed_days += 1.0;
+        if !is_trading_day(spec.asset_class, day) {
+            continue;
+        }
+
+        let t = elapsed_days * dt;
+        elapsed_days = 0.0;
+
+        // Overnight move since the previous session, then the intraday move.
+        let overnight = ((spec.drift - 0.5 * spec.volatility.powi(2)) * t * 0.3
+            + spec.volatility * (t * 0.3).sqrt() * standard_normal(&mut rng))
+            .exp();
+        let open = price * overnight;
+        let intraday = ((spec.drift - 0.5 * spec.volatility.powi(2)) * t * 0.7
+            + spec.volatility * (t * 0.7).sqrt() * standard_normal(&mut rng))
+            .exp();
+        let close = open * intraday;
+
+        let range = spec.volatility * dt.sqrt() * 0.5;
+        let high = open.max(close) * (1.0 + range * standard_normal(&mut rng).abs());
+        let low = open.min(close) * (1.0 - range * standard_normal(&mut rng).abs()).max(0.5);
+
+        let move_size = (close / open).ln().abs() / (spec.volatility * dt.sqrt()).max(f64::EPSILON);
+        let volume = spec.base_volume
+            * (0.25 * standard_normal(&mut rng)).exp()
+            * (1.0 + 0.5 * move_size.min(4.0));
+
+        price = close;
+
+        if day < start || rng.random_bool(MISSING_BAR_PROBABILITY) {
+            continue;
+        }
+        bars.push(Bar {
+            date: day,
+            open,
+            high,
+            low,
+            close,
+            volume: volume.round() as i64,
+        });
+    }
+
+    bars
+}
+
+/// Stable per-symbol salt (FNV-1a); `DefaultHasher` is not stable across releases.
+pub(super) fn fnv1a(symbol: &str) -> u64 {
+    symbol.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+pub async fn seed(
+    pool: &PgPool,
+    rng: &mut StdRng,
+    catalog_assets: usize,
+    synthetic_assets: usize,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<(u64, u64)> {
+    let series_seed: u64 = rng.random();
+    let mut specs: Vec<AssetSpec> = catalog().into_iter().take(catalog_assets).collect();
+    specs.extend((0..synthetic_assets).map(|index| synthetic(rng, index)));
+
+    let mut assets_inserted = 0;
+    for chunk in specs.chunks(BATCH_SIZE) {
+        let mut query: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO assets (id, symbol, name, asset_class, currency, exchange) ");
+        query.push_values(chunk, |mut row, spec| {
+            row.push_bind(cuid::cuid2())
+                .push_bind(&spec.symbol)
+                .push_bind(&spec.name)
+                .push_bind(spec.asset_class)
+                .push_bind(spec.currency)
+                .push_bind(spec.exchange);
+        });
+        query.push(" ON CONFLICT (symbol) DO NOTHING");
+        assets_inserted += query.build().execute(pool).await?.rows_affected();
+    }
+
+    let symbols: Vec<&str> = specs.iter().map(|spec| spec.symbol.as_str()).collect();
+    let ids: HashMap<String, String> = sqlx::query("SELECT symbol, id FROM assets WHERE symbol = ANY($1)")
+        .bind(&symbols)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("symbol"), row.get("id")))
+        .collect();
+
+    let mut bars_inserted = 0;
+    for spec in &specs {
+        let Some(asset_id) = ids.get(&spec.symbol) else {
+            continue;
+        };
+        let bars = simulate(spec, series_seed, start, end);
+        for chunk in bars.chunks(BATCH_SIZE) {
+            let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO price_bars (asset_id, bar_date, open, high, low, close, volume) ",
+            );
+            query.push_values(chunk, |mut row, bar| {
+                row.push_bind(asset_id)
+                    .push_bind(bar.date)
+                    .push_bind(bar.open)
+                    .push_bind(bar.high)
+                    .push_bind(bar.low)
+                    .push_bind(bar.close)
+                    .push_bind(bar.volume);
+            });
+            query.push(" ON CONFLICT (asset_id, bar_date) DO NOTHING");
+            bars_inserted += query.build().execute(pool).await?.rows_affected();
+        }
+    }
+
+    Ok((assets_inserted, bars_inserted))
+}
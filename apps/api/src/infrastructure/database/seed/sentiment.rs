@@ -0,0 +1,93 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use fake::faker::lorem::en::Words;
+use fake::rand::rngs::StdRng;
+use fake::rand::seq::IndexedRandom;
+use fake::rand::Rng;
+use fake::Fake;
+use serde_json::json;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use super::market::catalog;
+use super::BATCH_SIZE;
+
+const SOURCES: &[&str] = &["twitter", "newsapi"];
+
+/// Same vocabulary as the scraper's keyword scorer (`processors/sentiment.py`).
+const POSITIVE_WORDS: &[&str] = &["bullish", "growth", "profit", "gain", "positive"];
+const NEGATIVE_WORDS: &[&str] = &["bearish", "loss", "drop", "decline", "negative"];
+
+const TEMPLATES: &[&str] = &[
+    "{symbol} looking {word} after today's session",
+    "Analysts see {word} momentum for {symbol} into next quarter",
+    "{symbol}: {word} signals as volume picks up",
+    "Is {symbol} heading for a {word} week?",
+    "{symbol} earnings call hints at {word} outlook",
+];
+
+/// Scores text the way the scraper does so seeded rows look like ingested ones.
+fn score(text: &str) -> f64 {
+    let lower = text.to_lowercase();
+    let positive = POSITIVE_WORDS.iter().filter(|w| lower.contains(*w)).count() as f64;
+    let negative = NEGATIVE_WORDS.iter().filter(|w| lower.contains(*w)).count() as f64;
+    if positive + negative == 0.0 {
+        0.0
+    } else {
+        (positive - negative) / (positive + negative)
+    }
+}
+
+pub async fn seed(pool: &PgPool, rng: &mut StdRng, count: usize, end: NaiveDate) -> Result<u64> {
+    let symbols: Vec<String> = catalog().into_iter().map(|spec| spec.symbol).collect();
+    let window_secs = 90 * 24 * 60 * 60;
+    let end = Utc.from_utc_datetime(&end.and_time(NaiveTime::MIN));
+
+    let items: Vec<_> = (0..count)
+        .map(|n| {
+            let symbol = symbols.choose(rng).map(String::as_str).unwrap_or("SPY");
+            let vocabulary = match rng.random_range(0..3) {
+                0 => POSITIVE_WORDS,
+                1 => NEGATIVE_WORDS,
+                _ => &["steady", "mixed", "flat"][..],
+            };
+            let word = vocabulary.choose(rng).copied().unwrap_or("mixed");
+            let template = TEMPLATES.choose(rng).copied().unwrap_or(TEMPLATES[0]);
+            let filler: Vec<String> = Words(0..6).fake_with_rng(rng);
+            let text = format!(
+                "{} {}",
+                template.replace("{symbol}", symbol).replace("{word}", word),
+                filler.join(" ")
+            );
+            let source = SOURCES.choose(rng).copied().unwrap_or(SOURCES[0]);
+            let metrics = json!({
+                "like_count": rng.random_range(0..5_000),
+                "retweet_count": rng.random_range(0..1_000),
+                "reply_count": rng.random_range(0..300),
+            });
+            let published_at = end - Duration::seconds(rng.random_range(0..window_secs));
+
+            (format!("seed-{}", n), source, symbol.to_string(), score(&text), text, metrics, published_at)
+        })
+        .collect();
+
+    let mut inserted = 0;
+    for chunk in items.chunks(BATCH_SIZE) {
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO sentiment_items (id, source, external_id, query, text, score, metrics, published_at) ",
+        );
+        query.push_values(chunk, |mut row, (external_id, source, symbol, score, text, metrics, published_at)| {
+            row.push_bind(cuid::cuid2())
+                .push_bind(*source)
+                .push_bind(external_id)
+                .push_bind(symbol)
+                .push_bind(text)
+                .push_bind(*score)
+                .push_bind(metrics)
+                .push_bind(*published_at);
+        });
+        query.push(" ON CONFLICT (source, external_id) DO NOTHING");
+        inserted += query.build().execute(pool).await?.rows_affected();
+    }
+
+    Ok(inserted)
+}
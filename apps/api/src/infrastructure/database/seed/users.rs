@@ -0,0 +1,64 @@
+use anyhow::{anyhow, Result};
+use fake::faker::name::en::{FirstName, LastName};
+use fake::rand::rngs::StdRng;
+use fake::Fake;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use super::BATCH_SIZE;
+use crate::common::security::hash_password;
+use crate::features::users::infrastructure::repositories::user_repository::EMAIL_COLUMN;
+use crate::infrastructure::security::encryption::Encryptor;
+
+/// Password shared by every seeded account.
+pub const SEED_PASSWORD: &str = "Seed-Password-1";
+
+pub const ADMIN_EMAIL: &str = "admin@m5.local";
+
+struct SeedUser {
+    email: String,
+    name: String,
+}
+
+pub async fn seed(pool: &PgPool, encryptor: &Encryptor, rng: &mut StdRng, count: usize) -> Result<u64> {
+    // One argon2 hash reused for all rows: hashing per user would dominate
+    // the load-test profile and the accounts share a password anyway.
+    let password_hash = hash_password(SEED_PASSWORD).map_err(|e| anyhow!("hashing seed password: {}", e))?;
+
+    let mut users = vec![SeedUser {
+        email: ADMIN_EMAIL.to_string(),
+        name: "Admin".to_string(),
+    }];
+    users.extend((0..count).map(|n| {
+        let first: String = FirstName().fake_with_rng(rng);
+        let last: String = LastName().fake_with_rng(rng);
+        SeedUser {
+            email: format!("{}.{}.{}@example.com", first, last, n).to_lowercase().replace(' ', ""),
+            name: format!("{} {}", first, last),
+        }
+    }));
+
+    let mut inserted = 0;
+    for chunk in users.chunks(BATCH_SIZE) {
+        let emails = chunk
+            .iter()
+            .map(|user| {
+                let encrypted = encryptor.encrypt(EMAIL_COLUMN.context, &user.email)?;
+                Ok((encrypted, EMAIL_COLUMN.blind_index(encryptor, &user.email)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut query: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO users (id, email_encrypted, email_bidx, name, password_hash) ");
+        // The blind index is deterministic, so a re-run conflicts on it.
+        query.push_values(chunk.iter().zip(emails), |mut row, (user, (email, email_index))| {
+            row.push_bind(cuid::cuid2())
+                .push_bind(email)
+                .push_bind(email_index)
+                .push_bind(&user.name)
+                .push_bind(&password_hash);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        inserted += query.build().execute(pool).await?.rows_affected();
+    }
+
+    Ok(inserted)
+}
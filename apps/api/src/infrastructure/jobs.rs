@@ -0,0 +1,204 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::types::JsonValue;
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How long an idle worker waits before polling the queue again.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Background work persisted in Postgres. Workers claim pending jobs with
+/// `SKIP LOCKED`; a job that exhausts `max_attempts` stays `failed` until an
+/// operator retries it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    pub payload: JsonValue,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    pub run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const JOB_COLUMNS: &str =
+    "id, kind, payload, status, attempts, max_attempts, last_error, run_at, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct JobQueue {
+    pool: PgPool,
+}
+
+impl JobQueue {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn enqueue(&self, kind: &str, payload: JsonValue) -> Result<Job> {
+        Ok(sqlx::query_as(&format!(
+            "INSERT INTO jobs (id, kind, payload) VALUES ($1, $2, $3) RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(cuid::cuid2())
+        .bind(kind)
+        .bind(payload)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Marks the oldest runnable job of one of `kinds` as running and returns it.
+    pub async fn claim(&self, kinds: &[&str]) -> Result<Option<Job>> {
+        Ok(sqlx::query_as(&format!(
+            "UPDATE jobs SET status = 'running', attempts = attempts + 1, updated_at = now()
+             WHERE id = (
+                 SELECT id FROM jobs
+                 WHERE status = 'pending' AND run_at <= now() AND kind = ANY($1)
+                 ORDER BY run_at
+                 LIMIT 1
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(kinds)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn complete(&self, id: &str) -> Result<()> {
+        sqlx::query("UPDATE jobs SET status = 'succeeded', last_error = NULL, updated_at = now() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Records a failed attempt, rescheduling with exponential backoff until
+    /// the job runs out of attempts.
+    pub async fn fail(&self, id: &str, error: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE jobs SET
+                 status = CASE WHEN attempts >= max_attempts THEN 'failed' ELSE 'pending' END,
+                 run_at = now() + make_interval(secs => power(2, attempts)),
+                 last_error = $2,
+                 updated_at = now()
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Job>> {
+        Ok(sqlx::query_as(&format!("SELECT {} FROM jobs WHERE id = $1", JOB_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?)
+    }
+
+    pub async fn failed(&self, kind: Option<&str>, limit: i64) -> Result<Vec<Job>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM jobs
+             WHERE status = 'failed' AND ($1::TEXT IS NULL OR kind = $1)
+             ORDER BY updated_at DESC
+             LIMIT $2",
+            JOB_COLUMNS
+        ))
+        .bind(kind)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Puts failed jobs back in the queue with a fresh attempt budget. With no
+    /// `id`, retries every failed job (of `kind`, if given).
+    pub async fn retry(&self, id: Option<&str>, kind: Option<&str>) -> Result<Vec<Job>> {
+        Ok(sqlx::query_as(&format!(
+            "UPDATE jobs SET status = 'pending', attempts = 0, run_at = now(), updated_at = now()
+             WHERE status = 'failed'
+               AND ($1::TEXT IS NULL OR id = $1)
+               AND ($2::TEXT IS NULL OR kind = $2)
+             RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
+
+/// Runs one kind of job. An error fails the attempt, and the job is retried
+/// with backoff; handlers must therefore tolerate running more than once.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn run(&self, payload: &JsonValue) -> Result<()>;
+}
+
+/// Claims and runs jobs of the kinds it has handlers for, one at a time.
+/// Kinds nobody registered stay pending, so an instance never claims work
+/// it cannot do.
+pub struct JobRunner {
+    queue: JobQueue,
+    handlers: HashMap<&'static str, Arc<dyn JobHandler>>,
+}
+
+impl JobRunner {
+    pub fn new(queue: JobQueue) -> Self {
+        Self {
+            queue,
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn register(mut self, kind: &'static str, handler: impl JobHandler + 'static) -> Self {
+        self.handlers.insert(kind, Arc::new(handler));
+        self
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if self.handlers.is_empty() {
+                return;
+            }
+            let kinds: Vec<&str> = self.handlers.keys().copied().collect();
+            tracing::info!(kinds = ?kinds, "Job runner started");
+            loop {
+                match self.queue.claim(&kinds).await {
+                    Ok(Some(job)) => self.run(job).await,
+                    Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                    Err(e) => {
+                        tracing::warn!("Failed to claim a job: {}", e);
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                }
+            }
+        })
+    }
+
+    async fn run(&self, job: Job) {
+        let Some(handler) = self.handlers.get(job.kind.as_str()) else {
+            return;
+        };
+        let recorded = match handler.run(&job.payload).await {
+            Ok(()) => self.queue.complete(&job.id).await,
+            Err(e) => {
+                tracing::warn!(job_id = %job.id, kind = %job.kind, attempt = job.attempts, "Job failed: {:#}", e);
+                self.queue.fail(&job.id, &format!("{:#}", e)).await
+            }
+        };
+        if let Err(e) = recorded {
+            tracing::error!(job_id = %job.id, "Failed to record job outcome: {}", e);
+        }
+    }
+}
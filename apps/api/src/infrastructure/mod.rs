@@ -0,0 +1,4 @@
+pub mod database;
+pub mod jobs;
+pub mod security;
+pub mod services;
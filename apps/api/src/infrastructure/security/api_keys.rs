@@ -0,0 +1,287 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{FromRow, PgPool};
+
+use crate::common::security::generate_random_token;
+use crate::config::Secret;
+use crate::infrastructure::database::connection::DatabasePool;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Keys look like `m5_{prefix}_{secret}`. The prefix is stored in clear so a
+/// key can be identified in logs and listings; only a digest of the secret
+/// is kept.
+pub const KEY_NAMESPACE: &str = "m5";
+const PREFIX_LENGTH: usize = 8;
+const SECRET_LENGTH: usize = 32;
+
+/// How long a rotated-out key keeps working unless told otherwise.
+pub const DEFAULT_ROTATION_OVERLAP: Duration = Duration::hours(24);
+/// The longest a rotated-out key may keep working.
+pub const MAX_ROTATION_OVERLAP: Duration = Duration::days(30);
+
+/// `last_used_at` is only rewritten once per interval so a busy client does
+/// not turn every request into a write.
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// Marks a stored `secret_hash` as a [`SecretDigest`].
+const DIGEST_SCHEME: &str = "hmac-sha256$";
+
+/// HMAC-SHA256 of a key secret under the server's pepper. The secrets are
+/// random and long, so a fast keyed digest is as safe as a password hash
+/// while costing microseconds per request instead of an argon2 run.
+#[derive(Clone)]
+struct SecretDigest {
+    pepper: Secret<String>,
+}
+
+impl SecretDigest {
+    fn mac(&self, secret: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.pepper.expose().as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(secret.as_bytes());
+        mac
+    }
+
+    fn digest(&self, secret: &str) -> String {
+        format!("{}{}", DIGEST_SCHEME, hex::encode(self.mac(secret).finalize().into_bytes()))
+    }
+
+    /// Compares in constant time.
+    fn matches(&self, secret: &str, digest: &str) -> bool {
+        hex::decode(digest).is_ok_and(|expected| self.mac(secret).verify_slice(&expected).is_ok())
+    }
+}
+
+/// Splits `m5_{prefix}_{secret}`; `None` for anything else.
+pub fn parse_token(token: &str) -> Option<(&str, &str)> {
+    let rest = token.strip_prefix(KEY_NAMESPACE)?.strip_prefix('_')?;
+    let (prefix, secret) = rest.split_once('_')?;
+    (prefix.len() == PREFIX_LENGTH && secret.len() == SECRET_LENGTH).then_some((prefix, secret))
+}
+
+/// Cheap check used to route a bearer token to API key or JWT validation.
+pub fn looks_like_api_key(token: &str) -> bool {
+    token.starts_with(KEY_NAMESPACE) && token[KEY_NAMESPACE.len()..].starts_with('_')
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// The key this one was rotated from.
+    pub replaces: Option<String>,
+}
+
+impl ApiKey {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|at| at > Utc::now())
+    }
+}
+
+/// A freshly issued key. `token` is the only copy of the full key and cannot
+/// be recovered later.
+#[derive(Debug, Serialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub token: Secret<String>,
+}
+
+const API_KEY_COLUMNS: &str =
+    "id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at, replaces";
+
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    pool: DatabasePool,
+    digest: SecretDigest,
+}
+
+impl ApiKeyStore {
+    /// `pepper` is `auth.api_key_pepper`.
+    pub fn new(pool: DatabasePool, pepper: Secret<String>) -> Self {
+        Self {
+            pool,
+            digest: SecretDigest { pepper },
+        }
+    }
+
+    pub async fn issue(
+        &self,
+        name: &str,
+        scopes: &[String],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<IssuedApiKey> {
+        if let Some(unknown) = self.unknown_scopes(scopes).await?.first() {
+            bail!("unknown scope `{}`", unknown);
+        }
+        let mut tx = self.pool.write().begin().await?;
+        let issued = insert(&mut tx, &self.digest, name, scopes, expires_at, None).await?;
+        tx.commit().await?;
+        Ok(issued)
+    }
+
+    /// Scopes that do not name a permission.
+    pub async fn unknown_scopes(&self, scopes: &[String]) -> Result<Vec<String>> {
+        let known: Vec<String> = sqlx::query_scalar("SELECT name FROM permissions WHERE name = ANY($1)")
+            .bind(scopes)
+            .fetch_all(self.pool.read())
+            .await?;
+        Ok(scopes.iter().filter(|scope| !known.contains(scope)).cloned().collect())
+    }
+
+    /// Issues a replacement with the same name, scopes and expiry, and cuts
+    /// the old key's lifetime down to `overlap` so both work while clients
+    /// switch over. Returns `None` when no active key matches.
+    pub async fn rotate(&self, id_or_prefix: &str, overlap: Duration) -> Result<Option<IssuedApiKey>> {
+        if overlap < Duration::zero() || overlap > MAX_ROTATION_OVERLAP {
+            bail!("rotation overlap must be between 0 and {} days", MAX_ROTATION_OVERLAP.num_days());
+        }
+        let mut tx = self.pool.write().begin().await?;
+        let current: Option<ApiKey> = sqlx::query_as(&format!(
+            "SELECT {} FROM api_keys
+             WHERE (id = $1 OR prefix = $1)
+               AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > now())
+             FOR UPDATE",
+            API_KEY_COLUMNS
+        ))
+        .bind(id_or_prefix)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(current) = current else {
+            return Ok(None);
+        };
+
+        let Some(cutoff) = Utc::now().checked_add_signed(overlap) else {
+            bail!("rotation overlap is out of range");
+        };
+        sqlx::query("UPDATE api_keys SET expires_at = $2 WHERE id = $1")
+            .bind(&current.id)
+            .bind(current.expires_at.map_or(cutoff, |at| at.min(cutoff)))
+            .execute(&mut *tx)
+            .await?;
+        let issued = insert(&mut tx, &self.digest, &current.name, &current.scopes, current.expires_at, Some(&current.id)).await?;
+        tx.commit().await?;
+        Ok(Some(issued))
+    }
+
+    /// Resolves a presented token to its key. `Ok(None)` when the token is
+    /// malformed, unknown or its secret does not match; inactive keys are
+    /// returned so the caller can say why they were refused. Reads the
+    /// primary, so a revocation takes effect without waiting on replicas.
+    pub async fn authenticate(&self, token: &str) -> Result<Option<ApiKey>> {
+        let Some((prefix, secret)) = parse_token(token) else {
+            return Ok(None);
+        };
+        let row: Option<(String, String)> =
+            sqlx::query_as("SELECT id, secret_hash FROM api_keys WHERE prefix = $1")
+                .bind(prefix)
+                .fetch_optional(self.pool.primary())
+                .await?;
+        let Some((id, secret_hash)) = row else {
+            return Ok(None);
+        };
+
+        let valid = secret_hash
+            .strip_prefix(DIGEST_SCHEME)
+            .is_some_and(|digest| self.digest.matches(secret, digest));
+        if !valid {
+            return Ok(None);
+        }
+
+        let touched: Option<ApiKey> = sqlx::query_as(&format!(
+            "UPDATE api_keys SET last_used_at = now()
+             WHERE id = $1
+               AND (last_used_at IS NULL OR last_used_at < now() - make_interval(secs => $2))
+             RETURNING {}",
+            API_KEY_COLUMNS
+        ))
+        .bind(&id)
+        .bind(LAST_USED_RESOLUTION_SECS as f64)
+        .fetch_optional(self.pool.write())
+        .await?;
+        match touched {
+            Some(key) => Ok(Some(key)),
+            None => find(self.pool.primary(), &id).await,
+        }
+    }
+
+    pub async fn get(&self, id_or_prefix: &str) -> Result<Option<ApiKey>> {
+        find(self.pool.read(), id_or_prefix).await
+    }
+
+    pub async fn list(&self, include_inactive: bool) -> Result<Vec<ApiKey>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM api_keys
+             WHERE $1 OR (revoked_at IS NULL AND (expires_at IS NULL OR expires_at > now()))
+             ORDER BY created_at DESC",
+            API_KEY_COLUMNS
+        ))
+        .bind(include_inactive)
+        .fetch_all(self.pool.read())
+        .await?)
+    }
+
+    /// Revokes by id or prefix. Returns `None` when no unrevoked key matches.
+    pub async fn revoke(&self, id_or_prefix: &str) -> Result<Option<ApiKey>> {
+        Ok(sqlx::query_as(&format!(
+            "UPDATE api_keys SET revoked_at = now()
+             WHERE (id = $1 OR prefix = $1) AND revoked_at IS NULL
+             RETURNING {}",
+            API_KEY_COLUMNS
+        ))
+        .bind(id_or_prefix)
+        .fetch_optional(self.pool.write())
+        .await?)
+    }
+}
+
+async fn find(pool: &PgPool, id_or_prefix: &str) -> Result<Option<ApiKey>> {
+    Ok(sqlx::query_as(&format!(
+        "SELECT {} FROM api_keys WHERE id = $1 OR prefix = $1",
+        API_KEY_COLUMNS
+    ))
+    .bind(id_or_prefix)
+    .fetch_optional(pool)
+    .await?)
+}
+
+async fn insert(
+    tx: &mut sqlx::PgConnection,
+    digest: &SecretDigest,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+    replaces: Option<&str>,
+) -> Result<IssuedApiKey> {
+    let prefix = generate_random_token(PREFIX_LENGTH);
+    let secret = Secret::new(generate_random_token(SECRET_LENGTH));
+    let secret_hash = digest.digest(secret.expose());
+
+    let key: ApiKey = sqlx::query_as(&format!(
+        "INSERT INTO api_keys (id, name, prefix, secret_hash, scopes, expires_at, replaces)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING {}",
+        API_KEY_COLUMNS
+    ))
+    .bind(cuid::cuid2())
+    .bind(name)
+    .bind(&prefix)
+    .bind(secret_hash)
+    .bind(scopes)
+    .bind(expires_at)
+    .bind(replaces)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let token = Secret::new(format!("{}_{}_{}", KEY_NAMESPACE, prefix, secret.expose()));
+    Ok(IssuedApiKey { key, token })
+}
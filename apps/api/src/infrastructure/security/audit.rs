@@ -0,0 +1,914 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, SecondsFormat, SubsecRound, Utc};
+use futures::TryStreamExt;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use sqlx::types::JsonValue;
+use sqlx::FromRow;
+use std::collections::hash_map::{Entry, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::api_keys::ApiKey;
+use super::network::NetworkPolicy;
+use crate::common::security::sha256_hex;
+use crate::infrastructure::database::connection::DatabasePool;
+
+const AUDIT_COLUMNS: &str =
+    "id, day, seq, occurred_at, recorded_at, event, actor_type, actor_id, subject, ip_address, details, prev_hash, hash";
+
+/// `prev_hash` of the first entry of every day.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serializes appends across instances so each day's chain stays linear.
+/// "m5audit" in ASCII.
+const CHAIN_LOCK_KEY: i64 = 0x006d_3561_7564_6974;
+
+/// Entries waiting for the writer. When full, recording waits for room
+/// rather than dropping entries.
+const BUFFER_CAPACITY: usize = 4096;
+const BATCH_SIZE: usize = 256;
+const RETRY_DELAYS: [Duration; 3] = [Duration::from_secs(1), Duration::from_secs(5), Duration::from_secs(30)];
+
+pub const DEFAULT_SEARCH_LIMIT: i64 = 100;
+pub const MAX_SEARCH_LIMIT: i64 = 1000;
+
+/// Who performed an audited action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Actor {
+    User(String),
+    ApiKey(String),
+    /// An operator running the `cli` binary, by OS account when known.
+    Cli(Option<String>),
+    /// The service itself, e.g. reloading its configuration.
+    System,
+    /// An unauthenticated caller, e.g. someone attempting a login.
+    Anonymous,
+}
+
+impl Actor {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Actor::User(_) => "user",
+            Actor::ApiKey(_) => "api_key",
+            Actor::Cli(_) => "cli",
+            Actor::System => "system",
+            Actor::Anonymous => "anonymous",
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Actor::User(id) | Actor::ApiKey(id) => Some(id),
+            Actor::Cli(name) => name.as_deref(),
+            Actor::System | Actor::Anonymous => None,
+        }
+    }
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id() {
+            Some(id) => write!(f, "{}:{}", self.kind(), id),
+            None => f.write_str(self.kind()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMethod {
+    Password,
+    Oidc,
+    /// A challenge that followed a password or OIDC login was answered.
+    SecondFactor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginFailure {
+    InvalidCredentials,
+    AccountDisabled,
+    InvalidSecondFactor,
+}
+
+/// A security-relevant action. The variant picks the event name; its fields
+/// are stored as the entry's `details`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AuditEvent {
+    LoginSucceeded {
+        user_id: String,
+        session_id: String,
+        method: LoginMethod,
+    },
+    LoginFailed {
+        /// The email as submitted, normalized when possible.
+        account: Option<String>,
+        /// Set when the account exists.
+        user_id: Option<String>,
+        reason: LoginFailure,
+    },
+    LoginBlocked {
+        /// `account` or `ip`.
+        scope: String,
+        key: String,
+        retry_after_secs: u64,
+    },
+    AccountLocked {
+        account: String,
+        user_id: Option<String>,
+        failures: u32,
+        locked_until: Option<DateTime<Utc>>,
+    },
+    IpLocked {
+        ip_address: String,
+        failures: u32,
+        locked_until: Option<DateTime<Utc>>,
+    },
+    AccountUnlocked {
+        user_id: String,
+        /// Whether there were failed logins to clear.
+        cleared: bool,
+    },
+    IpUnlocked {
+        ip_address: String,
+        cleared: bool,
+    },
+    RoleGranted {
+        user_id: String,
+        role: String,
+    },
+    RoleRevoked {
+        user_id: String,
+        role: String,
+    },
+    ApiKeyIssued {
+        key_id: String,
+        name: String,
+        prefix: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+        /// The key this one was rotated from.
+        replaces: Option<String>,
+    },
+    ApiKeyRevoked {
+        key_id: String,
+        name: String,
+        prefix: String,
+    },
+    ConfigReloaded {
+        trigger: String,
+        changed: Vec<String>,
+    },
+    ConfigReloadRejected {
+        trigger: String,
+        error: String,
+    },
+    NetworkAccessDenied {
+        policy: String,
+        route_group: String,
+        method: String,
+        path: String,
+    },
+    NetworkPolicySaved {
+        name: String,
+        allow: Vec<IpNet>,
+        deny: Vec<IpNet>,
+        route_groups: Vec<String>,
+        trust_forwarded_for: bool,
+        enabled: bool,
+    },
+    NetworkPolicyDeleted {
+        name: String,
+    },
+    DataReencrypted {
+        /// `table.column`.
+        column: String,
+        master_key_id: String,
+        rewrapped: u64,
+        encrypted: u64,
+    },
+    ImpersonationStarted {
+        user_id: String,
+        reason: Option<String>,
+    },
+    DataExported {
+        dataset: String,
+        /// The filters the export was made with.
+        filters: JsonValue,
+        rows: u64,
+    },
+}
+
+impl AuditEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditEvent::LoginSucceeded { .. } => "auth.login_succeeded",
+            AuditEvent::LoginFailed { .. } => "auth.login_failed",
+            AuditEvent::LoginBlocked { .. } => "auth.login_blocked",
+            AuditEvent::AccountLocked { .. } => "auth.account_locked",
+            AuditEvent::IpLocked { .. } => "auth.ip_locked",
+            AuditEvent::AccountUnlocked { .. } => "auth.account_unlocked",
+            AuditEvent::IpUnlocked { .. } => "auth.ip_unlocked",
+            AuditEvent::RoleGranted { .. } => "rbac.role_granted",
+            AuditEvent::RoleRevoked { .. } => "rbac.role_revoked",
+            AuditEvent::ApiKeyIssued { .. } => "api_key.issued",
+            AuditEvent::ApiKeyRevoked { .. } => "api_key.revoked",
+            AuditEvent::ConfigReloaded { .. } => "config.reloaded",
+            AuditEvent::ConfigReloadRejected { .. } => "config.reload_rejected",
+            AuditEvent::NetworkAccessDenied { .. } => "network.access_denied",
+            AuditEvent::NetworkPolicySaved { .. } => "network.policy_saved",
+            AuditEvent::NetworkPolicyDeleted { .. } => "network.policy_deleted",
+            AuditEvent::DataReencrypted { .. } => "encryption.reencrypted",
+            AuditEvent::ImpersonationStarted { .. } => "admin.impersonation_started",
+            AuditEvent::DataExported { .. } => "data.exported",
+        }
+    }
+
+    /// The user, key or address the action was about, for filtering.
+    pub fn subject(&self) -> Option<&str> {
+        match self {
+            AuditEvent::LoginSucceeded { user_id, .. }
+            | AuditEvent::AccountUnlocked { user_id, .. }
+            | AuditEvent::RoleGranted { user_id, .. }
+            | AuditEvent::RoleRevoked { user_id, .. }
+            | AuditEvent::ImpersonationStarted { user_id, .. } => Some(user_id),
+            AuditEvent::LoginFailed { account, user_id, .. } => user_id.as_deref().or(account.as_deref()),
+            AuditEvent::AccountLocked { account, user_id, .. } => Some(user_id.as_deref().unwrap_or(account)),
+            AuditEvent::LoginBlocked { key, .. } => Some(key),
+            AuditEvent::IpLocked { ip_address, .. } | AuditEvent::IpUnlocked { ip_address, .. } => Some(ip_address),
+            AuditEvent::ApiKeyIssued { key_id, .. } | AuditEvent::ApiKeyRevoked { key_id, .. } => Some(key_id),
+            AuditEvent::DataExported { dataset, .. } => Some(dataset),
+            AuditEvent::DataReencrypted { column, .. } => Some(column),
+            AuditEvent::NetworkAccessDenied { policy: name, .. }
+            | AuditEvent::NetworkPolicySaved { name, .. }
+            | AuditEvent::NetworkPolicyDeleted { name } => Some(name),
+            AuditEvent::ConfigReloaded { .. } | AuditEvent::ConfigReloadRejected { .. } => None,
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            AuditEvent::LoginSucceeded { .. } => "User logged in",
+            AuditEvent::LoginFailed { .. } => "Failed login",
+            AuditEvent::LoginBlocked { .. } => "Login attempt rejected while throttled",
+            AuditEvent::AccountLocked { .. } => "Account locked after repeated failed logins",
+            AuditEvent::IpLocked { .. } => "Source IP locked after repeated failed logins",
+            AuditEvent::AccountUnlocked { .. } => "Account login throttle cleared",
+            AuditEvent::IpUnlocked { .. } => "Source IP login throttle cleared",
+            AuditEvent::RoleGranted { .. } => "Role granted",
+            AuditEvent::RoleRevoked { .. } => "Role revoked",
+            AuditEvent::ApiKeyIssued { .. } => "API key issued",
+            AuditEvent::ApiKeyRevoked { .. } => "API key revoked",
+            AuditEvent::ConfigReloaded { .. } => "Runtime configuration reloaded",
+            AuditEvent::ConfigReloadRejected { .. } => "Configuration reload rejected; keeping previous configuration",
+            AuditEvent::NetworkAccessDenied { .. } => "Request denied by network policy",
+            AuditEvent::NetworkPolicySaved { .. } => "Network policy saved",
+            AuditEvent::NetworkPolicyDeleted { .. } => "Network policy deleted",
+            AuditEvent::DataReencrypted { .. } => "Encrypted column moved to the current master key",
+            AuditEvent::ImpersonationStarted { .. } => "Administrator started impersonating a user",
+            AuditEvent::DataExported { .. } => "Data exported",
+        }
+    }
+
+    /// Worth a warning in the log, not just a record.
+    fn is_alert(&self) -> bool {
+        matches!(
+            self,
+            AuditEvent::LoginBlocked { .. }
+                | AuditEvent::AccountLocked { .. }
+                | AuditEvent::IpLocked { .. }
+                | AuditEvent::AccountUnlocked { .. }
+                | AuditEvent::IpUnlocked { .. }
+                | AuditEvent::ConfigReloadRejected { .. }
+                | AuditEvent::NetworkAccessDenied { .. }
+                | AuditEvent::ImpersonationStarted { .. }
+        )
+    }
+
+    pub fn api_key_issued(key: &ApiKey) -> Self {
+        AuditEvent::ApiKeyIssued {
+            key_id: key.id.clone(),
+            name: key.name.clone(),
+            prefix: key.prefix.clone(),
+            scopes: key.scopes.clone(),
+            expires_at: key.expires_at,
+            replaces: key.replaces.clone(),
+        }
+    }
+
+    pub fn api_key_revoked(key: &ApiKey) -> Self {
+        AuditEvent::ApiKeyRevoked {
+            key_id: key.id.clone(),
+            name: key.name.clone(),
+            prefix: key.prefix.clone(),
+        }
+    }
+
+    pub fn network_policy_saved(policy: &NetworkPolicy) -> Self {
+        AuditEvent::NetworkPolicySaved {
+            name: policy.name.clone(),
+            allow: policy.spec.allow.clone(),
+            deny: policy.spec.deny.clone(),
+            route_groups: policy.spec.route_groups.clone(),
+            trust_forwarded_for: policy.spec.trust_forwarded_for,
+            enabled: policy.spec.enabled,
+        }
+    }
+
+    pub fn by(self, actor: Actor) -> AuditEntry {
+        AuditEntry {
+            event: self,
+            actor,
+            ip_address: None,
+            occurred_at: Utc::now(),
+        }
+    }
+}
+
+/// An event with who did it, from where and when.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub event: AuditEvent,
+    pub actor: Actor,
+    pub ip_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    pub fn from_ip(mut self, ip_address: Option<String>) -> Self {
+        self.ip_address = ip_address;
+        self
+    }
+
+    fn details(&self) -> JsonValue {
+        let mut details = serde_json::to_value(&self.event).unwrap_or_default();
+        scrub_json(&mut details);
+        details
+    }
+
+    /// Mirrors the entry to the `m5::audit` log target.
+    fn trace(&self) {
+        let details = self.details();
+        let subject = self.event.subject().unwrap_or("-");
+        let ip_address = self.ip_address.as_deref().unwrap_or("-");
+        if self.event.is_alert() {
+            tracing::warn!(
+                target: "m5::audit",
+                event = self.event.name(),
+                actor = %self.actor,
+                subject,
+                ip_address,
+                details = %details,
+                "{}",
+                self.event.message()
+            );
+        } else {
+            tracing::info!(
+                target: "m5::audit",
+                event = self.event.name(),
+                actor = %self.actor,
+                subject,
+                ip_address,
+                details = %details,
+                "{}",
+                self.event.message()
+            );
+        }
+    }
+}
+
+/// A stored entry.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub day: NaiveDate,
+    /// Position in the day's chain, from 1.
+    pub seq: i32,
+    pub occurred_at: DateTime<Utc>,
+    pub recorded_at: DateTime<Utc>,
+    pub event: String,
+    pub actor_type: String,
+    pub actor_id: Option<String>,
+    pub subject: Option<String>,
+    pub ip_address: Option<String>,
+    pub details: JsonValue,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// The fields a chain hash covers, in order.
+struct ChainLink<'a> {
+    day: NaiveDate,
+    seq: i32,
+    occurred_at: DateTime<Utc>,
+    event: &'a str,
+    actor_type: &'a str,
+    actor_id: Option<&'a str>,
+    subject: Option<&'a str>,
+    ip_address: Option<&'a str>,
+    details: &'a JsonValue,
+}
+
+impl ChainLink<'_> {
+    /// SHA-256 over a JSON array of `prev_hash` and the fields. Object keys
+    /// serialize sorted, so `details` hashes the same after a round trip
+    /// through JSONB.
+    fn hash(&self, prev_hash: &str) -> String {
+        let canonical = serde_json::json!([
+            prev_hash,
+            self.day.to_string(),
+            self.seq,
+            self.occurred_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.event,
+            self.actor_type,
+            self.actor_id,
+            self.subject,
+            self.ip_address,
+            self.details,
+        ]);
+        sha256_hex(&canonical.to_string())
+    }
+}
+
+impl<'a> From<&'a AuditRecord> for ChainLink<'a> {
+    fn from(record: &'a AuditRecord) -> Self {
+        Self {
+            day: record.day,
+            seq: record.seq,
+            occurred_at: record.occurred_at,
+            event: &record.event,
+            actor_type: &record.actor_type,
+            actor_id: record.actor_id.as_deref(),
+            subject: record.subject.as_deref(),
+            ip_address: record.ip_address.as_deref(),
+            details: &record.details,
+        }
+    }
+}
+
+/// Postgres rejects NUL in text and JSONB, and these values can come from
+/// request input.
+fn scrub(value: &str) -> String {
+    value.replace('\0', "\u{fffd}")
+}
+
+fn scrub_json(value: &mut JsonValue) {
+    match value {
+        JsonValue::String(s) if s.contains('\0') => *s = scrub(s),
+        JsonValue::Array(items) => items.iter_mut().for_each(scrub_json),
+        JsonValue::Object(map) => {
+            if map.keys().any(|key| key.contains('\0')) {
+                *map = std::mem::take(map).into_iter().map(|(key, value)| (scrub(&key), value)).collect();
+            }
+            map.values_mut().for_each(scrub_json);
+        }
+        _ => {}
+    }
+}
+
+/// Filters for [`AuditStore::search`]. All are optional and combine with AND.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    /// An event name, or a prefix ending in `.*` such as `auth.*`.
+    pub event: Option<String>,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<String>,
+    pub subject: Option<String>,
+    pub ip_address: Option<String>,
+    /// Inclusive.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive.
+    pub to: Option<DateTime<Utc>>,
+    /// Only entries older than this id; pass the last id of a page to get
+    /// the next one.
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Where a day's chain first fails to verify.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainBreak {
+    pub id: i64,
+    pub seq: i32,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub day: NaiveDate,
+    pub entries: u64,
+    pub broken: Option<ChainBreak>,
+}
+
+/// The append-only `audit_log` table. Appends extend the chain of each
+/// entry's UTC day under an advisory lock, so any number of instances can
+/// write.
+#[derive(Clone)]
+pub struct AuditStore {
+    pool: DatabasePool,
+}
+
+impl AuditStore {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// Writes entries immediately, for callers without an [`AuditLog`].
+    pub async fn record(&self, entry: AuditEntry) -> Result<()> {
+        self.append(std::slice::from_ref(&entry)).await
+    }
+
+    pub async fn append(&self, entries: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.write().begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(CHAIN_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut heads: HashMap<NaiveDate, (i32, String)> = HashMap::new();
+        for entry in entries {
+            // Postgres keeps microseconds; hash what will be read back.
+            let occurred_at = entry.occurred_at.trunc_subsecs(6);
+            let day = occurred_at.date_naive();
+            let (last_seq, prev_hash) = match heads.entry(day) {
+                Entry::Occupied(head) => head.into_mut(),
+                Entry::Vacant(slot) => {
+                    let head: Option<(i32, String)> =
+                        sqlx::query_as("SELECT seq, hash FROM audit_log WHERE day = $1 ORDER BY seq DESC LIMIT 1")
+                            .bind(day)
+                            .fetch_optional(&mut *tx)
+                            .await?;
+                    slot.insert(head.unwrap_or_else(|| (0, GENESIS_HASH.to_string())))
+                }
+            };
+
+            let actor_id = entry.actor.id().map(scrub);
+            let subject = entry.event.subject().map(scrub);
+            let ip_address = entry.ip_address.as_deref().map(scrub);
+            let details = entry.details();
+            let link = ChainLink {
+                day,
+                seq: *last_seq + 1,
+                occurred_at,
+                event: entry.event.name(),
+                actor_type: entry.actor.kind(),
+                actor_id: actor_id.as_deref(),
+                subject: subject.as_deref(),
+                ip_address: ip_address.as_deref(),
+                details: &details,
+            };
+            let hash = link.hash(prev_hash);
+
+            sqlx::query(
+                "INSERT INTO audit_log
+                     (day, seq, occurred_at, event, actor_type, actor_id, subject, ip_address, details, prev_hash, hash)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            )
+            .bind(link.day)
+            .bind(link.seq)
+            .bind(link.occurred_at)
+            .bind(link.event)
+            .bind(link.actor_type)
+            .bind(link.actor_id)
+            .bind(link.subject)
+            .bind(link.ip_address)
+            .bind(link.details)
+            .bind(prev_hash.as_str())
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await?;
+
+            *last_seq = link.seq;
+            *prev_hash = hash;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Newest first.
+    pub async fn search(&self, filter: &AuditFilter) -> Result<Vec<AuditRecord>> {
+        let limit = filter.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM audit_log
+             WHERE ($1::text IS NULL OR event = $1
+                    OR (right($1, 2) = '.*' AND starts_with(event, left($1, -1))))
+               AND ($2::text IS NULL OR actor_type = $2)
+               AND ($3::text IS NULL OR actor_id = $3)
+               AND ($4::text IS NULL OR subject = $4)
+               AND ($5::text IS NULL OR ip_address = $5)
+               AND ($6::timestamptz IS NULL OR occurred_at >= $6)
+               AND ($7::timestamptz IS NULL OR occurred_at < $7)
+               AND ($8::bigint IS NULL OR id < $8)
+             ORDER BY id DESC
+             LIMIT $9",
+            AUDIT_COLUMNS
+        ))
+        .bind(&filter.event)
+        .bind(&filter.actor_type)
+        .bind(&filter.actor_id)
+        .bind(&filter.subject)
+        .bind(&filter.ip_address)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.before)
+        .bind(limit)
+        .fetch_all(self.pool.read())
+        .await?)
+    }
+
+    /// Days with entries, oldest first, within the inclusive bounds.
+    pub async fn days(&self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<Vec<NaiveDate>> {
+        Ok(sqlx::query_scalar(
+            "SELECT DISTINCT day FROM audit_log
+             WHERE ($1::date IS NULL OR day >= $1) AND ($2::date IS NULL OR day <= $2)
+             ORDER BY day",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(self.pool.read())
+        .await?)
+    }
+
+    /// Recomputes the day's chain. Entries missing from the end of a day
+    /// cannot be detected this way; the table's triggers guard against that.
+    pub async fn verify_day(&self, day: NaiveDate) -> Result<ChainVerification> {
+        let sql = format!("SELECT {} FROM audit_log WHERE day = $1 ORDER BY seq", AUDIT_COLUMNS);
+        let mut rows = sqlx::query_as::<_, AuditRecord>(&sql).bind(day).fetch(self.pool.read());
+
+        let mut walk = ChainWalk::new(day);
+        while let Some(record) = rows.try_next().await? {
+            walk.push(record);
+        }
+        Ok(walk.verification)
+    }
+}
+
+/// Follows a day's entries in `seq` order, noting the first that does not
+/// link to the one before it.
+struct ChainWalk {
+    verification: ChainVerification,
+    prev_hash: String,
+}
+
+impl ChainWalk {
+    fn new(day: NaiveDate) -> Self {
+        Self {
+            verification: ChainVerification {
+                day,
+                entries: 0,
+                broken: None,
+            },
+            prev_hash: GENESIS_HASH.to_string(),
+        }
+    }
+
+    fn push(&mut self, record: AuditRecord) {
+        let verification = &mut self.verification;
+        verification.entries += 1;
+        if verification.broken.is_some() {
+            return;
+        }
+        let reason = if record.seq as u64 != verification.entries {
+            Some("sequence gap: an entry is missing")
+        } else if record.prev_hash != self.prev_hash {
+            Some("prev_hash does not match the previous entry")
+        } else if ChainLink::from(&record).hash(&self.prev_hash) != record.hash {
+            Some("hash does not match the entry's contents")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            verification.broken = Some(ChainBreak {
+                id: record.id,
+                seq: record.seq,
+                reason,
+            });
+        }
+        self.prev_hash = record.hash;
+    }
+}
+
+/// Records audit entries without waiting on the database. Entries go
+/// through a bounded buffer to a writer task that appends them in batches;
+/// every entry is also logged to the `m5::audit` target as it is recorded.
+/// A full buffer slows recorders down instead of losing entries.
+#[derive(Clone)]
+pub struct AuditLog {
+    sender: mpsc::Sender<AuditEntry>,
+    store: AuditStore,
+    writer: Arc<Mutex<Option<Writer>>>,
+}
+
+struct Writer {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl AuditLog {
+    /// Starts the writer. It runs until [`close`](Self::close) is called or
+    /// every handle is dropped, then drains the buffer.
+    pub fn spawn(store: AuditStore) -> Self {
+        let (sender, receiver) = mpsc::channel(BUFFER_CAPACITY);
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(write_batches(store.clone(), receiver, stopped));
+        Self {
+            sender,
+            store,
+            writer: Arc::new(Mutex::new(Some(Writer { stop, task }))),
+        }
+    }
+
+    /// Stops accepting entries and waits until the buffered ones are
+    /// stored, so none are lost on shutdown. Call before closing the pool.
+    pub async fn close(&self) {
+        let writer = self.writer.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(Writer { stop, task }) = writer {
+            let _ = stop.send(());
+            if let Err(e) = task.await {
+                tracing::error!(target: "m5::audit", error = %e, "Audit writer failed while draining");
+            }
+        }
+    }
+
+    pub fn store(&self) -> &AuditStore {
+        &self.store
+    }
+
+    /// Waits for room in the buffer when the writer falls behind.
+    pub async fn record(&self, entry: AuditEntry) {
+        entry.trace();
+        if let Err(mpsc::error::SendError(entry)) = self.sender.send(entry).await {
+            tracing::error!(
+                target: "m5::audit",
+                event = entry.event.name(),
+                "Audit writer stopped; entry was logged but not stored"
+            );
+        }
+    }
+}
+
+async fn write_batches(
+    store: AuditStore,
+    mut receiver: mpsc::Receiver<AuditEntry>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut stopping = false;
+    loop {
+        tokio::select! {
+            received = receiver.recv_many(&mut batch, BATCH_SIZE) => {
+                if received == 0 {
+                    break;
+                }
+            }
+            // Closing refuses new entries; those already buffered are
+            // still received, after which `recv_many` returns 0.
+            _ = &mut stop, if !stopping => {
+                stopping = true;
+                receiver.close();
+                continue;
+            }
+        }
+        let mut attempt = 0;
+        loop {
+            match store.append(&batch).await {
+                Ok(()) => break,
+                Err(e) if attempt < RETRY_DELAYS.len() => {
+                    tracing::warn!(error = %e, entries = batch.len(), "Failed to store audit entries; retrying");
+                    tokio::time::sleep(RETRY_DELAYS[attempt]).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        target: "m5::audit",
+                        error = %e,
+                        entries = batch.len(),
+                        "Dropping audit entries that could not be stored; they remain in this log"
+                    );
+                    break;
+                }
+            }
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    /// Entries chained the way [`AuditStore::append`] writes them.
+    fn chain(events: &[&str]) -> Vec<AuditRecord> {
+        let mut prev_hash = GENESIS_HASH.to_string();
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let seq = i as i32 + 1;
+                let occurred_at = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, seq as u32).unwrap();
+                let mut record = AuditRecord {
+                    id: 100 + i64::from(seq),
+                    day: day(),
+                    seq,
+                    occurred_at,
+                    recorded_at: occurred_at,
+                    event: event.to_string(),
+                    actor_type: "user".to_string(),
+                    actor_id: Some("u1".to_string()),
+                    subject: None,
+                    ip_address: Some("203.0.113.7".to_string()),
+                    details: json!({ "seq": seq, "reason": "test" }),
+                    prev_hash: prev_hash.clone(),
+                    hash: String::new(),
+                };
+                record.hash = ChainLink::from(&record).hash(&prev_hash);
+                prev_hash = record.hash.clone();
+                record
+            })
+            .collect()
+    }
+
+    fn verify(records: Vec<AuditRecord>) -> ChainVerification {
+        let mut walk = ChainWalk::new(day());
+        records.into_iter().for_each(|record| walk.push(record));
+        walk.verification
+    }
+
+    #[test]
+    fn an_untouched_chain_verifies() {
+        let verification = verify(chain(&["auth.login", "auth.logout", "user.updated"]));
+        assert_eq!(verification.entries, 3);
+        assert!(verification.broken.is_none());
+    }
+
+    #[test]
+    fn hash_covers_every_field_and_the_previous_hash() {
+        let record = &chain(&["auth.login"])[0];
+        let link = ChainLink::from(record);
+        assert_eq!(link.hash(GENESIS_HASH), record.hash);
+        assert_eq!(record.hash.len(), 64);
+        assert_ne!(link.hash(&"1".repeat(64)), record.hash);
+
+        let mut edited = record.clone();
+        edited.actor_id = None;
+        assert_ne!(ChainLink::from(&edited).hash(GENESIS_HASH), record.hash);
+        let mut edited = record.clone();
+        edited.occurred_at += chrono::Duration::microseconds(1);
+        assert_ne!(ChainLink::from(&edited).hash(GENESIS_HASH), record.hash);
+    }
+
+    #[test]
+    fn hash_ignores_the_key_order_of_details() {
+        let mut record = chain(&["auth.login"]).remove(0);
+        record.details = serde_json::from_str(r#"{"b": 1, "a": {"y": 2, "x": 3}}"#).unwrap();
+        let reordered: JsonValue = serde_json::from_str(r#"{"a": {"x": 3, "y": 2}, "b": 1}"#).unwrap();
+        let original = ChainLink::from(&record).hash(GENESIS_HASH);
+        record.details = reordered;
+        assert_eq!(ChainLink::from(&record).hash(GENESIS_HASH), original);
+    }
+
+    #[test]
+    fn detects_an_edited_entry() {
+        let mut records = chain(&["auth.login", "auth.logout", "user.updated"]);
+        records[1].details = json!({ "seq": 2, "reason": "edited" });
+
+        let verification = verify(records);
+        assert_eq!(verification.entries, 3);
+        let broken = verification.broken.unwrap();
+        assert_eq!((broken.id, broken.seq), (102, 2));
+        assert_eq!(broken.reason, "hash does not match the entry's contents");
+    }
+
+    #[test]
+    fn detects_an_edited_entry_whose_hash_was_recomputed() {
+        let mut records = chain(&["auth.login", "auth.logout", "user.updated"]);
+        records[1].event = "auth.login".to_string();
+        records[1].hash = ChainLink::from(&records[1]).hash(&records[0].hash);
+
+        let broken = verify(records).broken.unwrap();
+        assert_eq!(broken.seq, 3);
+        assert_eq!(broken.reason, "prev_hash does not match the previous entry");
+    }
+
+    #[test]
+    fn detects_a_removed_entry() {
+        let mut records = chain(&["auth.login", "auth.logout", "user.updated"]);
+        records.remove(1);
+
+        let broken = verify(records).broken.unwrap();
+        assert_eq!(broken.seq, 3);
+        assert_eq!(broken.reason, "sequence gap: an entry is missing");
+    }
+}
@@ -0,0 +1,645 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, PgPool, Postgres, Type};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{identities, mfa};
+use crate::config::encryption::{EncryptionConfig, KEY_LENGTH};
+use crate::config::Secret;
+use crate::features::users::infrastructure::repositories::user_repository;
+
+const FORMAT_VERSION: u8 = 1;
+const KEY_ID_LENGTH: usize = 8;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const WRAPPED_KEY_LENGTH: usize = NONCE_LENGTH + KEY_LENGTH + TAG_LENGTH;
+/// Version byte, master key id and wrapped data key.
+const HEADER_LENGTH: usize = 1 + KEY_ID_LENGTH + WRAPPED_KEY_LENGTH;
+
+/// Every column holding [`Encrypted`] values, for `cli encryption`.
+pub const ENCRYPTED_COLUMNS: &[EncryptedColumn] = &[
+    mfa::SECRET_COLUMN,
+    user_repository::EMAIL_COLUMN,
+    identities::EMAIL_COLUMN,
+];
+
+/// A value encrypted under a data key of its own, which is stored next to
+/// it wrapped by a master key:
+/// `version | master key id | wrapped data key | nonce | ciphertext`.
+///
+/// Only [`Encryptor`] can read it; as a column it is a plain `BYTEA`.
+pub struct Encrypted<T> {
+    bytes: Vec<u8>,
+    plaintext: PhantomData<fn() -> T>,
+}
+
+impl<T> Encrypted<T> {
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() < HEADER_LENGTH + NONCE_LENGTH + TAG_LENGTH {
+            bail!("encrypted value is truncated ({} bytes)", bytes.len());
+        }
+        if bytes[0] != FORMAT_VERSION {
+            bail!("unknown encrypted value format {}", bytes[0]);
+        }
+        Ok(Self {
+            bytes,
+            plaintext: PhantomData,
+        })
+    }
+
+    /// The master key the data key is wrapped with, in hex.
+    pub fn master_key_id(&self) -> String {
+        hex::encode(self.key_id())
+    }
+
+    fn key_id(&self) -> &[u8] {
+        &self.bytes[1..1 + KEY_ID_LENGTH]
+    }
+
+    fn header(&self) -> &[u8] {
+        &self.bytes[..1 + KEY_ID_LENGTH]
+    }
+
+    fn wrapped_key(&self) -> &[u8] {
+        &self.bytes[1 + KEY_ID_LENGTH..HEADER_LENGTH]
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.bytes[HEADER_LENGTH..]
+    }
+}
+
+impl<T> Clone for Encrypted<T> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            plaintext: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Encrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Encrypted(key {}, {} bytes)", self.master_key_id(), self.bytes.len())
+    }
+}
+
+impl<T> Type<Postgres> for Encrypted<T> {
+    fn type_info() -> PgTypeInfo {
+        <Vec<u8> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Vec<u8> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<T> Encode<'_, Postgres> for Encrypted<T> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> std::result::Result<IsNull, BoxDynError> {
+        <Vec<u8> as Encode<Postgres>>::encode_by_ref(&self.bytes, buf)
+    }
+}
+
+impl<'r, T> Decode<'r, Postgres> for Encrypted<T> {
+    fn decode(value: PgValueRef<'r>) -> std::result::Result<Self, BoxDynError> {
+        let bytes = <Vec<u8> as Decode<Postgres>>::decode(value)?;
+        Ok(Self::from_bytes(bytes)?)
+    }
+}
+
+/// What can be stored in an [`Encrypted`] column.
+pub trait Plaintext: Sized {
+    fn to_plaintext(&self) -> Zeroizing<Vec<u8>>;
+    fn from_plaintext(bytes: Zeroizing<Vec<u8>>) -> Result<Self>;
+}
+
+impl Plaintext for Vec<u8> {
+    fn to_plaintext(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.clone())
+    }
+
+    fn from_plaintext(bytes: Zeroizing<Vec<u8>>) -> Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Plaintext for String {
+    fn to_plaintext(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.as_bytes().to_vec())
+    }
+
+    fn from_plaintext(bytes: Zeroizing<Vec<u8>>) -> Result<Self> {
+        std::str::from_utf8(&bytes)
+            .map(str::to_string)
+            .map_err(|_| anyhow!("decrypted value is not UTF-8"))
+    }
+}
+
+impl<T: Plaintext + Zeroize> Plaintext for Secret<T> {
+    fn to_plaintext(&self) -> Zeroizing<Vec<u8>> {
+        self.expose().to_plaintext()
+    }
+
+    fn from_plaintext(bytes: Zeroizing<Vec<u8>>) -> Result<Self> {
+        T::from_plaintext(bytes).map(Secret::new)
+    }
+}
+
+/// A column of [`Encrypted`] values. `context` is bound into every value
+/// as associated data, so a ciphertext copied to another column does not
+/// decrypt; it must never change.
+#[derive(Debug, Clone, Copy)]
+pub struct EncryptedColumn {
+    pub table: &'static str,
+    /// A unique text column, or expression, identifying the row.
+    pub key: &'static str,
+    pub column: &'static str,
+    pub context: &'static str,
+    /// A plaintext column the values are being moved out of, if any.
+    pub legacy_plaintext: Option<&'static str>,
+    /// The column holding a [`blind_index`](Encryptor::blind_index) of
+    /// each value, if the column is looked up by equality.
+    pub blind_index: Option<BlindIndex>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlindIndex {
+    pub column: &'static str,
+    /// Applied to a value before it is indexed, on write and on lookup.
+    pub normalize: fn(&str) -> String,
+}
+
+impl EncryptedColumn {
+    /// The blind index of `value`, if the column has one.
+    pub fn blind_index(&self, encryptor: &Encryptor, value: &str) -> Option<Vec<u8>> {
+        self.blind_index
+            .map(|index| encryptor.blind_index(self.context, &(index.normalize)(value)))
+    }
+}
+
+impl fmt::Display for EncryptedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.table, self.column)
+    }
+}
+
+struct MasterKey {
+    id: [u8; KEY_ID_LENGTH],
+    cipher: Aes256Gcm,
+}
+
+impl MasterKey {
+    fn new(key: &[u8]) -> Result<Self> {
+        let mut id = [0; KEY_ID_LENGTH];
+        id.copy_from_slice(&Sha256::digest(key)[..KEY_ID_LENGTH]);
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("master key must be {} bytes", KEY_LENGTH))?;
+        Ok(Self { id, cipher })
+    }
+}
+
+struct Keys {
+    current: MasterKey,
+    retired: Vec<MasterKey>,
+    blind_index: Hmac<Sha256>,
+}
+
+/// Envelope encryption for sensitive columns. Each value gets a fresh
+/// AES-256-GCM data key, wrapped by the current master key, so rotating
+/// the master key only means re-wrapping data keys
+/// ([`Encryptor::rewrap`]), never decrypting the data itself.
+///
+/// Encrypted values cannot be compared in SQL; store a
+/// [`blind_index`](Encryptor::blind_index) beside any that must be looked
+/// up by equality.
+#[derive(Clone)]
+pub struct Encryptor {
+    keys: Arc<Keys>,
+}
+
+impl Encryptor {
+    pub fn from_config(config: &EncryptionConfig) -> Result<Self> {
+        let current = MasterKey::new(config.master_key.expose())?;
+        let retired = config
+            .retired_master_keys
+            .iter()
+            .map(|key| MasterKey::new(key.expose()))
+            .collect::<Result<Vec<_>>>()?;
+        let blind_index = <Hmac<Sha256> as Mac>::new_from_slice(config.blind_index_key.expose())
+            .map_err(|_| anyhow!("blind index key is unusable"))?;
+        Ok(Self {
+            keys: Arc::new(Keys {
+                current,
+                retired,
+                blind_index,
+            }),
+        })
+    }
+
+    /// The id recorded in values encrypted from now on, in hex.
+    pub fn master_key_id(&self) -> String {
+        hex::encode(self.keys.current.id)
+    }
+
+    pub fn encrypt<T: Plaintext>(&self, context: &str, value: &T) -> Result<Encrypted<T>> {
+        let mut data_key = Zeroizing::new([0u8; KEY_LENGTH]);
+        OsRng.fill_bytes(&mut data_key[..]);
+        let cipher = Aes256Gcm::new_from_slice(&data_key[..]).expect("data keys have the AES-256 length");
+
+        let plaintext = value.to_plaintext();
+        let nonce = random_nonce();
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: context.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("encryption failed"))?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + NONCE_LENGTH + ciphertext.len());
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.keys.current.id);
+        bytes.extend_from_slice(&wrap(&self.keys.current, &bytes[..1 + KEY_ID_LENGTH], &data_key[..])?);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Encrypted::from_bytes(bytes)
+    }
+
+    pub fn decrypt<T: Plaintext>(&self, context: &str, value: &Encrypted<T>) -> Result<T> {
+        let data_key = self.unwrap(value)?;
+        let cipher = Aes256Gcm::new_from_slice(&data_key).map_err(|_| anyhow!("wrapped data key is malformed"))?;
+        let (nonce, ciphertext) = value.body().split_at(NONCE_LENGTH);
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: context.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("cannot decrypt {} value: wrong context or tampered ciphertext", context))?;
+        T::from_plaintext(Zeroizing::new(plaintext))
+    }
+
+    /// Whether `value` is wrapped by the current master key.
+    pub fn is_current<T>(&self, value: &Encrypted<T>) -> bool {
+        value.key_id() == self.keys.current.id
+    }
+
+    /// Re-wraps the data key of `value` under the current master key. The
+    /// ciphertext itself is kept.
+    pub fn rewrap<T>(&self, value: &Encrypted<T>) -> Result<Encrypted<T>> {
+        let data_key = self.unwrap(value)?;
+        let mut bytes = Vec::with_capacity(value.bytes.len());
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.keys.current.id);
+        bytes.extend_from_slice(&wrap(&self.keys.current, &bytes[..1 + KEY_ID_LENGTH], &data_key)?);
+        bytes.extend_from_slice(value.body());
+        Encrypted::from_bytes(bytes)
+    }
+
+    /// A keyed hash of `value` for equality lookups on an encrypted column,
+    /// e.g. `WHERE email_bidx = $1`. Normalize `value` the same way on
+    /// write and lookup (trim, lowercase an email); `context` keeps equal
+    /// values in different columns from sharing an index.
+    pub fn blind_index(&self, context: &str, value: &str) -> Vec<u8> {
+        let mut mac = self.keys.blind_index.clone();
+        mac.update(context.as_bytes());
+        mac.update(&[0]);
+        mac.update(value.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn unwrap<T>(&self, value: &Encrypted<T>) -> Result<Zeroizing<Vec<u8>>> {
+        let master = std::iter::once(&self.keys.current)
+            .chain(&self.keys.retired)
+            .find(|key| key.id == value.key_id())
+            .ok_or_else(|| {
+                anyhow!(
+                    "value is wrapped by master key {}, which is neither the current nor a retired key",
+                    value.master_key_id()
+                )
+            })?;
+        let (nonce, wrapped) = value.wrapped_key().split_at(NONCE_LENGTH);
+        let data_key = master
+            .cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: wrapped,
+                    aad: value.header(),
+                },
+            )
+            .map_err(|_| anyhow!("cannot unwrap data key under master key {}", value.master_key_id()))?;
+        Ok(Zeroizing::new(data_key))
+    }
+}
+
+fn wrap(master: &MasterKey, header: &[u8], data_key: &[u8]) -> Result<Vec<u8>> {
+    let nonce = random_nonce();
+    let wrapped = master
+        .cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: data_key,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow!("cannot wrap data key"))?;
+    Ok([nonce.as_slice(), &wrapped].concat())
+}
+
+fn random_nonce() -> [u8; NONCE_LENGTH] {
+    let mut nonce = [0; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Rows of one column by the master key their values are wrapped with.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnKeyUsage {
+    pub column: String,
+    /// Hex id, or `plaintext` for rows still in the legacy column.
+    pub master_key_id: String,
+    pub current: bool,
+    pub rows: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReencryptReport {
+    pub column: String,
+    pub rewrapped: u64,
+    /// Values moved out of the legacy plaintext column.
+    pub encrypted: u64,
+}
+
+/// Bulk maintenance of [`ENCRYPTED_COLUMNS`] after a master key rotation.
+pub struct Reencryptor {
+    pool: PgPool,
+    encryptor: Encryptor,
+}
+
+impl Reencryptor {
+    pub fn new(pool: PgPool, encryptor: Encryptor) -> Self {
+        Self { pool, encryptor }
+    }
+
+    pub async fn usage(&self, column: &EncryptedColumn) -> Result<Vec<ColumnKeyUsage>> {
+        let mut sql = format!(
+            "SELECT encode(substring({c} FROM 2 FOR {n}), 'hex'), count(*) FROM {t}
+             WHERE {c} IS NOT NULL GROUP BY 1",
+            c = column.column,
+            t = column.table,
+            n = KEY_ID_LENGTH
+        );
+        if let Some(legacy) = column.legacy_plaintext {
+            sql.push_str(&format!(
+                " UNION ALL SELECT 'plaintext', count(*) FROM {t} WHERE {l} IS NOT NULL HAVING count(*) > 0",
+                t = column.table,
+                l = legacy
+            ));
+        }
+        let rows: Vec<(String, i64)> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let current = self.encryptor.master_key_id();
+        Ok(rows
+            .into_iter()
+            .map(|(master_key_id, rows)| ColumnKeyUsage {
+                column: column.to_string(),
+                current: master_key_id == current,
+                master_key_id,
+                rows,
+            })
+            .collect())
+    }
+
+    /// Moves every value of `column` to the current master key, and any
+    /// left in its legacy plaintext column into it. Rows changed meanwhile
+    /// are skipped; whatever wrote them used the current key.
+    pub async fn reencrypt(&self, column: &EncryptedColumn, batch_size: i64) -> Result<ReencryptReport> {
+        let mut report = ReencryptReport {
+            column: column.to_string(),
+            ..Default::default()
+        };
+        let current_id = self.encryptor.keys.current.id.to_vec();
+
+        let select = format!(
+            "SELECT {k}, {c} FROM {t}
+             WHERE {c} IS NOT NULL AND substring({c} FROM 2 FOR {n}) <> $1 AND {k} > $2
+             ORDER BY {k} LIMIT $3",
+            k = column.key,
+            c = column.column,
+            t = column.table,
+            n = KEY_ID_LENGTH
+        );
+        let update = format!(
+            "UPDATE {t} SET {c} = $3 WHERE {k} = $1 AND {c} = $2",
+            k = column.key,
+            c = column.column,
+            t = column.table
+        );
+        let mut after = String::new();
+        loop {
+            let rows: Vec<(String, Encrypted<Vec<u8>>)> = sqlx::query_as(&select)
+                .bind(&current_id)
+                .bind(&after)
+                .bind(batch_size)
+                .fetch_all(&self.pool)
+                .await?;
+            let Some((last, _)) = rows.last() else {
+                break;
+            };
+            after = last.clone();
+            for (key, value) in rows {
+                let rewrapped = self
+                    .encryptor
+                    .rewrap(&value)
+                    .with_context(|| format!("{} row {}", column, key))?;
+                let result = sqlx::query(&update)
+                    .bind(&key)
+                    .bind(&value)
+                    .bind(&rewrapped)
+                    .execute(&self.pool)
+                    .await?;
+                report.rewrapped += result.rows_affected();
+            }
+        }
+
+        let Some(legacy) = column.legacy_plaintext else {
+            return Ok(report);
+        };
+        let select = format!(
+            "SELECT {k}, {l} FROM {t} WHERE {l} IS NOT NULL AND {k} > $1 ORDER BY {k} LIMIT $2",
+            k = column.key,
+            l = legacy,
+            t = column.table
+        );
+        // Rewrapping leaves the plaintext, and so its blind index, as it was;
+        // a value leaving the legacy column gets its index here.
+        let index = column
+            .blind_index
+            .map(|index| format!(", {} = $4", index.column))
+            .unwrap_or_default();
+        let update = format!(
+            "UPDATE {t} SET {c} = $3, {l} = NULL{i} WHERE {k} = $1 AND {l} = $2",
+            k = column.key,
+            c = column.column,
+            l = legacy,
+            i = index,
+            t = column.table
+        );
+        let mut after = String::new();
+        loop {
+            let rows: Vec<(String, String)> = sqlx::query_as(&select)
+                .bind(&after)
+                .bind(batch_size)
+                .fetch_all(&self.pool)
+                .await?;
+            let Some((last, _)) = rows.last() else {
+                break;
+            };
+            after = last.clone();
+            for (key, plaintext) in rows {
+                let plaintext = Secret::new(plaintext);
+                let encrypted = self.encryptor.encrypt(column.context, &plaintext)?;
+                let mut query = sqlx::query(&update)
+                    .bind(&key)
+                    .bind(plaintext.expose())
+                    .bind(&encrypted);
+                if let Some(index) = column.blind_index(&self.encryptor, plaintext.expose()) {
+                    query = query.bind(index);
+                }
+                let result = query
+                    .execute(&self.pool)
+                    .await
+                    .with_context(|| format!("{} row {}", column, key))?;
+                report.encrypted += result.rows_affected();
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: &str = "users.email";
+
+    fn key(byte: u8) -> Secret<Vec<u8>> {
+        Secret::new(vec![byte; KEY_LENGTH])
+    }
+
+    fn encryptor(master: u8, retired: &[u8], blind_index: u8) -> Encryptor {
+        Encryptor::from_config(&EncryptionConfig {
+            master_key: key(master),
+            retired_master_keys: retired.iter().copied().map(key).collect(),
+            blind_index_key: key(blind_index),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips_values() {
+        let encryptor = encryptor(1, &[], 9);
+        let text = encryptor.encrypt(CONTEXT, &"alice@example.com".to_string()).unwrap();
+        assert_eq!(encryptor.decrypt(CONTEXT, &text).unwrap(), "alice@example.com");
+
+        let bytes = encryptor.encrypt("user_mfa.secret", &vec![0u8, 1, 2, 255]).unwrap();
+        assert_eq!(encryptor.decrypt("user_mfa.secret", &bytes).unwrap(), vec![0u8, 1, 2, 255]);
+
+        let secret = encryptor.encrypt(CONTEXT, &Secret::new("hunter2".to_string())).unwrap();
+        assert_eq!(encryptor.decrypt(CONTEXT, &secret).unwrap().expose(), "hunter2");
+    }
+
+    #[test]
+    fn every_encryption_uses_a_fresh_data_key() {
+        let encryptor = encryptor(1, &[], 9);
+        let value = "alice@example.com".to_string();
+        let first = encryptor.encrypt(CONTEXT, &value).unwrap();
+        let second = encryptor.encrypt(CONTEXT, &value).unwrap();
+        assert_ne!(first.bytes, second.bytes);
+        assert_ne!(first.wrapped_key(), second.wrapped_key());
+    }
+
+    #[test]
+    fn refuses_another_context_or_a_tampered_value() {
+        let encryptor = encryptor(1, &[], 9);
+        let value = encryptor.encrypt(CONTEXT, &"alice@example.com".to_string()).unwrap();
+        assert!(encryptor.decrypt("identities.email", &value).is_err());
+
+        for index in [1, HEADER_LENGTH - 1, value.bytes.len() - 1] {
+            let mut bytes = value.bytes.clone();
+            bytes[index] ^= 1;
+            let tampered = Encrypted::<String>::from_bytes(bytes).unwrap();
+            assert!(encryptor.decrypt(CONTEXT, &tampered).is_err(), "byte {}", index);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_or_unknown_formats() {
+        let encryptor = encryptor(1, &[], 9);
+        let value = encryptor.encrypt(CONTEXT, &String::new()).unwrap();
+        assert!(Encrypted::<String>::from_bytes(value.bytes[..value.bytes.len() - 1].to_vec()).is_err());
+
+        let mut bytes = value.bytes.clone();
+        bytes[0] = FORMAT_VERSION + 1;
+        assert!(Encrypted::<String>::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn rewraps_under_the_current_master_key() {
+        let old = encryptor(1, &[], 9);
+        let value = old.encrypt(CONTEXT, &"alice@example.com".to_string()).unwrap();
+
+        let rotated = encryptor(2, &[1], 9);
+        assert!(!rotated.is_current(&value));
+        assert_eq!(rotated.decrypt(CONTEXT, &value).unwrap(), "alice@example.com");
+
+        let rewrapped = rotated.rewrap(&value).unwrap();
+        assert!(rotated.is_current(&rewrapped));
+        assert_eq!(rewrapped.master_key_id(), rotated.master_key_id());
+        assert_eq!(rewrapped.body(), value.body());
+
+        let retired_dropped = encryptor(2, &[], 9);
+        assert_eq!(retired_dropped.decrypt(CONTEXT, &rewrapped).unwrap(), "alice@example.com");
+        assert!(retired_dropped.decrypt(CONTEXT, &value).is_err());
+        assert!(retired_dropped.rewrap(&value).is_err());
+    }
+
+    #[test]
+    fn blind_index_is_stable_across_instances_and_master_keys() {
+        let index = encryptor(1, &[], 9).blind_index(CONTEXT, "alice@example.com");
+        assert_eq!(index.len(), 32);
+        assert_eq!(encryptor(1, &[], 9).blind_index(CONTEXT, "alice@example.com"), index);
+        assert_eq!(encryptor(2, &[1], 9).blind_index(CONTEXT, "alice@example.com"), index);
+
+        assert_ne!(encryptor(1, &[], 8).blind_index(CONTEXT, "alice@example.com"), index);
+        assert_ne!(encryptor(1, &[], 9).blind_index("identities.email", "alice@example.com"), index);
+        assert_ne!(encryptor(1, &[], 9).blind_index(CONTEXT, "bob@example.com"), index);
+    }
+
+    #[test]
+    fn column_blind_index_normalizes_before_hashing() {
+        let encryptor = encryptor(1, &[], 9);
+        let column = user_repository::EMAIL_COLUMN;
+        assert_eq!(
+            column.blind_index(&encryptor, "  Alice@Example.COM "),
+            Some(encryptor.blind_index(column.context, "alice@example.com"))
+        );
+        assert_eq!(mfa::SECRET_COLUMN.blind_index(&encryptor, "anything"), None);
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use super::encryption::{Encrypted, EncryptedColumn, Encryptor};
+use crate::common::security::{generate_random_token, sha256_hex};
+use crate::config::Secret;
+
+const STATE_LENGTH: usize = 43;
+/// RFC 7636 allows 43 to 128 characters.
+const CODE_VERIFIER_LENGTH: usize = 64;
+const NONCE_LENGTH: usize = 32;
+
+const IDENTITY_COLUMNS: &str = "provider, subject, user_id, email, email_encrypted, created_at, last_login_at";
+
+/// Where the email a provider reports for an identity is kept, encrypted.
+/// Emails stored before encryption sit in `email` until
+/// `cli encryption re-encrypt` moves them.
+pub const EMAIL_COLUMN: EncryptedColumn = EncryptedColumn {
+    table: "user_identities",
+    key: "(provider || ' ' || subject)",
+    column: "email_encrypted",
+    context: "user_identities.email",
+    legacy_plaintext: Some("email"),
+    blind_index: None,
+};
+
+#[derive(FromRow)]
+struct IdentityRow {
+    provider: String,
+    subject: String,
+    user_id: String,
+    email: Option<String>,
+    email_encrypted: Option<Encrypted<String>>,
+    created_at: DateTime<Utc>,
+    last_login_at: Option<DateTime<Utc>>,
+}
+
+/// An external account linked to a local user.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkedIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub user_id: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+/// What a new authorization request needs to send, and later prove.
+#[derive(Debug)]
+pub struct PendingAuthorization {
+    pub state: Secret<String>,
+    pub code_verifier: Secret<String>,
+    pub nonce: String,
+}
+
+/// An authorization request that came back with a matching `state`.
+#[derive(Debug, FromRow)]
+pub struct ReturnedAuthorization {
+    pub code_verifier: String,
+    pub nonce: String,
+    pub link_user_id: Option<String>,
+}
+
+/// Linked OIDC identities and in-flight authorization requests. Only a
+/// hash of `state` is stored; the verifier and nonce never leave the server
+/// except as the PKCE challenge and inside the authorization URL.
+#[derive(Clone)]
+pub struct IdentityStore {
+    pool: PgPool,
+    encryptor: Encryptor,
+}
+
+impl IdentityStore {
+    pub fn new(pool: PgPool, encryptor: Encryptor) -> Self {
+        Self { pool, encryptor }
+    }
+
+    fn restore(&self, row: IdentityRow) -> Result<LinkedIdentity> {
+        let email = match row.email_encrypted {
+            Some(encrypted) => Some(self.encryptor.decrypt(EMAIL_COLUMN.context, &encrypted)?),
+            None => row.email,
+        };
+        Ok(LinkedIdentity {
+            provider: row.provider,
+            subject: row.subject,
+            user_id: row.user_id,
+            email,
+            created_at: row.created_at,
+            last_login_at: row.last_login_at,
+        })
+    }
+
+    fn seal_email(&self, email: Option<&str>) -> Result<Option<Encrypted<String>>> {
+        email
+            .map(|email| self.encryptor.encrypt(EMAIL_COLUMN.context, &email.to_string()))
+            .transpose()
+    }
+
+    pub async fn find(&self, provider: &str, subject: &str) -> Result<Option<LinkedIdentity>> {
+        let row: Option<IdentityRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM user_identities WHERE provider = $1 AND subject = $2",
+            IDENTITY_COLUMNS
+        ))
+        .bind(provider)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| self.restore(row)).transpose()
+    }
+
+    pub async fn list_for_user(&self, user_id: &str) -> Result<Vec<LinkedIdentity>> {
+        let rows: Vec<IdentityRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM user_identities WHERE user_id = $1 ORDER BY provider",
+            IDENTITY_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(|row| self.restore(row)).collect()
+    }
+
+    /// Links `subject` at `provider` to `user_id`. Returns `None` when the
+    /// identity belongs to someone else or the user already has a different
+    /// identity at this provider.
+    pub async fn link(
+        &self,
+        provider: &str,
+        subject: &str,
+        user_id: &str,
+        email: Option<&str>,
+    ) -> Result<Option<LinkedIdentity>> {
+        // The no-op update makes an existing link of the same pair come
+        // back from RETURNING, so linking twice is idempotent.
+        let linked: std::result::Result<Option<IdentityRow>, _> = sqlx::query_as(&format!(
+            "INSERT INTO user_identities (provider, subject, user_id, email_encrypted) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (provider, subject) DO UPDATE
+                 SET email = NULL, email_encrypted = EXCLUDED.email_encrypted
+                 WHERE user_identities.user_id = EXCLUDED.user_id
+             RETURNING {}",
+            IDENTITY_COLUMNS
+        ))
+        .bind(provider)
+        .bind(subject)
+        .bind(user_id)
+        .bind(self.seal_email(email)?)
+        .fetch_optional(&self.pool)
+        .await;
+
+        match linked {
+            Ok(linked) => linked.map(|row| self.restore(row)).transpose(),
+            // UNIQUE (user_id, provider): a different identity is linked.
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn record_login(&self, provider: &str, subject: &str, email: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "UPDATE user_identities
+             SET last_login_at = now(),
+                 email = CASE WHEN $3::bytea IS NULL THEN email END,
+                 email_encrypted = COALESCE($3, email_encrypted)
+             WHERE provider = $1 AND subject = $2",
+        )
+        .bind(provider)
+        .bind(subject)
+        .bind(self.seal_email(email)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn unlink(&self, user_id: &str, provider: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM user_identities WHERE user_id = $1 AND provider = $2")
+            .bind(user_id)
+            .bind(provider)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records a new authorization request. `link_user_id` marks a request
+    /// started by a signed-in user to link another identity.
+    pub async fn begin_authorization(
+        &self,
+        provider: &str,
+        link_user_id: Option<&str>,
+        ttl: Duration,
+    ) -> Result<PendingAuthorization> {
+        let pending = PendingAuthorization {
+            state: Secret::new(generate_random_token(STATE_LENGTH)),
+            code_verifier: Secret::new(generate_random_token(CODE_VERIFIER_LENGTH)),
+            nonce: generate_random_token(NONCE_LENGTH),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM oidc_login_states WHERE expires_at < now()")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO oidc_login_states (state_hash, provider, code_verifier, nonce, link_user_id, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(sha256_hex(pending.state.expose()))
+        .bind(provider)
+        .bind(pending.code_verifier.expose())
+        .bind(&pending.nonce)
+        .bind(link_user_id)
+        .bind(Utc::now() + ttl)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(pending)
+    }
+
+    /// Consumes the request `state` refers to. Each state works once, and
+    /// only for the provider it was created for.
+    pub async fn take_authorization(&self, provider: &str, state: &str) -> Result<Option<ReturnedAuthorization>> {
+        Ok(sqlx::query_as(
+            "DELETE FROM oidc_login_states
+             WHERE state_hash = $1 AND provider = $2 AND expires_at > now()
+             RETURNING code_verifier, nonce, link_user_id",
+        )
+        .bind(sha256_hex(state))
+        .bind(provider)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::Utc;
+use rsa::pkcs8::DecodePrivateKey as _;
+use rsa::traits::PublicKeyParts as _;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::signing_keys::{default_verify_grace, KeyAlgorithm, KeyState, SigningKey, SigningKeyStore};
+use crate::common::errors::AppError;
+use crate::config::auth::AuthConfig;
+
+/// Access token claims. The signing key is named by the `kid` header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    /// Session (refresh-token family) the token was issued for.
+    pub sid: String,
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: String,
+    /// Authentication methods (RFC 8176): `pwd`, plus `otp` once a second
+    /// factor was verified for the session.
+    #[serde(default)]
+    pub amr: Vec<String>,
+}
+
+impl Claims {
+    pub fn mfa_verified(&self) -> bool {
+        self.amr.iter().any(|method| method == "otp")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    pub token: String,
+    pub kid: String,
+    pub expires_in: u64,
+}
+
+/// Public half of an asymmetric signing key, as published at
+/// `/.well-known/jwks.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    pub kty: &'static str,
+    pub kid: String,
+    pub alg: &'static str,
+    #[serde(rename = "use")]
+    pub key_use: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+/// A signing key parsed once on load rather than on every token.
+struct LoadedKey {
+    kid: String,
+    algorithm: KeyAlgorithm,
+    state: KeyState,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+impl LoadedKey {
+    fn prepare(key: &SigningKey) -> Result<Self> {
+        let material = key.secret.expose();
+        let (encoding, decoding, jwk) = match key.algorithm {
+            KeyAlgorithm::HS256 => (
+                EncodingKey::from_secret(material.as_bytes()),
+                DecodingKey::from_secret(material.as_bytes()),
+                None,
+            ),
+            KeyAlgorithm::RS256 => {
+                let public = rsa::RsaPrivateKey::from_pkcs8_pem(material)?.to_public_key();
+                let n = URL_SAFE_NO_PAD.encode(public.n().to_bytes_be());
+                let e = URL_SAFE_NO_PAD.encode(public.e().to_bytes_be());
+                (
+                    EncodingKey::from_rsa_pem(material.as_bytes())?,
+                    DecodingKey::from_rsa_components(&n, &e)?,
+                    Some(Jwk {
+                        n: Some(n),
+                        e: Some(e),
+                        ..Jwk::new("RSA", key)
+                    }),
+                )
+            }
+            KeyAlgorithm::EdDSA => {
+                let private = ed25519_dalek::SigningKey::from_pkcs8_pem(material)
+                    .map_err(|e| anyhow!("parsing Ed25519 key {}: {}", key.kid, e))?;
+                let x = URL_SAFE_NO_PAD.encode(private.verifying_key().as_bytes());
+                (
+                    EncodingKey::from_ed_pem(material.as_bytes())?,
+                    DecodingKey::from_ed_components(&x)?,
+                    Some(Jwk {
+                        crv: Some("Ed25519"),
+                        x: Some(x),
+                        ..Jwk::new("OKP", key)
+                    }),
+                )
+            }
+        };
+        Ok(Self {
+            kid: key.kid.clone(),
+            algorithm: key.algorithm,
+            state: key.state,
+            encoding,
+            decoding,
+            jwk,
+        })
+    }
+}
+
+impl Jwk {
+    fn new(kty: &'static str, key: &SigningKey) -> Self {
+        Self {
+            kty,
+            kid: key.kid.clone(),
+            alg: key.algorithm.as_str(),
+            key_use: "sig",
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+        }
+    }
+}
+
+/// Minimum time between reloads triggered by tokens signed with a key this
+/// instance has not loaded, so forged `kid`s cannot hammer the database.
+const UNKNOWN_KID_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Issues and verifies access tokens against the signing keys in the
+/// database. Keys are cached in memory and reloaded periodically; tokens
+/// signed by any active or verify-only key are accepted, and the public
+/// halves of asymmetric keys are published so other services can verify
+/// tokens without sharing a secret.
+#[derive(Clone)]
+pub struct TokenService {
+    store: SigningKeyStore,
+    keys: Arc<RwLock<Vec<LoadedKey>>>,
+    /// When a token with an unknown `kid` last triggered a reload.
+    unknown_kid_reload: Arc<Mutex<Option<Instant>>>,
+    issuer: String,
+    audience: String,
+    access_token_ttl: Duration,
+}
+
+impl TokenService {
+    pub async fn load(store: SigningKeyStore, config: &AuthConfig) -> Result<Self> {
+        store
+            .ensure_active(config.signing_algorithm, default_verify_grace(config))
+            .await?;
+        let service = Self {
+            store,
+            keys: Arc::new(RwLock::new(Vec::new())),
+            unknown_kid_reload: Arc::new(Mutex::new(None)),
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            access_token_ttl: config.access_token_ttl,
+        };
+        service.reload().await?;
+        Ok(service)
+    }
+
+    pub async fn reload(&self) -> Result<()> {
+        let mut keys = Vec::new();
+        for key in self.store.list().await? {
+            if key.state == KeyState::Retired {
+                continue;
+            }
+            // One unreadable key must not take the others down with it.
+            match LoadedKey::prepare(&key) {
+                Ok(loaded) => keys.push(loaded),
+                Err(e) => warn!(kid = %key.kid, "Skipping unusable signing key: {}", e),
+            }
+        }
+        *self.keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+        Ok(())
+    }
+
+    /// Public keys of every asymmetric key that still verifies.
+    pub fn jwks(&self) -> JwkSet {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        JwkSet {
+            keys: keys.iter().filter_map(|key| key.jwk.clone()).collect(),
+        }
+    }
+
+    pub fn spawn_refresh(&self, interval: Duration) -> JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = service.reload().await {
+                    warn!("Failed to reload signing keys: {}", e);
+                }
+            }
+        })
+    }
+
+    pub fn access_token_ttl(&self) -> Duration {
+        self.access_token_ttl
+    }
+
+    pub fn issue(&self, user_id: &str, session_id: &str, mfa_verified: bool) -> Result<AccessToken, AppError> {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let key = keys
+            .iter()
+            .find(|key| key.state == KeyState::Active)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("no active signing key")))?;
+
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            sid: session_id.to_string(),
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            iat: now,
+            exp: now + self.access_token_ttl.as_secs() as i64,
+            jti: cuid::cuid2(),
+            amr: if mfa_verified {
+                vec!["pwd".to_string(), "otp".to_string()]
+            } else {
+                vec!["pwd".to_string()]
+            },
+        };
+        let mut header = Header::new(algorithm(key.algorithm));
+        header.kid = Some(key.kid.clone());
+
+        let token = encode(&header, &claims, &key.encoding).map_err(|e| AppError::Internal(e.into()))?;
+        Ok(AccessToken {
+            token,
+            kid: key.kid.clone(),
+            expires_in: self.access_token_ttl.as_secs(),
+        })
+    }
+
+    /// A token signed with a key this instance has not loaded yet (one just
+    /// rotated in by another instance or the CLI) triggers a reload before it
+    /// is rejected.
+    pub async fn verify(&self, token: &str) -> Result<Claims, AppError> {
+        let header = decode_header(token).map_err(|_| invalid("malformed token"))?;
+        let kid = header.kid.ok_or_else(|| invalid("token has no key id"))?;
+        if !self.has_key(&kid) {
+            self.reload_for_unknown_kid(&kid).await;
+        }
+
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let key = keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .ok_or_else(|| invalid("unknown or retired signing key"))?;
+
+        let algorithm = algorithm(key.algorithm);
+        if header.alg != algorithm {
+            return Err(invalid("unexpected signing algorithm"));
+        }
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<Claims>(token, &key.decoding, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => invalid("token expired"),
+                _ => invalid("invalid token"),
+            })
+    }
+
+    fn has_key(&self, kid: &str) -> bool {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        keys.iter().any(|key| key.kid == kid)
+    }
+
+    async fn reload_for_unknown_kid(&self, kid: &str) {
+        let mut last_reload = self.unknown_kid_reload.lock().await;
+        // Requests that queued behind a reload may find the key loaded now.
+        if self.has_key(kid) || last_reload.is_some_and(|at| at.elapsed() < UNKNOWN_KID_RELOAD_INTERVAL) {
+            return;
+        }
+        *last_reload = Some(Instant::now());
+        if let Err(e) = self.reload().await {
+            warn!(kid, "Failed to reload signing keys for an unknown key id: {}", e);
+        }
+    }
+}
+
+fn algorithm(algorithm: KeyAlgorithm) -> Algorithm {
+    match algorithm {
+        KeyAlgorithm::HS256 => Algorithm::HS256,
+        KeyAlgorithm::RS256 => Algorithm::RS256,
+        KeyAlgorithm::EdDSA => Algorithm::EdDSA,
+    }
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::Authentication(reason.to_string())
+}
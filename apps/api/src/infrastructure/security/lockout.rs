@@ -0,0 +1,178 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::config::auth::LockoutConfig;
+
+const THROTTLE_COLUMNS: &str = "scope, key, failures, last_failure_at, blocked_until, locked_at";
+
+/// What failed logins are counted against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleScope {
+    /// Keyed by normalized email, so unknown accounts are throttled the
+    /// same way as real ones.
+    Account,
+    Ip,
+}
+
+impl ThrottleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThrottleScope::Account => "account",
+            ThrottleScope::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ThrottleEntry {
+    pub scope: String,
+    pub key: String,
+    pub failures: i32,
+    pub last_failure_at: DateTime<Utc>,
+    pub blocked_until: Option<DateTime<Utc>>,
+    /// Set while the block comes from reaching a threshold.
+    pub locked_at: Option<DateTime<Utc>>,
+}
+
+impl ThrottleEntry {
+    /// Whole seconds until the block ends, at least one.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.blocked_until
+            .map(|until| (until - Utc::now()).num_milliseconds())
+            .map_or(1, |ms| ((ms + 999) / 1000).max(1) as u64)
+    }
+}
+
+/// Result of counting one failed login against a key.
+#[derive(Debug, Clone)]
+pub struct FailureOutcome {
+    pub failures: u32,
+    pub blocked_until: Option<DateTime<Utc>>,
+    /// This failure reached the lockout threshold.
+    pub locked: bool,
+}
+
+/// Failed-login counters with progressive delays and lockouts, shared by
+/// every instance through Postgres. See [`LockoutConfig`] for the policy.
+#[derive(Clone)]
+pub struct LoginThrottle {
+    pool: PgPool,
+    config: LockoutConfig,
+}
+
+impl LoginThrottle {
+    pub fn new(pool: PgPool, config: LockoutConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// The block on any of `keys` that ends last, if one is in effect.
+    pub async fn blocked(&self, keys: &[(ThrottleScope, &str)]) -> Result<Option<ThrottleEntry>> {
+        if !self.config.enabled || keys.is_empty() {
+            return Ok(None);
+        }
+        let (scopes, keys): (Vec<&str>, Vec<&str>) = keys.iter().map(|(scope, key)| (scope.as_str(), *key)).unzip();
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM login_throttles
+             WHERE (scope, key) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+               AND blocked_until > now()
+             ORDER BY blocked_until DESC
+             LIMIT 1",
+            THROTTLE_COLUMNS
+        ))
+        .bind(scopes)
+        .bind(keys)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Counts a failure against `key` and blocks it if the policy says so.
+    /// Failures older than the window start the count over.
+    pub async fn record_failure(&self, scope: ThrottleScope, key: &str) -> Result<FailureOutcome> {
+        if !self.config.enabled {
+            return Ok(FailureOutcome {
+                failures: 0,
+                blocked_until: None,
+                locked: false,
+            });
+        }
+        let cutoff = Utc::now() - chrono::Duration::from_std(self.config.window).unwrap_or(chrono::Duration::MAX);
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM login_throttles
+             WHERE last_failure_at < $1 AND (blocked_until IS NULL OR blocked_until < now())",
+        )
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+        let (failures,): (i32,) = sqlx::query_as(
+            "INSERT INTO login_throttles (scope, key, failures) VALUES ($1, $2, 1)
+             ON CONFLICT (scope, key) DO UPDATE SET
+                 failures = CASE WHEN login_throttles.last_failure_at < $3 THEN 1
+                                 ELSE login_throttles.failures + 1 END,
+                 last_failure_at = now()
+             RETURNING failures",
+        )
+        .bind(scope.as_str())
+        .bind(key)
+        .bind(cutoff)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let failures = failures.max(0) as u32;
+        let threshold = match scope {
+            ThrottleScope::Account => self.config.account_threshold,
+            ThrottleScope::Ip => self.config.ip_threshold,
+        };
+        let (blocked_until, locked) = match self.config.block_after(failures, threshold) {
+            Some((duration, locked)) => (
+                Some(Utc::now() + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)),
+                locked,
+            ),
+            None => (None, false),
+        };
+        sqlx::query(
+            "UPDATE login_throttles
+             SET blocked_until = $3, locked_at = CASE WHEN $4 THEN now() END
+             WHERE scope = $1 AND key = $2",
+        )
+        .bind(scope.as_str())
+        .bind(key)
+        .bind(blocked_until)
+        .bind(locked)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(FailureOutcome {
+            failures,
+            blocked_until,
+            locked,
+        })
+    }
+
+    /// Forgets the failures of `key`, lifting any block on it.
+    pub async fn clear(&self, scope: ThrottleScope, key: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM login_throttles WHERE scope = $1 AND key = $2")
+            .bind(scope.as_str())
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Accounts and IPs currently locked out, soonest to expire first.
+    pub async fn list_locked(&self) -> Result<Vec<ThrottleEntry>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM login_throttles
+             WHERE locked_at IS NOT NULL AND blocked_until > now()
+             ORDER BY blocked_until",
+            THROTTLE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
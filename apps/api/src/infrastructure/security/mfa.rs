@@ -0,0 +1,250 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::encryption::{Encrypted, EncryptedColumn, Encryptor};
+use crate::common::security::{generate_random_token, sha256_hex};
+use crate::config::Secret;
+
+const RECOVERY_CODE_LENGTH: usize = 12;
+const CHALLENGE_TOKEN_LENGTH: usize = 48;
+
+/// Where TOTP secrets are kept, encrypted. Secrets enrolled before
+/// encryption sit in `secret` until `cli encryption re-encrypt` moves them.
+pub const SECRET_COLUMN: EncryptedColumn = EncryptedColumn {
+    table: "user_mfa",
+    key: "user_id",
+    column: "secret_encrypted",
+    context: "user_mfa.secret",
+    legacy_plaintext: Some("secret"),
+    blind_index: None,
+};
+
+/// Wrong codes allowed against one pending login before it is burned.
+pub const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MfaStatus {
+    pub enabled: bool,
+    /// Enrollment started but not yet confirmed with a code.
+    pub pending: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Debug)]
+pub struct TotpSecret {
+    pub secret: Secret<String>,
+    pub enabled: bool,
+    pub last_used_step: Option<i64>,
+}
+
+/// TOTP secrets (encrypted), recovery codes (SHA-256 hashed) and
+/// pending-login challenges for two-factor authentication.
+#[derive(Clone)]
+pub struct MfaStore {
+    pool: PgPool,
+    encryptor: Encryptor,
+}
+
+impl MfaStore {
+    pub fn new(pool: PgPool, encryptor: Encryptor) -> Self {
+        Self { pool, encryptor }
+    }
+
+    pub async fn status(&self, user_id: &str) -> Result<MfaStatus> {
+        let (enabled, pending, recovery_codes_remaining): (bool, bool, i64) = sqlx::query_as(
+            "SELECT
+                 EXISTS (SELECT 1 FROM user_mfa WHERE user_id = $1 AND enabled_at IS NOT NULL),
+                 EXISTS (SELECT 1 FROM user_mfa WHERE user_id = $1 AND enabled_at IS NULL),
+                 (SELECT count(*) FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(MfaStatus {
+            enabled,
+            pending,
+            recovery_codes_remaining,
+        })
+    }
+
+    pub async fn is_enabled(&self, user_id: &str) -> Result<bool> {
+        Ok(self.status(user_id).await?.enabled)
+    }
+
+    /// Stores a new unconfirmed secret, replacing any earlier unconfirmed
+    /// one. Returns `false` when 2FA is already enabled.
+    pub async fn begin_enrollment(&self, user_id: &str, secret: &str) -> Result<bool> {
+        let secret = self
+            .encryptor
+            .encrypt(SECRET_COLUMN.context, &Secret::new(secret.to_string()))?;
+        let result = sqlx::query(
+            "INSERT INTO user_mfa (user_id, secret_encrypted) VALUES ($1, $2)
+             ON CONFLICT (user_id) DO UPDATE
+                 SET secret_encrypted = EXCLUDED.secret_encrypted, secret = NULL,
+                     last_used_step = NULL, created_at = now()
+                 WHERE user_mfa.enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(&secret)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn secret(&self, user_id: &str) -> Result<Option<TotpSecret>> {
+        type Row = (Option<Encrypted<Secret<String>>>, Option<String>, bool, Option<i64>);
+        let row: Option<Row> = sqlx::query_as(
+            "SELECT secret_encrypted, secret, enabled_at IS NOT NULL, last_used_step
+             FROM user_mfa WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((encrypted, plaintext, enabled, last_used_step)) = row else {
+            return Ok(None);
+        };
+        let secret = match (encrypted, plaintext) {
+            (Some(encrypted), _) => self.encryptor.decrypt(SECRET_COLUMN.context, &encrypted)?,
+            (None, Some(plaintext)) => Secret::new(plaintext),
+            (None, None) => anyhow::bail!("user_mfa row for {} has no secret", user_id),
+        };
+        Ok(Some(TotpSecret {
+            secret,
+            enabled,
+            last_used_step,
+        }))
+    }
+
+    /// Records `step` as used. Returns `false` if it, or a later step, was
+    /// already used, which makes concurrent submissions of one code safe.
+    pub async fn record_step(&self, user_id: &str, step: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE user_mfa SET last_used_step = $2
+             WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Confirms a pending enrollment and replaces the recovery codes. The
+    /// returned codes are the only plaintext copies.
+    pub async fn enable(&self, user_id: &str, step: i64, recovery_codes: usize) -> Result<Option<Vec<Secret<String>>>> {
+        let mut tx = self.pool.begin().await?;
+        let enabled = sqlx::query(
+            "UPDATE user_mfa SET enabled_at = now(), last_used_step = $2
+             WHERE user_id = $1 AND enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&mut *tx)
+        .await?;
+        if enabled.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let codes = replace_recovery_codes(&mut tx, user_id, recovery_codes).await?;
+        tx.commit().await?;
+        Ok(Some(codes))
+    }
+
+    /// Burns a recovery code. Returns `false` for unknown or used codes.
+    pub async fn use_recovery_code(&self, user_id: &str, code: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE mfa_recovery_codes SET used_at = now()
+             WHERE code_hash = $1 AND user_id = $2 AND used_at IS NULL",
+        )
+        .bind(sha256_hex(code.trim()))
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Removes the secret, recovery codes and pending challenges.
+    pub async fn disable(&self, user_id: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM mfa_challenges WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let removed = sqlx::query("DELETE FROM user_mfa WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(removed.rows_affected() == 1)
+    }
+
+    /// Opens a pending login for `user_id`; the returned token is the "mfa
+    /// pending" credential the client exchanges together with a code.
+    pub async fn create_challenge(&self, user_id: &str, ttl: Duration) -> Result<Secret<String>> {
+        let token = Secret::new(generate_random_token(CHALLENGE_TOKEN_LENGTH));
+        sqlx::query("INSERT INTO mfa_challenges (token_hash, user_id, expires_at) VALUES ($1, $2, $3)")
+            .bind(sha256_hex(token.expose()))
+            .bind(user_id)
+            .bind(Utc::now() + ttl)
+            .execute(&self.pool)
+            .await?;
+        Ok(token)
+    }
+
+    /// Counts an attempt against a live challenge and returns its user, or
+    /// `None` when the challenge is unknown, expired, consumed or out of
+    /// attempts.
+    pub async fn attempt_challenge(&self, token: &str) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar(
+            "UPDATE mfa_challenges SET attempts = attempts + 1
+             WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > now() AND attempts < $2
+             RETURNING user_id",
+        )
+        .bind(sha256_hex(token))
+        .bind(MAX_CHALLENGE_ATTEMPTS)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Marks a challenge used. Returns `false` if it was consumed already.
+    pub async fn consume_challenge(&self, token: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE mfa_challenges SET consumed_at = now() WHERE token_hash = $1 AND consumed_at IS NULL",
+        )
+        .bind(sha256_hex(token))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+async fn replace_recovery_codes(
+    tx: &mut sqlx::PgConnection,
+    user_id: &str,
+    count: usize,
+) -> Result<Vec<Secret<String>>> {
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let codes: Vec<Secret<String>> = (0..count)
+        .map(|_| Secret::new(generate_random_token(RECOVERY_CODE_LENGTH)))
+        .collect();
+    let hashes: Vec<String> = codes.iter().map(|code| sha256_hex(code.expose())).collect();
+    sqlx::query(
+        "INSERT INTO mfa_recovery_codes (code_hash, user_id)
+         SELECT hash, $2 FROM unnest($1::text[]) AS hash",
+    )
+    .bind(&hashes)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    Ok(codes)
+}
@@ -0,0 +1,17 @@
+pub mod api_keys;
+pub mod audit;
+pub mod encryption;
+pub mod identities;
+pub mod jwt;
+pub mod lockout;
+pub mod mfa;
+pub mod network;
+pub mod oidc;
+pub mod rate_limit;
+pub mod rbac;
+pub mod refresh_tokens;
+pub mod request_signing;
+pub mod roles;
+pub mod sessions;
+pub mod signing_keys;
+pub mod totp;
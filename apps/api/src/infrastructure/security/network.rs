@@ -0,0 +1,260 @@
+use anyhow::Result;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use ipnet::{IpNet, Ipv4Net};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::infrastructure::database::connection::DatabasePool;
+
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+const POLICY_COLUMNS: &str =
+    "name, description, allow, deny, route_groups, trust_forwarded_for, enabled, created_at, updated_at, updated_by";
+
+/// A CIDR range, or a single address as its host route. IPv6 may be
+/// written in full, compressed (`2001:db8::/32`) or with an embedded IPv4
+/// address. IPv4-mapped ranges (`::ffff:10.0.0.0/104`) become the IPv4
+/// ranges they stand for, as client addresses are matched in that form.
+pub fn parse_network(value: &str) -> Option<IpNet> {
+    let value = value.trim();
+    let network = value
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| value.parse::<IpAddr>().ok().map(IpNet::from))?;
+    match network {
+        IpNet::V6(v6) if v6.prefix_len() >= 96 => match v6.addr().to_ipv4_mapped() {
+            Some(v4) => Ipv4Net::new(v4, v6.prefix_len() - 96).ok().map(IpNet::V4),
+            None => Some(network),
+        },
+        network => Some(network),
+    }
+}
+
+/// The address a request originates from. Each trusted proxy appends the
+/// address it received the request from to `X-Forwarded-For`, so the
+/// header is read right to left from the peer until an address outside
+/// `trusted_proxies`; anything further left is client-supplied and ignored.
+pub fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpNet]) -> IpAddr {
+    // Dual-stack listeners report IPv4 peers as `::ffff:a.b.c.d`.
+    let peer = peer.to_canonical();
+    let trusted = |ip: &IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+    if !trusted(&peer) {
+        return peer;
+    }
+
+    let mut client = peer;
+    let hops = headers
+        .get_all(FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+    for hop in hops.into_iter().rev() {
+        // A hop that does not parse was not written by a proxy we trust.
+        let Some(ip) = parse_hop(hop) else {
+            break;
+        };
+        client = ip;
+        if !trusted(&ip) {
+            break;
+        }
+    }
+    client
+}
+
+/// Accepts `1.2.3.4`, `1.2.3.4:5678`, `2001:db8::1` and `[2001:db8::1]:5678`.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim();
+    hop.parse::<IpAddr>()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .map(|ip| ip.to_canonical())
+}
+
+/// A named [`NetworkPolicySpec`] as stored.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct NetworkPolicy {
+    pub name: String,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub spec: NetworkPolicySpec,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<String>,
+}
+
+/// Where requests to the route groups a policy is attached to may come
+/// from. Denied ranges win over allowed ones; an empty `allow` admits any
+/// address not denied.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct NetworkPolicySpec {
+    pub description: Option<String>,
+    pub allow: Vec<IpNet>,
+    pub deny: Vec<IpNet>,
+    pub route_groups: Vec<String>,
+    /// Judge the client address resolved through `app.trusted_proxies`
+    /// rather than the connecting peer.
+    pub trust_forwarded_for: bool,
+    pub enabled: bool,
+}
+
+impl NetworkPolicySpec {
+    pub fn guards(&self, group: &str) -> bool {
+        self.enabled && self.route_groups.iter().any(|g| g == group)
+    }
+
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        let matches = |networks: &[IpNet]| networks.iter().any(|network| network.contains(&ip));
+        !matches(&self.deny) && (self.allow.is_empty() || matches(&self.allow))
+    }
+
+    /// The address this policy judges a request by.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpNet]) -> IpAddr {
+        if self.trust_forwarded_for {
+            client_ip(peer, headers, trusted_proxies)
+        } else {
+            peer.to_canonical()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkPolicyStore {
+    pool: DatabasePool,
+}
+
+impl NetworkPolicyStore {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<NetworkPolicy>> {
+        Ok(
+            sqlx::query_as(&format!("SELECT {} FROM network_policies ORDER BY name", POLICY_COLUMNS))
+                .fetch_all(self.pool.read())
+                .await?,
+        )
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Option<NetworkPolicy>> {
+        Ok(
+            sqlx::query_as(&format!("SELECT {} FROM network_policies WHERE name = $1", POLICY_COLUMNS))
+                .bind(name)
+                .fetch_optional(self.pool.read())
+                .await?,
+        )
+    }
+
+    /// Creates or replaces the policy called `name`.
+    pub async fn save(&self, name: &str, spec: &NetworkPolicySpec, updated_by: Option<&str>) -> Result<NetworkPolicy> {
+        // CIDR columns reject host bits, so 10.1.2.3/8 is stored as 10.0.0.0/8.
+        let trunc = |networks: &[IpNet]| networks.iter().map(IpNet::trunc).collect::<Vec<_>>();
+        Ok(sqlx::query_as(&format!(
+            "INSERT INTO network_policies
+                 (name, description, allow, deny, route_groups, trust_forwarded_for, enabled, updated_by)
+             VALUES ($1, $2, $3::cidr[], $4::cidr[], $5, $6, $7, $8)
+             ON CONFLICT (name) DO UPDATE
+                 SET description = EXCLUDED.description,
+                     allow = EXCLUDED.allow,
+                     deny = EXCLUDED.deny,
+                     route_groups = EXCLUDED.route_groups,
+                     trust_forwarded_for = EXCLUDED.trust_forwarded_for,
+                     enabled = EXCLUDED.enabled,
+                     updated_at = now(),
+                     updated_by = EXCLUDED.updated_by
+             RETURNING {}",
+            POLICY_COLUMNS
+        ))
+        .bind(name)
+        .bind(&spec.description)
+        .bind(trunc(&spec.allow))
+        .bind(trunc(&spec.deny))
+        .bind(&spec.route_groups)
+        .bind(spec.trust_forwarded_for)
+        .bind(spec.enabled)
+        .bind(updated_by)
+        .fetch_one(self.pool.write())
+        .await?)
+    }
+
+    pub async fn set_enabled(&self, name: &str, enabled: bool, updated_by: Option<&str>) -> Result<Option<NetworkPolicy>> {
+        Ok(sqlx::query_as(&format!(
+            "UPDATE network_policies SET enabled = $2, updated_at = now(), updated_by = $3
+             WHERE name = $1
+             RETURNING {}",
+            POLICY_COLUMNS
+        ))
+        .bind(name)
+        .bind(enabled)
+        .bind(updated_by)
+        .fetch_optional(self.pool.write())
+        .await?)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<Option<NetworkPolicy>> {
+        Ok(
+            sqlx::query_as(&format!("DELETE FROM network_policies WHERE name = $1 RETURNING {}", POLICY_COLUMNS))
+                .bind(name)
+                .fetch_optional(self.pool.write())
+                .await?,
+        )
+    }
+}
+
+/// The policies in force, held in memory so checking a request costs no
+/// query. Changes made through this instance apply at once; those made
+/// elsewhere (another instance, the cli) on the next refresh.
+#[derive(Clone)]
+pub struct NetworkPolicies {
+    store: NetworkPolicyStore,
+    policies: Arc<RwLock<Arc<[NetworkPolicy]>>>,
+}
+
+impl NetworkPolicies {
+    pub async fn load(store: NetworkPolicyStore) -> Result<Self> {
+        let policies = Self {
+            store,
+            policies: Arc::new(RwLock::new(Arc::from(Vec::new()))),
+        };
+        policies.reload().await?;
+        Ok(policies)
+    }
+
+    pub fn store(&self) -> &NetworkPolicyStore {
+        &self.store
+    }
+
+    pub async fn reload(&self) -> Result<()> {
+        let policies = self.store.list().await?;
+        *self.policies.write().unwrap_or_else(|e| e.into_inner()) = policies.into();
+        Ok(())
+    }
+
+    pub fn spawn_refresh(&self, interval: Duration) -> JoinHandle<()> {
+        let policies = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = policies.reload().await {
+                    warn!("Failed to reload network policies: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Enabled policies attached to `group`, all of which must allow a
+    /// request.
+    pub fn for_group(&self, group: &str) -> Vec<NetworkPolicy> {
+        let policies = self.policies.read().unwrap_or_else(|e| e.into_inner());
+        policies.iter().filter(|policy| policy.spec.guards(group)).cloned().collect()
+    }
+}
@@ -0,0 +1,405 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::common::errors::AppError;
+use crate::config::auth::{OidcConfig, OidcProviderConfig};
+
+/// Discovery documents rarely change; refetch them hourly.
+const METADATA_TTL: Duration = Duration::from_secs(3600);
+/// An unknown `kid` triggers a JWKS refetch at most this often, so tokens
+/// with made-up key ids cannot be used to hammer the provider.
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(60);
+const CLOCK_LEEWAY_SECS: u64 = 60;
+
+/// Algorithms accepted on ID tokens. Symmetric algorithms are excluded:
+/// they would make the client secret a signing key.
+const ALLOWED_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// The subset of the provider's discovery document we use.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub jwks_uri: Url,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+/// Who the provider says signed in, taken from a validated ID token.
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    azp: Option<String>,
+    #[serde(default)]
+    aud: serde_json::Value,
+    email: Option<String>,
+    /// Some providers send this as the string `"true"`.
+    email_verified: Option<serde_json::Value>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// A public summary of a configured provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub display_name: String,
+}
+
+/// `S256` PKCE challenge for `verifier` (RFC 7636).
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+struct Cached<T> {
+    value: Arc<T>,
+    fetched_at: Instant,
+}
+
+/// Talks to one OpenID Connect provider: discovery, the authorization
+/// redirect, the code exchange and ID token validation.
+pub struct OidcProvider {
+    config: OidcProviderConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<Cached<ProviderMetadata>>>,
+    jwks: RwLock<Option<Cached<JwkSet>>>,
+}
+
+impl OidcProvider {
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    pub fn config(&self) -> &OidcProviderConfig {
+        &self.config
+    }
+
+    pub fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            name: self.config.name.clone(),
+            display_name: self.config.display_name.clone(),
+        }
+    }
+
+    /// Where to send the browser to start signing in.
+    pub async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<Url, AppError> {
+        let metadata = self.metadata().await?;
+        let mut url = metadata.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", self.config.redirect_uri.as_str())
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+        Ok(url)
+    }
+
+    /// Redeems an authorization code and returns the raw ID token.
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<String, AppError> {
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+
+        let mut request = self.http.post(metadata.token_endpoint.clone());
+        match &self.config.client_secret {
+            // `client_secret_basic` is the default when the provider does
+            // not list its methods.
+            Some(secret)
+                if metadata.token_endpoint_auth_methods_supported.is_empty()
+                    || metadata
+                        .token_endpoint_auth_methods_supported
+                        .iter()
+                        .any(|method| method == "client_secret_basic") =>
+            {
+                request = request.basic_auth(form_encode(&self.config.client_id), Some(form_encode(secret.expose())));
+            }
+            Some(secret) => {
+                form.push(("client_id", &self.config.client_id));
+                form.push(("client_secret", secret.expose()));
+            }
+            None => form.push(("client_id", &self.config.client_id)),
+        }
+
+        let response = request
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| self.unavailable(e))?;
+        let status = response.status();
+        if status.is_client_error() {
+            // RFC 6749 §5.2: a bad, expired or reused code is `invalid_grant`.
+            let reason = match response.json::<TokenErrorResponse>().await {
+                Ok(body) => body.error_description.unwrap_or(body.error),
+                Err(_) => status.to_string(),
+            };
+            return Err(AppError::Authentication(format!(
+                "{} rejected the authorization code: {}",
+                self.config.display_name, reason
+            )));
+        }
+        let body: TokenResponse = self.read_json(response).await?;
+        body.id_token.ok_or_else(|| {
+            AppError::external_service_error(self.service(), "token response has no id_token", Some(status.as_u16()))
+        })
+    }
+
+    /// Validates signature, issuer, audience, expiry and `nonce` of an ID
+    /// token (OIDC Core §3.1.3.7).
+    pub async fn validate_id_token(&self, id_token: &str, nonce: &str) -> Result<ExternalIdentity, AppError> {
+        let header = decode_header(id_token).map_err(|_| invalid("malformed id token"))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(invalid("id token uses an unsupported algorithm"));
+        }
+        let key = self.decoding_key(header.kid.as_deref()).await?;
+        let metadata = self.metadata().await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = CLOCK_LEEWAY_SECS;
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => invalid("id token expired"),
+                _ => invalid("invalid id token"),
+            })?;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid("id token nonce does not match"));
+        }
+        let audiences = claims.aud.as_array().map_or(1, Vec::len);
+        if audiences > 1 && claims.azp.as_deref() != Some(self.config.client_id.as_str()) {
+            return Err(invalid("id token was issued to another client"));
+        }
+
+        let email_verified = match claims.email_verified {
+            Some(serde_json::Value::Bool(verified)) => verified,
+            Some(serde_json::Value::String(value)) => value.eq_ignore_ascii_case("true"),
+            _ => false,
+        };
+        Ok(ExternalIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified,
+            name: claims.name,
+        })
+    }
+
+    async fn metadata(&self) -> Result<Arc<ProviderMetadata>, AppError> {
+        if let Some(cached) = self.metadata.read().await.as_ref() {
+            if cached.fetched_at.elapsed() < METADATA_TTL {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let mut slot = self.metadata.write().await;
+        if let Some(cached) = slot.as_ref() {
+            if cached.fetched_at.elapsed() < METADATA_TTL {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let issuer = self.config.issuer.as_str().trim_end_matches('/');
+        let url = format!("{}/.well-known/openid-configuration", issuer);
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+        // OIDC Discovery §4.3: the document must be for the issuer we asked.
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(AppError::external_service_error(
+                self.service(),
+                format!("discovery document is for issuer `{}`", metadata.issuer),
+                None,
+            ));
+        }
+
+        let metadata = Arc::new(metadata);
+        *slot = Some(Cached {
+            value: metadata.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(metadata)
+    }
+
+    async fn decoding_key(&self, kid: Option<&str>) -> Result<DecodingKey, AppError> {
+        let stale = {
+            let jwks = self.jwks.read().await;
+            match jwks.as_ref() {
+                Some(cached) => match find_key(&cached.value, kid) {
+                    Some(key) => return key,
+                    None => cached.fetched_at.elapsed() >= JWKS_MIN_REFRESH,
+                },
+                None => true,
+            }
+        };
+        if !stale {
+            return Err(invalid("id token is signed with an unknown key"));
+        }
+
+        let mut slot = self.jwks.write().await;
+        // Another request may have refreshed while we waited for the lock.
+        if let Some(cached) = slot.as_ref() {
+            if cached.fetched_at.elapsed() < JWKS_MIN_REFRESH {
+                return find_key(&cached.value, kid).unwrap_or_else(|| Err(invalid("id token is signed with an unknown key")));
+            }
+        }
+
+        let metadata = self.metadata().await?;
+        let jwks: JwkSet = self.get_json(metadata.jwks_uri.as_str()).await?;
+        let key = find_key(&jwks, kid);
+        *slot = Some(Cached {
+            value: Arc::new(jwks),
+            fetched_at: Instant::now(),
+        });
+        key.unwrap_or_else(|| Err(invalid("id token is signed with an unknown key")))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        let response = self
+            .http
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| self.unavailable(e))?;
+        self.read_json(response).await
+    }
+
+    async fn read_json<T: DeserializeOwned>(&self, response: reqwest::Response) -> Result<T, AppError> {
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::external_service_error(
+                self.service(),
+                format!("{} returned {}", response.url(), status),
+                Some(status.as_u16()),
+            ));
+        }
+        response.json().await.map_err(|e| {
+            AppError::external_service_error(self.service(), format!("unexpected response: {}", e), Some(status.as_u16()))
+        })
+    }
+
+    fn unavailable(&self, error: reqwest::Error) -> AppError {
+        AppError::external_service_error(self.service(), error.without_url().to_string(), None)
+    }
+
+    fn service(&self) -> String {
+        format!("oidc:{}", self.config.name)
+    }
+}
+
+/// The key named by `kid`, or the only key when the token names none.
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<Result<DecodingKey, AppError>> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid)?,
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => return None,
+    };
+    Some(DecodingKey::from_jwk(jwk).map_err(|_| invalid("provider published an unusable key")))
+}
+
+/// `client_secret_basic` form-encodes the credentials before base64
+/// (RFC 6749 §2.3.1).
+fn form_encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::Authentication(reason.to_string())
+}
+
+/// The configured providers, by name.
+#[derive(Clone)]
+pub struct OidcProviders {
+    providers: Arc<HashMap<String, Arc<OidcProvider>>>,
+    order: Arc<Vec<String>>,
+}
+
+impl OidcProviders {
+    pub fn new(config: &OidcConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.http_timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let providers = config
+            .providers
+            .iter()
+            .map(|provider| {
+                let provider = OidcProvider {
+                    config: provider.clone(),
+                    http: http.clone(),
+                    metadata: RwLock::new(None),
+                    jwks: RwLock::new(None),
+                };
+                (provider.config.name.clone(), Arc::new(provider))
+            })
+            .collect();
+        Ok(Self {
+            providers: Arc::new(providers),
+            order: Arc::new(config.providers.iter().map(|provider| provider.name.clone()).collect()),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<OidcProvider>, AppError> {
+        self.providers
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("identity provider `{}`", name)))
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.order
+            .iter()
+            .filter_map(|name| self.providers.get(name))
+            .map(|provider| provider.info())
+            .collect()
+    }
+}
@@ -0,0 +1,290 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::app::StoreKind;
+use crate::config::runtime::RateLimitQuota;
+
+/// Expired buckets are dropped once every this many checks.
+const PRUNE_EVERY: u64 = 1024;
+
+/// Per-key state for GCRA: each key has a theoretical arrival time (TAT)
+/// that every admitted request pushes back by one emission interval. A
+/// request is admitted while the TAT stays within `capacity` of now.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Admits a request for `key` if its TAT allows, returning how far the
+    /// TAT is ahead of now afterwards (or, when rejected, as it stands).
+    async fn acquire(&self, key: &str, emission: Duration, capacity: Duration) -> Result<Acquired>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Acquired {
+    pub admitted: bool,
+    pub ahead: Duration,
+}
+
+/// The outcome of a check, with what the `RateLimit-*` headers report.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    /// Requests that may arrive at once: the quota's burst.
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the full burst is available again.
+    pub reset_secs: u64,
+    /// Seconds until the next request will be admitted; zero when allowed.
+    pub retry_after_secs: u64,
+}
+
+/// Token-bucket rate limiting in its GCRA form over a [`RateLimitStore`].
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
+        Self { store }
+    }
+
+    pub fn from_config(kind: StoreKind, pool: PgPool) -> Self {
+        match kind {
+            StoreKind::Memory => Self::new(Arc::new(MemoryRateLimitStore::default())),
+            StoreKind::Postgres => Self::new(Arc::new(PgRateLimitStore::new(pool))),
+        }
+    }
+
+    pub async fn check(&self, key: &str, quota: &RateLimitQuota) -> Result<Decision> {
+        let emission = Duration::from_secs(quota.window_secs) / quota.max_requests.max(1);
+        let capacity = emission * quota.burst.max(1);
+        let acquired = self.store.acquire(key, emission, capacity).await?;
+
+        let spare = capacity.saturating_sub(acquired.ahead);
+        let remaining = if acquired.admitted {
+            (spare.as_micros() / emission.as_micros().max(1)) as u32
+        } else {
+            0
+        };
+        // When rejected, the next request fits once the TAT is one
+        // emission interval closer than `capacity`.
+        let retry_after = (acquired.ahead + emission).saturating_sub(capacity);
+        Ok(Decision {
+            allowed: acquired.admitted,
+            limit: quota.burst,
+            remaining,
+            reset_secs: ceil_secs(acquired.ahead),
+            retry_after_secs: if acquired.admitted { 0 } else { ceil_secs(retry_after).max(1) },
+        })
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+/// Buckets in this process only; each instance limits independently.
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Instant>>,
+    checks: AtomicU64,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(&self, key: &str, emission: Duration, capacity: Duration) -> Result<Acquired> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if self.checks.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+            buckets.retain(|_, tat| *tat > now);
+        }
+
+        let tat = buckets.get(key).copied().filter(|tat| *tat > now).unwrap_or(now);
+        let next = tat + emission;
+        if next - now > capacity {
+            return Ok(Acquired {
+                admitted: false,
+                ahead: tat - now,
+            });
+        }
+        buckets.insert(key.to_string(), next);
+        Ok(Acquired {
+            admitted: true,
+            ahead: next - now,
+        })
+    }
+}
+
+/// Buckets shared by every instance in the `rate_limit_buckets` table,
+/// timed by the database clock so instance clocks need not agree.
+pub struct PgRateLimitStore {
+    pool: PgPool,
+    checks: AtomicU64,
+}
+
+impl PgRateLimitStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            checks: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PgRateLimitStore {
+    async fn acquire(&self, key: &str, emission: Duration, capacity: Duration) -> Result<Acquired> {
+        if self.checks.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+            sqlx::query("DELETE FROM rate_limit_buckets WHERE tat < now()")
+                .execute(&self.pool)
+                .await?;
+        }
+
+        let emission_us = emission.as_micros() as i64;
+        let capacity_us = capacity.as_micros() as i64;
+        // The conditional upsert admits and advances the TAT atomically; no
+        // row back means the bucket was full.
+        let admitted: Option<i64> = sqlx::query_scalar(
+            "INSERT INTO rate_limit_buckets AS b (key, tat)
+             VALUES ($1, now() + $2 * interval '1 microsecond')
+             ON CONFLICT (key) DO UPDATE
+                 SET tat = GREATEST(b.tat, now()) + $2 * interval '1 microsecond'
+                 WHERE GREATEST(b.tat, now()) + $2 * interval '1 microsecond'
+                       <= now() + $3 * interval '1 microsecond'
+             RETURNING (EXTRACT(EPOCH FROM b.tat - now()) * 1000000)::bigint",
+        )
+        .bind(key)
+        .bind(emission_us)
+        .bind(capacity_us)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(ahead_us) = admitted {
+            return Ok(Acquired {
+                admitted: true,
+                ahead: Duration::from_micros(ahead_us.max(0) as u64),
+            });
+        }
+
+        let ahead_us: Option<i64> = sqlx::query_scalar(
+            "SELECT (EXTRACT(EPOCH FROM tat - now()) * 1000000)::bigint FROM rate_limit_buckets WHERE key = $1",
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(Acquired {
+            admitted: false,
+            ahead: Duration::from_micros(ahead_us.unwrap_or(capacity_us).max(0) as u64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(window_secs: u64, max_requests: u32, burst: u32) -> RateLimitQuota {
+        RateLimitQuota {
+            enabled: true,
+            window_secs,
+            max_requests,
+            burst,
+        }
+    }
+
+    fn memory_limiter() -> RateLimiter {
+        RateLimiter::new(Arc::new(MemoryRateLimitStore::default()))
+    }
+
+    /// Reports a fixed TAT so the header arithmetic can be checked exactly.
+    struct FixedStore(Acquired);
+
+    #[async_trait]
+    impl RateLimitStore for FixedStore {
+        async fn acquire(&self, _key: &str, _emission: Duration, _capacity: Duration) -> Result<Acquired> {
+            Ok(self.0)
+        }
+    }
+
+    async fn decide(admitted: bool, ahead: Duration, quota: &RateLimitQuota) -> Decision {
+        RateLimiter::new(Arc::new(FixedStore(Acquired { admitted, ahead })))
+            .check("key", quota)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn admits_the_burst_then_rejects() {
+        let limiter = memory_limiter();
+        let quota = quota(60, 60, 3);
+
+        let mut remaining = Vec::new();
+        for _ in 0..3 {
+            let decision = limiter.check("client", &quota).await.unwrap();
+            assert!(decision.allowed);
+            assert_eq!(decision.limit, 3);
+            assert_eq!(decision.retry_after_secs, 0);
+            remaining.push(decision.remaining);
+        }
+        assert_eq!(remaining, [2, 1, 0]);
+
+        let rejected = limiter.check("client", &quota).await.unwrap();
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert_eq!(rejected.retry_after_secs, 1);
+        assert_eq!(rejected.reset_secs, 3);
+    }
+
+    #[tokio::test]
+    async fn keys_have_separate_buckets() {
+        let limiter = memory_limiter();
+        let quota = quota(60, 1, 1);
+
+        assert!(limiter.check("a", &quota).await.unwrap().allowed);
+        assert!(!limiter.check("a", &quota).await.unwrap().allowed);
+        assert!(limiter.check("b", &quota).await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn retry_after_waits_for_one_emission_interval_of_room() {
+        // One request per 30 seconds, no burst: the next fits once the TAT
+        // is within 30 seconds of now again.
+        let limiter = memory_limiter();
+        let quota = quota(60, 2, 1);
+        assert!(limiter.check("client", &quota).await.unwrap().allowed);
+        let rejected = limiter.check("client", &quota).await.unwrap();
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.retry_after_secs, 30);
+    }
+
+    #[tokio::test]
+    async fn retry_after_rounds_up_and_is_at_least_one_second() {
+        // Emission 1s, capacity 3s.
+        let quota = quota(10, 10, 3);
+
+        let full = decide(false, Duration::from_secs(3), &quota).await;
+        assert_eq!(full.retry_after_secs, 1);
+
+        let partly_drained = decide(false, Duration::from_millis(2_500), &quota).await;
+        assert_eq!(partly_drained.retry_after_secs, 1);
+        assert_eq!(partly_drained.reset_secs, 3);
+
+        let backlogged = decide(false, Duration::from_millis(5_200), &quota).await;
+        assert_eq!(backlogged.retry_after_secs, 4);
+        assert_eq!(backlogged.reset_secs, 6);
+    }
+
+    #[tokio::test]
+    async fn remaining_counts_whole_emission_intervals_of_spare_capacity() {
+        let quota = quota(10, 10, 5);
+
+        let decision = decide(true, Duration::from_millis(2_500), &quota).await;
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 2);
+        assert_eq!(decision.reset_secs, 3);
+        assert_eq!(decision.retry_after_secs, 0);
+    }
+}
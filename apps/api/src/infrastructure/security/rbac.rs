@@ -0,0 +1,102 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::BTreeSet;
+
+/// Permission names used by route and resolver guards. Each must exist in
+/// the `permissions` table.
+pub mod permissions {
+    pub const ALL: &str = "*";
+    pub const USERS_READ: &str = "users:read";
+    pub const USERS_WRITE: &str = "users:write";
+    pub const ROLES_MANAGE: &str = "roles:manage";
+    pub const API_KEYS_MANAGE: &str = "api_keys:manage";
+    pub const SESSIONS_MANAGE: &str = "sessions:manage";
+    pub const AUDIT_READ: &str = "audit:read";
+    pub const NETWORK_MANAGE: &str = "network:manage";
+    pub const ASSETS_READ: &str = "assets:read";
+    pub const ASSETS_WRITE: &str = "assets:write";
+    pub const INGEST_ASSETS: &str = "ingest:assets";
+    pub const INGEST_MACRO: &str = "ingest:macro";
+    pub const INGEST_SENTIMENT: &str = "ingest:sentiment";
+}
+
+/// Held implicitly by every active user, whether or not it was granted.
+pub const DEFAULT_ROLE: &str = "user";
+
+/// What an active user may do right now.
+#[derive(Debug, Clone, Default)]
+pub struct Grants {
+    pub roles: Vec<String>,
+    pub permissions: BTreeSet<String>,
+    /// Roles the user holds that were left out of `permissions`.
+    pub withheld: Vec<String>,
+}
+
+pub fn allows(permissions: &BTreeSet<String>, permission: &str) -> bool {
+    permissions.contains(permissions::ALL) || permissions.contains(permission)
+}
+
+/// Resolves roles and permissions from the role tables. Evaluated on every
+/// request, so grants and revocations apply to live tokens immediately.
+#[derive(Clone)]
+pub struct RbacStore {
+    pool: PgPool,
+}
+
+impl RbacStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns `None` when the user does not exist or is disabled. Roles in
+    /// `withhold` are reported but contribute no permissions.
+    pub async fn grants_for(&self, user_id: &str, withhold: &[String]) -> Result<Option<Grants>> {
+        let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT r.role, rp.permission
+             FROM users u
+             CROSS JOIN LATERAL (
+                 SELECT role FROM user_roles WHERE user_id = u.id
+                 UNION SELECT $2
+             ) r
+             LEFT JOIN role_permissions rp ON rp.role = r.role
+             WHERE u.id = $1 AND u.is_active",
+        )
+        .bind(user_id)
+        .bind(DEFAULT_ROLE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut grants = Grants::default();
+        for (role, permission) in rows {
+            if withhold.contains(&role) {
+                if !grants.withheld.contains(&role) {
+                    grants.withheld.push(role);
+                }
+                continue;
+            }
+            if !grants.roles.contains(&role) {
+                grants.roles.push(role);
+            }
+            grants.permissions.extend(permission);
+        }
+        grants.roles.sort();
+        grants.withheld.sort();
+        Ok(Some(grants))
+    }
+
+    /// Every role with its permissions, for listings.
+    pub async fn role_permissions(&self) -> Result<Vec<(String, Vec<String>)>> {
+        Ok(sqlx::query_as(
+            "SELECT r.name, COALESCE(array_agg(rp.permission ORDER BY rp.permission)
+                                      FILTER (WHERE rp.permission IS NOT NULL), '{}')
+             FROM roles r LEFT JOIN role_permissions rp ON rp.role = r.name
+             GROUP BY r.name ORDER BY r.name",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+
+use super::sessions::{ClientInfo, Session, SESSION_COLUMNS};
+use crate::common::security::{generate_random_token, sha256_hex};
+use crate::config::Secret;
+
+const REFRESH_TOKEN_LENGTH: usize = 48;
+
+pub const REUSE_REVOKE_REASON: &str = "refresh_token_reuse";
+
+#[derive(Debug)]
+pub struct IssuedRefreshToken {
+    pub session: Session,
+    pub token: Secret<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub enum RefreshOutcome {
+    Rotated(IssuedRefreshToken),
+    /// The token had already been exchanged; its whole family is now revoked.
+    Reused { session_id: String, user_id: String },
+    /// Unknown, expired, or belonging to a revoked or expired session.
+    Rejected,
+}
+
+/// Opaque refresh tokens stored as SHA-256 hashes. A login creates a session
+/// (token family); each refresh consumes the presented token and issues its
+/// successor in the same family.
+#[derive(Clone)]
+pub struct RefreshTokenStore {
+    pool: PgPool,
+}
+
+impl RefreshTokenStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn start_session(
+        &self,
+        user_id: &str,
+        ttl: Duration,
+        mfa_verified: bool,
+        client: &ClientInfo,
+    ) -> Result<IssuedRefreshToken> {
+        let mut tx = self.pool.begin().await?;
+        let session: Session = sqlx::query_as(&format!(
+            "INSERT INTO auth_sessions (id, user_id, expires_at, mfa_verified, device_label, user_agent, ip_address)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING {}",
+            SESSION_COLUMNS
+        ))
+        .bind(cuid::cuid2())
+        .bind(user_id)
+        .bind(Utc::now() + ttl)
+        .bind(mfa_verified)
+        .bind(&client.device_label)
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (token, expires_at) = insert_token(&mut tx, &session.id, session.expires_at).await?;
+        tx.commit().await?;
+        Ok(IssuedRefreshToken {
+            session,
+            token,
+            expires_at,
+        })
+    }
+
+    /// Exchanges `token` for a new one. Marking the old token used is a
+    /// single conditional update, so two concurrent refreshes with the same
+    /// token cannot both succeed. The session's device details are updated
+    /// from `client`; a label is only replaced when one is sent.
+    pub async fn rotate(&self, token: &str, client: &ClientInfo) -> Result<RefreshOutcome> {
+        let token_hash = sha256_hex(token);
+        let mut tx = self.pool.begin().await?;
+
+        let consumed: Option<String> = sqlx::query_scalar(
+            "UPDATE refresh_tokens SET used_at = now()
+             WHERE token_hash = $1 AND used_at IS NULL AND expires_at > now()
+             RETURNING session_id",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(session_id) = consumed else {
+            let reused: Option<(String, String)> = sqlx::query_as(
+                "SELECT s.id, s.user_id FROM refresh_tokens t
+                 JOIN auth_sessions s ON s.id = t.session_id
+                 WHERE t.token_hash = $1 AND t.used_at IS NOT NULL",
+            )
+            .bind(&token_hash)
+            .fetch_optional(&mut *tx)
+            .await?;
+            tx.rollback().await?;
+
+            let Some((session_id, user_id)) = reused else {
+                return Ok(RefreshOutcome::Rejected);
+            };
+            sqlx::query(
+                "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = $2
+                 WHERE id = $1 AND revoked_at IS NULL",
+            )
+            .bind(&session_id)
+            .bind(REUSE_REVOKE_REASON)
+            .execute(&self.pool)
+            .await?;
+            warn!(session_id = %session_id, user_id = %user_id, "Refresh token reuse detected; session revoked");
+            return Ok(RefreshOutcome::Reused { session_id, user_id });
+        };
+
+        let session: Option<Session> = sqlx::query_as(&format!(
+            "UPDATE auth_sessions SET last_seen_at = now(), device_label = COALESCE($2, device_label),
+                 user_agent = COALESCE($3, user_agent), ip_address = COALESCE($4, ip_address)
+             WHERE id = $1 AND revoked_at IS NULL AND expires_at > now()
+             RETURNING {}",
+            SESSION_COLUMNS
+        ))
+        .bind(&session_id)
+        .bind(&client.device_label)
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(session) = session else {
+            tx.rollback().await?;
+            return Ok(RefreshOutcome::Rejected);
+        };
+
+        let (token, expires_at) = insert_token(&mut tx, &session.id, session.expires_at).await?;
+        tx.commit().await?;
+        Ok(RefreshOutcome::Rotated(IssuedRefreshToken {
+            session,
+            token,
+            expires_at,
+        }))
+    }
+
+    /// Ends the session `token` belongs to. Returns the session id, or `None`
+    /// for an unknown token.
+    pub async fn revoke_session(&self, token: &str, reason: &str) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar(
+            "UPDATE auth_sessions SET revoked_at = COALESCE(revoked_at, now()),
+                 revoked_reason = COALESCE(revoked_reason, $2)
+             WHERE id = (SELECT session_id FROM refresh_tokens WHERE token_hash = $1)
+             RETURNING id",
+        )
+        .bind(sha256_hex(token))
+        .bind(reason)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+}
+
+/// New tokens never outlive their session.
+async fn insert_token(
+    tx: &mut sqlx::PgConnection,
+    session_id: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(Secret<String>, DateTime<Utc>)> {
+    let token = Secret::new(generate_random_token(REFRESH_TOKEN_LENGTH));
+    sqlx::query("INSERT INTO refresh_tokens (token_hash, session_id, expires_at) VALUES ($1, $2, $3)")
+        .bind(sha256_hex(token.expose()))
+        .bind(session_id)
+        .bind(expires_at)
+        .execute(tx)
+        .await?;
+    Ok((token, expires_at))
+}
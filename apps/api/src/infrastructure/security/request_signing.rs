@@ -0,0 +1,473 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use crate::common::errors::AppError;
+use crate::config::app::StoreKind;
+use crate::config::auth::RequestSigningConfig;
+use crate::config::Secret;
+
+pub const KEY_ID_HEADER: &str = "x-m5-key-id";
+pub const TIMESTAMP_HEADER: &str = "x-m5-timestamp";
+pub const NONCE_HEADER: &str = "x-m5-nonce";
+pub const SIGNATURE_HEADER: &str = "x-m5-signature";
+
+const MIN_NONCE_LENGTH: usize = 16;
+const MAX_NONCE_LENGTH: usize = 128;
+
+/// Expired nonces are deleted from Postgres once every this many inserts.
+const PRUNE_EVERY: u64 = 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Why a signed request was refused. Surfaced to the client as the
+/// reason of an `AppError::Authentication`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    Missing(&'static str),
+    Malformed(&'static str),
+    UnknownKey,
+    StaleTimestamp,
+    ReplayedNonce,
+    BodyTooLarge,
+    Mismatch,
+    Required,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Missing(header) => write!(f, "missing `{}` header", header),
+            SignatureError::Malformed(header) => write!(f, "malformed `{}` header", header),
+            SignatureError::UnknownKey => f.write_str("unknown signing key"),
+            SignatureError::StaleTimestamp => f.write_str("request timestamp is outside the allowed clock skew"),
+            SignatureError::ReplayedNonce => f.write_str("request nonce has already been used"),
+            SignatureError::BodyTooLarge => f.write_str("request body is too large to verify"),
+            SignatureError::Mismatch => f.write_str("request signature does not match"),
+            SignatureError::Required => f.write_str("requests made with an API key must be signed"),
+        }
+    }
+}
+
+impl From<SignatureError> for AppError {
+    fn from(error: SignatureError) -> Self {
+        AppError::Authentication(error.to_string())
+    }
+}
+
+/// Marks a request whose signature checked out.
+#[derive(Debug, Clone)]
+pub struct SignedRequest {
+    pub key_id: String,
+}
+
+/// The string a client signs:
+///
+/// ```text
+/// {METHOD}\n{path and query}\n{unix timestamp}\n{nonce}\n{hex sha256 of body}
+/// ```
+///
+/// The signature is the hex HMAC-SHA256 of it under the client's secret,
+/// sent in `X-M5-Signature` along with `X-M5-Key-Id`, `X-M5-Timestamp` and
+/// `X-M5-Nonce`.
+pub fn canonical_request(method: &str, path_and_query: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method.to_ascii_uppercase(),
+        path_and_query,
+        timestamp,
+        nonce,
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+pub fn sign(secret: &str, canonical: &str) -> String {
+    hex::encode(mac(secret, canonical).finalize().into_bytes())
+}
+
+fn mac(secret: &str, canonical: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(canonical.as_bytes());
+    mac
+}
+
+/// Checks request signatures against the configured client secrets.
+#[derive(Clone)]
+pub struct RequestVerifier {
+    secrets: Arc<HashMap<String, Secret<String>>>,
+    max_clock_skew_secs: i64,
+    require_for_api_keys: bool,
+    max_body_bytes: usize,
+    nonces: Arc<dyn NonceStore>,
+}
+
+impl RequestVerifier {
+    pub fn new(config: &RequestSigningConfig, pool: PgPool) -> Self {
+        let nonces: Arc<dyn NonceStore> = match config.nonce_store {
+            StoreKind::Memory => Arc::new(MemoryNonceStore::new(config.nonce_capacity)),
+            StoreKind::Postgres => Arc::new(PgNonceStore::new(pool)),
+        };
+        Self {
+            secrets: Arc::new(
+                config
+                    .clients
+                    .iter()
+                    .map(|client| (client.key_id.clone(), client.secret.clone()))
+                    .collect(),
+            ),
+            max_clock_skew_secs: config.max_clock_skew.as_secs() as i64,
+            require_for_api_keys: config.require_for_api_keys,
+            max_body_bytes: config.max_body_bytes,
+            nonces,
+        }
+    }
+
+    pub fn require_for_api_keys(&self) -> bool {
+        self.require_for_api_keys
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// Whether the request claims to be signed at all.
+    pub fn is_signed(headers: &HeaderMap) -> bool {
+        headers.contains_key(SIGNATURE_HEADER)
+    }
+
+    /// Verifies a signed request at unix time `now`. The nonce is only
+    /// recorded once the signature has matched, so forged requests cannot
+    /// burn a legitimate client's nonces. Fails closed when the nonce
+    /// store cannot be reached.
+    pub async fn verify(
+        &self,
+        method: &str,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+        now: i64,
+    ) -> Result<SignedRequest, AppError> {
+        let key_id = header(headers, KEY_ID_HEADER)?;
+        let timestamp: i64 = header(headers, TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| SignatureError::Malformed(TIMESTAMP_HEADER))?;
+        let nonce = header(headers, NONCE_HEADER)?;
+        if !(MIN_NONCE_LENGTH..=MAX_NONCE_LENGTH).contains(&nonce.len()) {
+            return Err(SignatureError::Malformed(NONCE_HEADER).into());
+        }
+        let signature = hex::decode(header(headers, SIGNATURE_HEADER)?)
+            .map_err(|_| SignatureError::Malformed(SIGNATURE_HEADER))?;
+
+        let secret = self.secrets.get(key_id).ok_or(SignatureError::UnknownKey)?;
+        // The timestamp is client input; `abs_diff` cannot overflow.
+        if now.abs_diff(timestamp) > self.max_clock_skew_secs as u64 {
+            return Err(SignatureError::StaleTimestamp.into());
+        }
+
+        // `verify_slice` compares in constant time.
+        let canonical = canonical_request(method, path_and_query, timestamp, nonce, body);
+        mac(secret.expose(), &canonical)
+            .verify_slice(&signature)
+            .map_err(|_| SignatureError::Mismatch)?;
+
+        // A nonce only has to be remembered while its timestamp is still
+        // acceptable; after that the skew check rejects the replay.
+        let remember_until = timestamp
+            .checked_add(self.max_clock_skew_secs)
+            .ok_or(SignatureError::StaleTimestamp)?;
+        let recorded = self
+            .nonces
+            .insert(&format!("{}:{}", key_id, nonce), remember_until, now)
+            .await
+            .map_err(|e| AppError::Internal(e.context("failed to record request nonce")))?;
+        match recorded {
+            NonceInsert::Recorded => {}
+            NonceInsert::Replayed => return Err(SignatureError::ReplayedNonce.into()),
+            NonceInsert::Full { retry_at } => {
+                warn!("Nonce store full; refusing signed requests. Raise auth.request_signing.nonce_capacity");
+                return Err(AppError::RateLimit(retry_at.saturating_sub(now).max(1) as u64));
+            }
+        }
+
+        Ok(SignedRequest {
+            key_id: key_id.to_string(),
+        })
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<&'a str, SignatureError> {
+    headers
+        .get(name)
+        .ok_or(SignatureError::Missing(name))?
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| SignatureError::Malformed(name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceInsert {
+    Recorded,
+    /// Already recorded and unexpired.
+    Replayed,
+    /// No room before unix time `retry_at`, when the oldest nonce expires.
+    /// Forgetting an unexpired nonce instead would reopen its replay window.
+    Full { retry_at: i64 },
+}
+
+/// Where verified nonces are remembered until their request would be
+/// rejected as stale anyway.
+#[async_trait]
+pub trait NonceStore: Send + Sync {
+    /// Records `nonce` until unix time `expires_at`, unless it is already
+    /// recorded and unexpired at `now`.
+    async fn insert(&self, nonce: &str, expires_at: i64, now: i64) -> Result<NonceInsert>;
+}
+
+/// Nonces seen by this instance only.
+pub struct MemoryNonceStore {
+    nonces: Mutex<NonceRing>,
+}
+
+impl MemoryNonceStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            nonces: Mutex::new(NonceRing::new(capacity)),
+        }
+    }
+}
+
+#[async_trait]
+impl NonceStore for MemoryNonceStore {
+    async fn insert(&self, nonce: &str, expires_at: i64, now: i64) -> Result<NonceInsert> {
+        let mut nonces = self.nonces.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(nonces.insert(nonce.to_string(), expires_at, now))
+    }
+}
+
+/// Nonces shared by every instance in the `request_nonces` table.
+pub struct PgNonceStore {
+    pool: PgPool,
+    inserts: AtomicU64,
+}
+
+impl PgNonceStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            inserts: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl NonceStore for PgNonceStore {
+    async fn insert(&self, nonce: &str, expires_at: i64, now: i64) -> Result<NonceInsert> {
+        if self.inserts.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+            sqlx::query("DELETE FROM request_nonces WHERE expires_at < to_timestamp($1)")
+                .bind(now as f64)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        // An expired row is taken over; a live one makes the upsert return
+        // nothing.
+        let recorded = sqlx::query(
+            "INSERT INTO request_nonces AS n (nonce, expires_at) VALUES ($1, to_timestamp($2))
+             ON CONFLICT (nonce) DO UPDATE SET expires_at = EXCLUDED.expires_at
+                 WHERE n.expires_at < to_timestamp($3)
+             RETURNING 1",
+        )
+        .bind(nonce)
+        .bind(expires_at as f64)
+        .bind(now as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(match recorded {
+            Some(_) => NonceInsert::Recorded,
+            None => NonceInsert::Replayed,
+        })
+    }
+}
+
+/// Seen nonces with their expiry, bounded by `capacity`. Entries leave in
+/// insertion order once expired; since every entry lives for the same
+/// window that is also expiry order, up to clock skew between clients.
+struct NonceRing {
+    seen: HashMap<String, i64>,
+    order: VecDeque<(String, i64)>,
+    capacity: usize,
+}
+
+impl NonceRing {
+    fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn insert(&mut self, nonce: String, expires_at: i64, now: i64) -> NonceInsert {
+        while let Some((_, expiry)) = self.order.front() {
+            if *expiry >= now {
+                break;
+            }
+            self.evict_front();
+        }
+
+        if self.seen.get(&nonce).is_some_and(|expiry| *expiry >= now) {
+            return NonceInsert::Replayed;
+        }
+
+        if self.order.len() >= self.capacity {
+            let retry_at = self.order.front().map_or(now, |(_, expiry)| expiry.saturating_add(1));
+            return NonceInsert::Full { retry_at };
+        }
+        self.seen.insert(nonce.clone(), expires_at);
+        self.order.push_back((nonce, expires_at));
+        NonceInsert::Recorded
+    }
+
+    fn evict_front(&mut self) {
+        if let Some((nonce, expiry)) = self.order.pop_front() {
+            // Only drop the map entry if it belongs to this queue slot.
+            if self.seen.get(&nonce) == Some(&expiry) {
+                self.seen.remove(&nonce);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const KEY_ID: &str = "scraper";
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+    const SKEW: i64 = 300;
+    const NOW: i64 = 1_700_000_000;
+
+    fn verifier(nonce_capacity: usize) -> RequestVerifier {
+        RequestVerifier {
+            secrets: Arc::new(HashMap::from([(KEY_ID.to_string(), Secret::new(SECRET.to_string()))])),
+            max_clock_skew_secs: SKEW,
+            require_for_api_keys: false,
+            max_body_bytes: 1024,
+            nonces: Arc::new(MemoryNonceStore::new(nonce_capacity)),
+        }
+    }
+
+    fn signed_headers(timestamp: i64, nonce: &str, body: &[u8]) -> HeaderMap {
+        let canonical = canonical_request("post", "/api/v1/ingest/asset?dry_run=1", timestamp, nonce, body);
+        let mut headers = HeaderMap::new();
+        headers.insert(KEY_ID_HEADER, HeaderValue::from_static(KEY_ID));
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from_str(&timestamp.to_string()).unwrap());
+        headers.insert(NONCE_HEADER, HeaderValue::from_str(nonce).unwrap());
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&sign(SECRET, &canonical)).unwrap());
+        headers
+    }
+
+    async fn verify(verifier: &RequestVerifier, headers: &HeaderMap, body: &[u8]) -> Result<SignedRequest, AppError> {
+        verifier
+            .verify("POST", "/api/v1/ingest/asset?dry_run=1", headers, body, NOW)
+            .await
+    }
+
+    fn is_refused(result: Result<SignedRequest, AppError>, reason: SignatureError) -> bool {
+        matches!(result, Err(AppError::Authentication(message)) if message == reason.to_string())
+    }
+
+    #[test]
+    fn canonical_request_joins_the_signed_parts() {
+        assert_eq!(
+            canonical_request("get", "/api/v1/users?page=2", 1_700_000_000, "nonce-0123456789", b""),
+            "GET\n/api/v1/users?page=2\n1700000000\nnonce-0123456789\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn sign_is_hex_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn accepts_a_signed_request() {
+        let body = br#"{"symbol":"AAPL"}"#;
+        let signed = verify(&verifier(16), &signed_headers(NOW, "nonce-0000000001", body), body).await.unwrap();
+        assert_eq!(signed.key_id, KEY_ID);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_tampered_body() {
+        let headers = signed_headers(NOW, "nonce-0000000001", b"{}");
+        assert!(is_refused(verify(&verifier(16), &headers, b"{ }").await, SignatureError::Mismatch));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_unknown_key() {
+        let mut headers = signed_headers(NOW, "nonce-0000000001", b"");
+        headers.insert(KEY_ID_HEADER, HeaderValue::from_static("someone-else"));
+        assert!(is_refused(verify(&verifier(16), &headers, b"").await, SignatureError::UnknownKey));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_replayed_nonce() {
+        let verifier = verifier(16);
+        let headers = signed_headers(NOW, "nonce-0000000001", b"");
+        assert!(verify(&verifier, &headers, b"").await.is_ok());
+        assert!(is_refused(verify(&verifier, &headers, b"").await, SignatureError::ReplayedNonce));
+    }
+
+    #[tokio::test]
+    async fn checks_the_timestamp_against_the_clock_skew() {
+        let verifier = verifier(16);
+        for (i, timestamp) in [NOW - SKEW, NOW + SKEW].into_iter().enumerate() {
+            let headers = signed_headers(timestamp, &format!("nonce-edge-{:06}", i), b"");
+            assert!(verify(&verifier, &headers, b"").await.is_ok(), "timestamp {}", timestamp);
+        }
+        for (i, timestamp) in [NOW - SKEW - 1, NOW + SKEW + 1, i64::MIN, i64::MAX].into_iter().enumerate() {
+            let headers = signed_headers(timestamp, &format!("nonce-stale-{:05}", i), b"");
+            assert!(
+                is_refused(verify(&verifier, &headers, b"").await, SignatureError::StaleTimestamp),
+                "timestamp {}",
+                timestamp
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_with_a_rate_limit_while_the_nonce_store_is_full() {
+        let verifier = verifier(1);
+        assert!(verify(&verifier, &signed_headers(NOW, "nonce-0000000001", b""), b"").await.is_ok());
+        let result = verify(&verifier, &signed_headers(NOW, "nonce-0000000002", b""), b"").await;
+        assert!(matches!(result, Err(AppError::RateLimit(secs)) if secs == SKEW as u64 + 1));
+    }
+
+    #[test]
+    fn nonce_ring_forgets_only_expired_nonces() {
+        let mut ring = NonceRing::new(2);
+        assert_eq!(ring.insert("a".to_string(), 10, 0), NonceInsert::Recorded);
+        assert_eq!(ring.insert("a".to_string(), 10, 5), NonceInsert::Replayed);
+        assert_eq!(ring.insert("b".to_string(), 20, 5), NonceInsert::Recorded);
+        assert_eq!(ring.insert("c".to_string(), 20, 5), NonceInsert::Full { retry_at: 11 });
+        // Still remembered through its expiry second.
+        assert_eq!(ring.insert("a".to_string(), 20, 10), NonceInsert::Replayed);
+
+        assert_eq!(ring.insert("c".to_string(), 30, 11), NonceInsert::Recorded);
+        assert_eq!(ring.insert("a".to_string(), 30, 11), NonceInsert::Full { retry_at: 21 });
+        assert_eq!(ring.insert("b".to_string(), 30, 11), NonceInsert::Replayed);
+    }
+}
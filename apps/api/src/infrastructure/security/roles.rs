@@ -0,0 +1,65 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct RoleStore {
+    pool: PgPool,
+}
+
+impl RoleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<Role>> {
+        Ok(sqlx::query_as("SELECT name, description, created_at FROM roles ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    pub async fn roles_for(&self, user_id: &str) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar("SELECT role FROM user_roles WHERE user_id = $1 ORDER BY role")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    /// Returns `false` when the user already had the role.
+    pub async fn grant(&self, user_id: &str, role: &str) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1)")
+            .bind(role)
+            .fetch_one(&self.pool)
+            .await?;
+        if !exists {
+            bail!("unknown role `{}`", role);
+        }
+
+        let result = sqlx::query(
+            "INSERT INTO user_roles (user_id, role) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(role)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Returns `false` when the user did not have the role.
+    pub async fn revoke(&self, user_id: &str, role: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM user_roles WHERE user_id = $1 AND role = $2")
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
+    }
+}
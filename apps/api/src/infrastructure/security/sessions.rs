@@ -0,0 +1,225 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::infrastructure::database::connection::DatabasePool;
+
+/// Longest stored device label and user agent; longer values are cut.
+pub const MAX_DEVICE_LABEL_LENGTH: usize = 100;
+const MAX_USER_AGENT_LENGTH: usize = 512;
+/// Entries the status cache holds before sweeping out stale ones.
+const STATUS_CACHE_SWEEP_AT: usize = 10_000;
+
+/// Where a login or refresh came from.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(device_label: Option<&str>, user_agent: Option<&str>, ip_address: Option<String>) -> Self {
+        Self {
+            device_label: device_label.and_then(|label| truncated(label, MAX_DEVICE_LABEL_LENGTH)),
+            user_agent: user_agent.and_then(|agent| truncated(agent, MAX_USER_AGENT_LENGTH)),
+            ip_address,
+        }
+    }
+}
+
+fn truncated(value: &str, max_chars: usize) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.chars().take(max_chars).collect())
+}
+
+/// A login. Every refresh token issued for it belongs to the session, so
+/// revoking the session logs that device out.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Session {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_reason: Option<String>,
+    /// Established with a second factor.
+    pub mfa_verified: bool,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl Session {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+pub(super) const SESSION_COLUMNS: &str = "id, user_id, created_at, last_seen_at, expires_at, revoked_at, \
+     revoked_reason, mfa_verified, device_label, user_agent, ip_address";
+
+#[derive(Clone)]
+pub struct SessionStore {
+    pool: DatabasePool,
+}
+
+impl SessionStore {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// Sessions for `user_id`, newest first. Revoked and expired sessions are
+    /// only included with `include_inactive`.
+    pub async fn list_for_user(&self, user_id: &str, include_inactive: bool) -> Result<Vec<Session>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {} FROM auth_sessions
+             WHERE user_id = $1 AND ($2 OR (revoked_at IS NULL AND expires_at > now()))
+             ORDER BY created_at DESC",
+            SESSION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(include_inactive)
+        .fetch_all(self.pool.read())
+        .await?)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Session>> {
+        Ok(sqlx::query_as(&format!("SELECT {} FROM auth_sessions WHERE id = $1", SESSION_COLUMNS))
+            .bind(id)
+            .fetch_optional(self.pool.read())
+            .await?)
+    }
+
+    /// Returns `false` when the session does not exist or was already revoked.
+    pub async fn revoke(&self, id: &str, reason: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = $2
+             WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .bind(reason)
+        .execute(self.pool.write())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Revokes `id` only if it belongs to `user_id`.
+    pub async fn revoke_owned(&self, user_id: &str, id: &str, reason: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = $3
+             WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(reason)
+        .execute(self.pool.write())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Revokes every live session of `user_id` except `keep`, returning the
+    /// revoked ids.
+    pub async fn revoke_all_for_user(&self, user_id: &str, keep: Option<&str>, reason: &str) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            "UPDATE auth_sessions SET revoked_at = now(), revoked_reason = $3
+             WHERE user_id = $1 AND revoked_at IS NULL AND ($2::text IS NULL OR id <> $2)
+             RETURNING id",
+        )
+        .bind(user_id)
+        .bind(keep)
+        .bind(reason)
+        .fetch_all(self.pool.write())
+        .await?)
+    }
+
+    /// Renames a live session of `user_id`. Returns `None` if there is none.
+    pub async fn set_device_label(&self, user_id: &str, id: &str, label: Option<&str>) -> Result<Option<Session>> {
+        Ok(sqlx::query_as(&format!(
+            "UPDATE auth_sessions SET device_label = $3
+             WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > now()
+             RETURNING {}",
+            SESSION_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .bind(label)
+        .fetch_optional(self.pool.write())
+        .await?)
+    }
+
+    /// Records activity on a session and reports whether it is still live.
+    pub async fn touch(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE auth_sessions SET last_seen_at = now()
+             WHERE id = $1 AND revoked_at IS NULL AND expires_at > now()",
+        )
+        .bind(id)
+        .execute(self.pool.write())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+/// Short-lived memory of whether sessions are live, consulted on every
+/// access-token check. Revocations made through this instance are denied
+/// immediately; those made elsewhere (another instance, the CLI) take
+/// effect once the cached entry is older than `ttl`. A zero `ttl` checks
+/// the database on every request.
+#[derive(Clone)]
+pub struct SessionStatusCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (bool, Instant)>>>,
+}
+
+impl SessionStatusCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Whether `session_id` is live, from cache when fresh or else from
+    /// the database (which also records the activity).
+    pub async fn is_active(&self, store: &SessionStore, session_id: &str) -> Result<bool> {
+        if let Some(active) = self.cached(session_id) {
+            return Ok(active);
+        }
+        let active = store.touch(session_id).await?;
+        self.record(session_id, active);
+        Ok(active)
+    }
+
+    /// Denylists sessions revoked by this instance.
+    pub fn deny<'a>(&self, session_ids: impl IntoIterator<Item = &'a String>) {
+        for session_id in session_ids {
+            self.record(session_id, false);
+        }
+    }
+
+    fn cached(&self, session_id: &str) -> Option<bool> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(session_id)
+            .filter(|(_, checked_at)| checked_at.elapsed() < self.ttl)
+            .map(|(active, _)| *active)
+    }
+
+    fn record(&self, session_id: &str, active: bool) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= STATUS_CACHE_SWEEP_AT {
+            // Stale entries would be rechecked against the database anyway.
+            entries.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+        }
+        entries.insert(session_id.to_string(), (active, Instant::now()));
+    }
+}
@@ -0,0 +1,293 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use rsa::pkcs8::{EncodePrivateKey as _, LineEnding};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::common::security::generate_random_token;
+use crate::config::auth::AuthConfig;
+use crate::config::Secret;
+
+const HS256_SECRET_LENGTH: usize = 64;
+const RSA_KEY_BITS: usize = 2048;
+
+/// `HS256` keys are shared secrets; `RS256` and `EdDSA` keys are stored as
+/// PKCS#8 PEM private keys and their public halves are published as JWKS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum KeyAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+impl KeyAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::HS256 => "HS256",
+            KeyAlgorithm::RS256 => "RS256",
+            KeyAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    pub fn is_asymmetric(&self) -> bool {
+        !matches!(self, KeyAlgorithm::HS256)
+    }
+
+    /// Fresh key material in the form stored in `signing_keys.secret`.
+    fn generate(&self) -> Result<String> {
+        let mut rng = rand::rngs::OsRng;
+        Ok(match self {
+            KeyAlgorithm::HS256 => generate_random_token(HS256_SECRET_LENGTH),
+            KeyAlgorithm::RS256 => rsa::RsaPrivateKey::new(&mut rng, RSA_KEY_BITS)?
+                .to_pkcs8_pem(LineEnding::LF)?
+                .to_string(),
+            KeyAlgorithm::EdDSA => ed25519_dalek::SigningKey::generate(&mut rng)
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| anyhow!("encoding Ed25519 key: {}", e))?
+                .to_string(),
+        })
+    }
+}
+
+impl FromStr for KeyAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "HS256" => Ok(KeyAlgorithm::HS256),
+            "RS256" => Ok(KeyAlgorithm::RS256),
+            "EdDSA" => Ok(KeyAlgorithm::EdDSA),
+            other => bail!("unsupported signing algorithm `{}` (expected HS256, RS256 or EdDSA)", other),
+        }
+    }
+}
+
+impl fmt::Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyState {
+    /// Signs new tokens. At most one key is active.
+    Active,
+    /// No longer signs, but tokens it signed are still accepted.
+    VerifyOnly,
+    Retired,
+}
+
+impl KeyState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyState::Active => "active",
+            KeyState::VerifyOnly => "verify_only",
+            KeyState::Retired => "retired",
+        }
+    }
+}
+
+impl FromStr for KeyState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "active" => Ok(KeyState::Active),
+            "verify_only" => Ok(KeyState::VerifyOnly),
+            "retired" => Ok(KeyState::Retired),
+            other => bail!("unknown signing key state `{}`", other),
+        }
+    }
+}
+
+impl fmt::Display for KeyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: KeyAlgorithm,
+    pub state: KeyState,
+    #[serde(skip)]
+    pub secret: Secret<String>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct SigningKeyRow {
+    kid: String,
+    algorithm: String,
+    state: String,
+    secret: String,
+    created_at: DateTime<Utc>,
+    rotated_at: Option<DateTime<Utc>>,
+    retired_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<SigningKeyRow> for SigningKey {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SigningKeyRow) -> Result<Self> {
+        Ok(SigningKey {
+            state: row.state.parse()?,
+            algorithm: row.algorithm.parse()?,
+            kid: row.kid,
+            secret: Secret::new(row.secret),
+            created_at: row.created_at,
+            rotated_at: row.rotated_at,
+            retired_at: row.retired_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Rotation {
+    pub activated: String,
+    pub demoted: Option<String>,
+    pub retired: Vec<String>,
+}
+
+/// How long a demoted key keeps verifying: long enough for every access
+/// token it signed to expire, counting tokens other instances signed with
+/// it until their next key refresh noticed the rotation.
+pub fn default_verify_grace(config: &AuthConfig) -> Duration {
+    Duration::from_std(config.access_token_ttl + config.key_refresh_interval).unwrap_or(Duration::MAX)
+}
+
+#[derive(Clone)]
+pub struct SigningKeyStore {
+    pool: PgPool,
+}
+
+impl SigningKeyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<SigningKey>> {
+        sqlx::query_as::<_, SigningKeyRow>(
+            "SELECT kid, algorithm, state, secret, created_at, rotated_at, retired_at
+             FROM signing_keys ORDER BY created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(SigningKey::try_from)
+        .collect()
+    }
+
+    /// Creates the first signing key on a fresh database. Safe to race: when
+    /// another instance wins, its key is used.
+    pub async fn ensure_active(&self, algorithm: KeyAlgorithm, verify_grace: Duration) -> Result<()> {
+        let has_active: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM signing_keys WHERE state = 'active')")
+                .fetch_one(&self.pool)
+                .await?;
+        if has_active {
+            return Ok(());
+        }
+
+        match self.rotate(verify_grace, algorithm).await {
+            Ok(rotation) => {
+                tracing::info!(kid = %rotation.activated, "Created initial token signing key");
+                Ok(())
+            }
+            Err(e) if is_unique_violation(&e) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Activates a new key, demotes the current one to verify-only and retires
+    /// verify-only keys demoted more than `verify_grace` ago.
+    pub async fn rotate(&self, verify_grace: Duration, algorithm: KeyAlgorithm) -> Result<Rotation> {
+        // RSA generation takes a while; do it before taking the table lock.
+        let material = tokio::task::spawn_blocking(move || algorithm.generate()).await??;
+
+        let mut tx = self.pool.begin().await?;
+        // Serialises concurrent rotations on the single-active index.
+        sqlx::query("LOCK TABLE signing_keys IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        let retired: Vec<String> = sqlx::query_scalar(
+            "UPDATE signing_keys SET state = 'retired', retired_at = now()
+             WHERE state = 'verify_only' AND rotated_at < $1
+             RETURNING kid",
+        )
+        .bind(Utc::now() - verify_grace)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let demoted: Option<String> = sqlx::query_scalar(
+            "UPDATE signing_keys SET state = 'verify_only', rotated_at = now()
+             WHERE state = 'active'
+             RETURNING kid",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let activated = cuid::cuid2();
+        sqlx::query(
+            "INSERT INTO signing_keys (kid, algorithm, secret, state) VALUES ($1, $2, $3, 'active')",
+        )
+        .bind(&activated)
+        .bind(algorithm.as_str())
+        .bind(material)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Rotation {
+            activated,
+            demoted,
+            retired,
+        })
+    }
+
+    /// Age of the active key, for scheduled rotation.
+    pub async fn active_since(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(
+            sqlx::query_scalar("SELECT created_at FROM signing_keys WHERE state = 'active'")
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Stops a verify-only key from verifying before its grace period ends,
+    /// e.g. after a compromise. The active key must be rotated away first.
+    pub async fn retire(&self, kid: &str) -> Result<Option<KeyState>> {
+        let state: Option<String> = sqlx::query_scalar("SELECT state FROM signing_keys WHERE kid = $1")
+            .bind(kid)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(state) = state.map(|state| state.parse::<KeyState>()).transpose()? else {
+            return Ok(None);
+        };
+        if state == KeyState::Active {
+            bail!("key {} is active; rotate before retiring it", kid);
+        }
+        sqlx::query(
+            "UPDATE signing_keys SET state = 'retired', retired_at = now()
+             WHERE kid = $1 AND state <> 'retired'",
+        )
+        .bind(kid)
+        .execute(&self.pool)
+        .await?;
+        Ok(Some(state))
+    }
+}
+
+fn is_unique_violation(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<sqlx::Error>(),
+        Some(sqlx::Error::Database(db)) if db.is_unique_violation()
+    )
+}
@@ -0,0 +1,160 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+/// RFC 6238 parameters understood by every authenticator app.
+pub const DIGITS: u32 = 6;
+pub const STEP_SECS: i64 = 30;
+const SECRET_BYTES: usize = 20;
+/// Codes from one step either side are accepted to absorb clock drift.
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// A fresh base32 secret as shown to the user during enrollment.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+pub fn step_at(unix_time: i64) -> i64 {
+    unix_time.div_euclid(STEP_SECS)
+}
+
+/// The code for `step`, or `None` if `secret` is not valid base32.
+pub fn code_at(secret: &str, step: i64) -> Option<String> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let mut mac = HmacSha1::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+        & 0x7fff_ffff;
+    Some(format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize))
+}
+
+/// Returns the matched step when `code` is valid at `unix_time`. Steps at
+/// or before `last_used_step` are skipped so a code cannot be replayed.
+pub fn verify(secret: &str, code: &str, unix_time: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = step_at(unix_time);
+    (current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(secret, *step).is_some_and(|expected| constant_time_eq(&expected, &code)))
+}
+
+/// `otpauth://` URI for QR codes, per the Key Uri Format used by
+/// authenticator apps.
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        encode_component(issuer),
+        encode_component(account),
+        secret,
+        encode_component(issuer),
+        DIGITS,
+        STEP_SECS
+    )
+}
+
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The RFC 6238 SHA-1 seed, ASCII "12345678901234567890", in base32.
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    /// RFC 6238 Appendix B, SHA-1 rows, truncated to six digits.
+    const RFC_VECTORS: &[(i64, &str)] = &[
+        (59, "287082"),
+        (1_111_111_109, "081804"),
+        (1_111_111_111, "050471"),
+        (1_234_567_890, "005924"),
+        (2_000_000_000, "279037"),
+        (20_000_000_000, "353130"),
+    ];
+
+    #[test]
+    fn matches_the_rfc_6238_test_vectors() {
+        assert_eq!(BASE32_NOPAD.encode(b"12345678901234567890"), RFC_SECRET);
+        for &(time, code) in RFC_VECTORS {
+            assert_eq!(code_at(RFC_SECRET, step_at(time)).as_deref(), Some(code), "T = {}", time);
+            assert_eq!(verify(RFC_SECRET, code, time, None), Some(step_at(time)), "T = {}", time);
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_of_drift_either_way() {
+        let (time, code) = (1_111_111_111, "050471");
+        let step = step_at(time);
+        assert_eq!(verify(RFC_SECRET, code, time - STEP_SECS, None), Some(step));
+        assert_eq!(verify(RFC_SECRET, code, time + STEP_SECS, None), Some(step));
+        assert_eq!(verify(RFC_SECRET, code, time - 2 * STEP_SECS, None), None);
+        assert_eq!(verify(RFC_SECRET, code, time + 2 * STEP_SECS, None), None);
+    }
+
+    #[test]
+    fn refuses_codes_at_or_before_the_last_used_step() {
+        let (time, code) = (1_234_567_890, "005924");
+        let step = step_at(time);
+        assert_eq!(verify(RFC_SECRET, code, time, Some(step - 1)), Some(step));
+        assert_eq!(verify(RFC_SECRET, code, time, Some(step)), None);
+        assert_eq!(verify(RFC_SECRET, code, time, Some(step + 1)), None);
+    }
+
+    #[test]
+    fn checks_the_code_format() {
+        let time = 1_234_567_890;
+        assert_eq!(verify(RFC_SECRET, " 005 924 ", time, None), Some(step_at(time)));
+        for code in ["", "05924", "0005924", "00592a", "005924\u{0}"] {
+            assert_eq!(verify(RFC_SECRET, code, time, None), None, "code {:?}", code);
+        }
+        assert_eq!(code_at("not base32!", 0), None);
+        assert_eq!(verify("not base32!", "005924", time, None), None);
+    }
+
+    #[test]
+    fn steps_round_down() {
+        assert_eq!(step_at(0), 0);
+        assert_eq!(step_at(29), 0);
+        assert_eq!(step_at(30), 1);
+        assert_eq!(step_at(-1), -1);
+    }
+
+    #[test]
+    fn generated_secrets_are_twenty_random_bytes() {
+        let secret = generate_secret();
+        assert_eq!(BASE32_NOPAD.decode(secret.as_bytes()).unwrap().len(), SECRET_BYTES);
+        assert_ne!(generate_secret(), secret);
+    }
+
+    #[test]
+    fn provisioning_uri_escapes_the_label() {
+        assert_eq!(
+            provisioning_uri("M5 Markets", "alice+mfa@example.com", RFC_SECRET),
+            "otpauth://totp/M5%20Markets:alice%2Bmfa%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+             &issuer=M5%20Markets&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
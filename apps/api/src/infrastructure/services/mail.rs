@@ -0,0 +1,98 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+/// A plain-text message to one recipient.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<()>;
+}
+
+/// Writes each message to the log instead of delivering it; for local
+/// development and CI.
+pub struct LogMailer {
+    from_email: String,
+}
+
+impl LogMailer {
+    pub fn new(from_email: &str) -> Self {
+        Self {
+            from_email: from_email.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        tracing::info!(
+            from = %self.from_email,
+            to = %email.to,
+            subject = %email.subject,
+            body = %email.body,
+            "Mail (log transport)"
+        );
+        Ok(())
+    }
+}
+
+/// Delivers into a Maildir (`tmp`, `new`, `cur`), which mail clients and
+/// test harnesses can read without an SMTP server. Messages are written to
+/// `tmp` and renamed into `new`, so a reader never sees a partial message.
+pub struct MaildirMailer {
+    path: PathBuf,
+    from_email: String,
+}
+
+impl MaildirMailer {
+    /// Creates the Maildir if it does not exist.
+    pub async fn open(path: &Path, from_email: &str) -> Result<Self> {
+        for dir in ["tmp", "new", "cur"] {
+            tokio::fs::create_dir_all(path.join(dir))
+                .await
+                .with_context(|| format!("creating maildir {}", path.display()))?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            from_email: from_email.to_string(),
+        })
+    }
+
+    fn render(&self, email: &Email, id: &str) -> String {
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@m5>\r\n\
+             MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 8bit\r\n\r\n{}\r\n",
+            self.from_email,
+            email.to,
+            email.subject,
+            Utc::now().to_rfc2822(),
+            id,
+            email.body.replace("\r\n", "\n").replace('\n', "\r\n"),
+        )
+    }
+}
+
+#[async_trait]
+impl Mailer for MaildirMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        // A line break in a header would let the value inject headers.
+        if [&email.to, &email.subject].iter().any(|value| value.contains(['\r', '\n'])) {
+            bail!("mail headers must not contain line breaks");
+        }
+        let id = cuid::cuid2();
+        let name = format!("{}.{}.m5", Utc::now().timestamp(), id);
+        let staging = self.path.join("tmp").join(&name);
+        tokio::fs::write(&staging, self.render(email, &id)).await?;
+        tokio::fs::rename(&staging, self.path.join("new").join(&name)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+pub mod mail;
+pub mod storage;
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::common::errors::AppError;
+use crate::config::services::{MailConfig, ServicesConfig, StorageConfig};
+use mail::{LogMailer, MaildirMailer, Mailer};
+use storage::{FilesystemStorage, Storage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Service {
+    Storage,
+    Mail,
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Service::Storage => f.write_str("storage"),
+            Service::Mail => f.write_str("mail"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+    pub service: Service,
+    pub enabled: bool,
+    pub backend: &'static str,
+}
+
+/// Which optional external services this process was configured with.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub services: Vec<Capability>,
+}
+
+impl Capabilities {
+    pub fn from_config(config: &ServicesConfig) -> Self {
+        let storage = config.storage.backend();
+        let mail = config.mail.transport();
+
+        Self {
+            services: vec![
+                Capability {
+                    service: Service::Storage,
+                    enabled: storage != "disabled",
+                    backend: storage,
+                },
+                Capability {
+                    service: Service::Mail,
+                    enabled: mail != "disabled",
+                    backend: mail,
+                },
+            ],
+        }
+    }
+
+    pub fn get(&self, service: Service) -> Option<&Capability> {
+        self.services.iter().find(|c| c.service == service)
+    }
+
+    pub fn is_enabled(&self, service: Service) -> bool {
+        self.get(service).is_some_and(|c| c.enabled)
+    }
+
+    /// Guard for handlers that cannot work without `service`.
+    pub fn require(&self, service: Service) -> Result<&Capability, AppError> {
+        match self.get(service) {
+            Some(capability) if capability.enabled => Ok(capability),
+            _ => Err(AppError::ServiceUnavailable(service.to_string())),
+        }
+    }
+}
+
+/// Adapters for the optional services, built from the same configuration as
+/// [`Capabilities`]. A disabled service has no adapter, and asking for it
+/// yields [`AppError::ServiceUnavailable`].
+#[derive(Clone, Default)]
+pub struct Services {
+    storage: Option<Arc<dyn Storage>>,
+    mail: Option<Arc<dyn Mailer>>,
+}
+
+impl Services {
+    /// Fails for a backend this build has no adapter for, rather than
+    /// reporting a service as enabled that cannot do anything.
+    pub async fn from_config(config: &ServicesConfig) -> anyhow::Result<Self> {
+        let storage: Option<Arc<dyn Storage>> = match &config.storage {
+            StorageConfig::Disabled => None,
+            StorageConfig::Filesystem { root } => Some(Arc::new(FilesystemStorage::open(root).await?)),
+            StorageConfig::S3(_) => anyhow::bail!(
+                "services.storage.backend `s3` is not supported by this build; use `filesystem` or `disabled`"
+            ),
+        };
+        let mail: Option<Arc<dyn Mailer>> = match &config.mail {
+            MailConfig::Disabled => None,
+            MailConfig::Log { from_email } => Some(Arc::new(LogMailer::new(from_email))),
+            MailConfig::Maildir { path, from_email } => Some(Arc::new(MaildirMailer::open(path, from_email).await?)),
+            MailConfig::Smtp(_) => anyhow::bail!(
+                "services.mail.transport `smtp` is not supported by this build; use `log`, `maildir` or `disabled`"
+            ),
+        };
+        Ok(Self { storage, mail })
+    }
+
+    pub fn storage(&self) -> Result<Arc<dyn Storage>, AppError> {
+        self.storage
+            .clone()
+            .ok_or_else(|| AppError::ServiceUnavailable(Service::Storage.to_string()))
+    }
+
+    pub fn mail(&self) -> Result<Arc<dyn Mailer>, AppError> {
+        self.mail
+            .clone()
+            .ok_or_else(|| AppError::ServiceUnavailable(Service::Mail.to_string()))
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// Blob storage addressed by slash-separated keys such as
+/// `exports/2026/report.csv`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores `bytes` under `key`, replacing any existing object.
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Returns whether an object was removed.
+    async fn delete(&self, key: &str) -> Result<bool>;
+}
+
+/// Objects as files under a root directory. Writes go through a temporary
+/// file and a rename, so readers never see a partial object.
+pub struct FilesystemStorage {
+    root: PathBuf,
+}
+
+impl FilesystemStorage {
+    /// Creates `root` if it does not exist, so a path the process cannot
+    /// write to fails at boot rather than on the first upload.
+    pub async fn open(root: &Path) -> Result<Self> {
+        tokio::fs::create_dir_all(root)
+            .await
+            .with_context(|| format!("creating storage root {}", root.display()))?;
+        Ok(Self {
+            root: root.to_path_buf(),
+        })
+    }
+
+    /// Keys are relative paths of normal components only; `..`, absolute
+    /// paths and empty segments would escape or alias the root.
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty()
+            || key.contains('\\')
+            || key.split('/').any(str::is_empty)
+            || !relative.components().all(|c| matches!(c, Component::Normal(_)))
+        {
+            bail!("invalid storage key `{}`", key);
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for FilesystemStorage {
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let staging = path.with_file_name(format!(".{}.tmp", cuid::cuid2()));
+        tokio::fs::write(&staging, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&staging, &path).await {
+            let _ = tokio::fs::remove_file(&staging).await;
+            return Err(e).with_context(|| format!("storing {}", key));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("reading {}", key)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("deleting {}", key)),
+        }
+    }
+}
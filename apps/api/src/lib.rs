@@ -6,7 +6,7 @@ pub mod features;
 pub mod api;
 pub mod infrastructure;
 
-pub use bootstrap::{AppState, init, shutdown_signal};
+pub use bootstrap::{AppState, init, serve, shutdown_signal};
 pub use config::Config;
 pub use common::constants;
 
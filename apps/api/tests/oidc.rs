@@ -0,0 +1,512 @@
+//! The OIDC sign-in flow against a stand-in provider: wiremock serves the
+//! discovery document, JWKS and token endpoint, and each test signs the ID
+//! token the token endpoint hands back. Every test gets a fresh database
+//! from `sqlx::test`, so `DATABASE_URL` must point at a Postgres server.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use chrono::Utc;
+use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+use ed25519_dalek::pkcs8::EncodePrivateKey as _;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use url::Url;
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Match, Mock, MockServer, Request, ResponseTemplate};
+
+use m5::application::command::CommandHandler;
+use m5::application::event::EventBus;
+use m5::common::errors::AppError;
+use m5::config::loader::LoadOptions;
+use m5::config::Config;
+use m5::features::auth::application::dtos::{
+    CompleteOidcLogin, OidcCallbackRequest, OidcCallbackResponse, StartOidcLogin, UnlinkIdentity,
+};
+use m5::features::auth::AuthModule;
+use m5::features::users::application::dtos::CreateUserRequest;
+use m5::features::users::UsersModule;
+use m5::infrastructure::database::connection::DatabasePool;
+use m5::infrastructure::security::audit::{AuditLog, AuditStore};
+use m5::infrastructure::security::encryption::Encryptor;
+use m5::infrastructure::security::jwt::TokenService;
+use m5::infrastructure::security::oidc::pkce_challenge;
+use m5::infrastructure::security::sessions::ClientInfo;
+use m5::infrastructure::security::signing_keys::SigningKeyStore;
+
+const PROVIDER: &str = "acme";
+const CLIENT_ID: &str = "m5-test-client";
+const KID: &str = "acme-key-1";
+
+/// A provider key the tests sign ID tokens with.
+struct ProviderKey {
+    encoding: EncodingKey,
+    jwk: Value,
+}
+
+impl ProviderKey {
+    fn generate() -> Self {
+        let key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let pem = key.to_pkcs8_pem(LineEnding::LF).expect("encoding test key");
+        Self {
+            encoding: EncodingKey::from_ed_pem(pem.as_bytes()).expect("loading test key"),
+            jwk: json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+                "kid": KID,
+                "alg": "EdDSA",
+                "use": "sig",
+            }),
+        }
+    }
+
+    fn sign(&self, claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(KID.to_string());
+        jsonwebtoken::encode(&header, claims, &self.encoding).expect("signing id token")
+    }
+}
+
+/// Matches a token request whose `code_verifier` hashes to `challenge`, as
+/// the provider checks it under RFC 7636 §4.6.
+struct PkceVerifier {
+    challenge: String,
+}
+
+impl Match for PkceVerifier {
+    fn matches(&self, request: &Request) -> bool {
+        let form: HashMap<String, String> = url::form_urlencoded::parse(&request.body).into_owned().collect();
+        form.get("grant_type").map(String::as_str) == Some("authorization_code")
+            && form
+                .get("code_verifier")
+                .is_some_and(|verifier| pkce_challenge(verifier) == self.challenge)
+    }
+}
+
+/// What the authorization URL asked the provider for.
+struct Authorization {
+    state: String,
+    nonce: String,
+    code_challenge: String,
+}
+
+struct Harness {
+    provider: MockServer,
+    key: ProviderKey,
+    auth: AuthModule,
+    users: UsersModule,
+    pool: PgPool,
+}
+
+impl Harness {
+    async fn start(pool: PgPool) -> Self {
+        let provider = MockServer::start().await;
+        let key = ProviderKey::generate();
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issuer": provider.uri(),
+                "authorization_endpoint": format!("{}/authorize", provider.uri()),
+                "token_endpoint": format!("{}/token", provider.uri()),
+                "jwks_uri": format!("{}/jwks", provider.uri()),
+            })))
+            .mount(&provider)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "keys": [key.jwk] })))
+            .mount(&provider)
+            .await;
+
+        let config = load_config(&provider.uri());
+        let encryptor = Encryptor::from_config(&config.encryption).expect("encryption keys");
+        let db_pool = DatabasePool::new(pool.clone());
+        let users = UsersModule::postgres(
+            db_pool.clone(),
+            &config.auth,
+            encryptor.clone(),
+            EventBus::new(),
+        )
+        .expect("users module");
+        let tokens = TokenService::load(SigningKeyStore::new(pool.clone()), &config.auth)
+            .await
+            .expect("signing keys");
+        let auth = AuthModule::postgres(
+            users.clone(),
+            tokens,
+            db_pool.clone(),
+            AuditLog::spawn(AuditStore::new(db_pool)),
+            encryptor,
+            None,
+            &config.auth,
+        )
+        .expect("auth module");
+
+        Self {
+            provider,
+            key,
+            auth,
+            users,
+            pool,
+        }
+    }
+
+    async fn create_user(&self, email: &str) -> String {
+        let user = self
+            .users
+            .create_user()
+            .handle(CreateUserRequest {
+                email: email.to_string(),
+                name: "Test User".to_string(),
+                password: "Correct-Horse-Battery-9".to_string(),
+            })
+            .await
+            .expect("creating user");
+        user.id
+    }
+
+    async fn authorize(&self, link_user_id: Option<&str>) -> Authorization {
+        let response = self
+            .auth
+            .start_oidc_login()
+            .handle(StartOidcLogin {
+                provider: PROVIDER.to_string(),
+                link_user_id: link_user_id.map(str::to_string),
+            })
+            .await
+            .expect("starting authorization");
+        let url = Url::parse(&response.authorization_url).expect("authorization url");
+        assert_eq!(url.path(), "/authorize");
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["client_id"], CLIENT_ID);
+        assert_eq!(query["code_challenge_method"], "S256");
+        Authorization {
+            state: query["state"].clone(),
+            nonce: query["nonce"].clone(),
+            code_challenge: query["code_challenge"].clone(),
+        }
+    }
+
+    /// Makes the token endpoint return `id_token`, once, to a request that
+    /// carries client credentials and the verifier for `authorization`.
+    async fn issue(&self, authorization: &Authorization, id_token: String) {
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(header_exists("authorization"))
+            .and(PkceVerifier {
+                challenge: authorization.code_challenge.clone(),
+            })
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "provider-access-token",
+                "token_type": "Bearer",
+                "id_token": id_token,
+            })))
+            .expect(1)
+            .mount(&self.provider)
+            .await;
+    }
+
+    fn claims(&self, authorization: &Authorization, subject: &str, email: &str, verified: bool) -> Value {
+        let now = Utc::now().timestamp();
+        json!({
+            "iss": self.provider.uri(),
+            "aud": CLIENT_ID,
+            "sub": subject,
+            "iat": now,
+            "exp": now + 300,
+            "nonce": authorization.nonce,
+            "email": email,
+            "email_verified": verified,
+        })
+    }
+
+    async fn callback(
+        &self,
+        authorization: &Authorization,
+        caller_id: Option<&str>,
+    ) -> Result<OidcCallbackResponse, AppError> {
+        self.auth
+            .complete_oidc_login()
+            .handle(CompleteOidcLogin {
+                provider: PROVIDER.to_string(),
+                caller_id: caller_id.map(str::to_string),
+                request: OidcCallbackRequest {
+                    code: "authorization-code".to_string().into(),
+                    state: authorization.state.clone().into(),
+                },
+                client: ClientInfo::new(None, Some("oidc-test"), None),
+            })
+            .await
+    }
+
+    /// Runs a sign-in for which the provider vouches for `subject` and
+    /// `email`.
+    async fn sign_in(&self, subject: &str, email: &str, verified: bool) -> Result<OidcCallbackResponse, AppError> {
+        let authorization = self.authorize(None).await;
+        let token = self.key.sign(&self.claims(&authorization, subject, email, verified));
+        self.issue(&authorization, token).await;
+        self.callback(&authorization, None).await
+    }
+
+    async fn linked_user(&self, subject: &str) -> Option<String> {
+        self.auth
+            .identities()
+            .find(PROVIDER, subject)
+            .await
+            .expect("reading identities")
+            .map(|identity| identity.user_id)
+    }
+}
+
+/// The repository's development config with one provider at `issuer`.
+fn load_config(issuer: &str) -> Config {
+    let file = std::env::temp_dir().join(format!("m5-oidc-test-{}.toml", cuid::cuid2()));
+    std::fs::write(
+        &file,
+        format!(
+            r#"
+            [[auth.oidc.providers]]
+            name = "{PROVIDER}"
+            display_name = "Acme"
+            issuer = "{issuer}"
+            client_id = "{CLIENT_ID}"
+            client_secret = "provider-client-secret"
+            redirect_uri = "http://localhost:5173/oidc/callback"
+            "#
+        ),
+    )
+    .expect("writing test config");
+    let config = Config::load_with(&LoadOptions {
+        config_dir: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config")),
+        profile: Some("development".to_string()),
+        config_file: Some(file.clone()),
+    });
+    let _ = std::fs::remove_file(&file);
+    config.expect("loading test config")
+}
+
+fn is_authentication_error(result: &Result<OidcCallbackResponse, AppError>) -> bool {
+    matches!(result, Err(AppError::Authentication(_)))
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn signs_in_by_verified_email_with_pkce(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    let user_id = harness.create_user("alice@example.com").await;
+
+    let response = harness.sign_in("alice-sub", "Alice@Example.com", true).await;
+
+    assert!(matches!(response, Ok(OidcCallbackResponse::Login(_))), "{:?}", response);
+    assert_eq!(harness.linked_user("alice-sub").await, Some(user_id));
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn sends_the_stored_code_verifier(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    let token = harness.key.sign(&harness.claims(&authorization, "alice-sub", "alice@example.com", true));
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(PkceVerifier {
+            challenge: authorization.code_challenge.clone(),
+        })
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id_token": token })))
+        .expect(0)
+        .mount(&harness.provider)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "error": "invalid_grant",
+            "error_description": "PKCE verification failed",
+        })))
+        .mount(&harness.provider)
+        .await;
+    // A verifier that does not match the challenge the provider was sent.
+    sqlx::query("UPDATE oidc_login_states SET code_verifier = $1")
+        .bind("x".repeat(64))
+        .execute(&harness.pool)
+        .await
+        .unwrap();
+
+    let response = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+    assert_eq!(harness.linked_user("alice-sub").await, None);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_an_unknown_state(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    let authorization = harness.authorize(None).await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&harness.provider)
+        .await;
+
+    let forged = Authorization {
+        state: "not-the-state-we-issued".to_string(),
+        ..authorization
+    };
+    let response = harness.callback(&forged, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn accepts_each_state_once(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    let token = harness.key.sign(&harness.claims(&authorization, "alice-sub", "alice@example.com", true));
+    harness.issue(&authorization, token).await;
+
+    assert!(harness.callback(&authorization, None).await.is_ok());
+    let replayed = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&replayed), "{:?}", replayed);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_a_nonce_mismatch(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    let mut claims = harness.claims(&authorization, "alice-sub", "alice@example.com", true);
+    claims["nonce"] = json!("nonce-from-another-request");
+    harness.issue(&authorization, harness.key.sign(&claims)).await;
+
+    let response = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+    assert_eq!(harness.linked_user("alice-sub").await, None);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_a_token_signed_with_another_key(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    // Same `kid` as the published key, different key material.
+    let forger = ProviderKey::generate();
+    let claims = harness.claims(&authorization, "alice-sub", "alice@example.com", true);
+    harness.issue(&authorization, forger.sign(&claims)).await;
+
+    let response = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+    assert_eq!(harness.linked_user("alice-sub").await, None);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_a_token_for_another_client(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    let mut claims = harness.claims(&authorization, "alice-sub", "alice@example.com", true);
+    claims["aud"] = json!("someone-elses-client");
+    harness.issue(&authorization, harness.key.sign(&claims)).await;
+
+    let response = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_a_shared_audience_authorized_for_another_client(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+    let authorization = harness.authorize(None).await;
+    let mut claims = harness.claims(&authorization, "alice-sub", "alice@example.com", true);
+    claims["aud"] = json!([CLIENT_ID, "someone-elses-client"]);
+    claims["azp"] = json!("someone-elses-client");
+    harness.issue(&authorization, harness.key.sign(&claims)).await;
+
+    let response = harness.callback(&authorization, None).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn does_not_link_by_an_unverified_email(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    harness.create_user("alice@example.com").await;
+
+    let response = harness.sign_in("mallory-sub", "alice@example.com", false).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+    assert_eq!(harness.linked_user("mallory-sub").await, None);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn links_signs_in_and_unlinks(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    let user_id = harness.create_user("alice@example.com").await;
+
+    // A signed-in user links an identity whose email differs from theirs.
+    let authorization = harness.authorize(Some(&user_id)).await;
+    let token = harness.key.sign(&harness.claims(&authorization, "alice-sub", "alice@work.example", false));
+    harness.issue(&authorization, token).await;
+    let linked = harness.callback(&authorization, Some(&user_id)).await;
+    match linked {
+        Ok(OidcCallbackResponse::Linked { linked }) => {
+            assert_eq!(linked.user_id, user_id);
+            assert_eq!(linked.email.as_deref(), Some("alice@work.example"));
+        }
+        other => panic!("expected a link, got {:?}", other),
+    }
+
+    // The link, not the email, now decides who signs in.
+    let response = harness.sign_in("alice-sub", "alice@work.example", false).await;
+    assert!(matches!(response, Ok(OidcCallbackResponse::Login(_))), "{:?}", response);
+
+    harness
+        .auth
+        .unlink_identity()
+        .handle(UnlinkIdentity {
+            user_id: user_id.clone(),
+            provider: PROVIDER.to_string(),
+        })
+        .await
+        .expect("unlinking");
+    assert_eq!(harness.linked_user("alice-sub").await, None);
+    let again = harness
+        .auth
+        .unlink_identity()
+        .handle(UnlinkIdentity {
+            user_id,
+            provider: PROVIDER.to_string(),
+        })
+        .await;
+    assert!(matches!(again, Err(AppError::NotFound(_))), "{:?}", again);
+
+    let response = harness.sign_in("alice-sub", "alice@work.example", false).await;
+    assert!(is_authentication_error(&response), "{:?}", response);
+}
+
+#[sqlx::test(migrator = "m5::infrastructure::database::migrations::MIGRATOR")]
+async fn rejects_a_link_completed_by_someone_else(pool: PgPool) {
+    let harness = Harness::start(pool).await;
+    let alice = harness.create_user("alice@example.com").await;
+    let mallory = harness.create_user("mallory@example.com").await;
+
+    let authorization = harness.authorize(Some(&mallory)).await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id_token": harness.key.sign(&harness.claims(&authorization, "alice-sub", "alice@example.com", true)),
+        })))
+        .mount(&harness.provider)
+        .await;
+
+    let response = harness.callback(&authorization, Some(&alice)).await;
+
+    assert!(is_authentication_error(&response), "{:?}", response);
+    assert_eq!(harness.linked_user("alice-sub").await, None);
+}
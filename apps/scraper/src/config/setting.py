@@ -7,7 +7,7 @@ load_dotenv(BASE_DIR / '.env')
 
 class Settings:
     # API Configuration
-    API_BASE_URL = os.getenv('API_BASE_URL', 'https://your-api.example.com')
+    API_BASE_URL = os.getenv('API_BASE_URL', 'https://your-api.example.com/api/v1/ingest')
     API_KEY = os.getenv('API_KEY', '')
     API_TIMEOUT = int(os.getenv('API_TIMEOUT', 30))
     